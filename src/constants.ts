@@ -111,31 +111,32 @@ export const WASI_ETXTBSY = 74;
 export const WASI_EXDEV = 75;
 export const WASI_ENOTCAPABLE = 76;
 
-export const WASI_SIGABRT = 0;
-export const WASI_SIGALRM = 1;
-export const WASI_SIGBUS = 2;
-export const WASI_SIGCHLD = 3;
-export const WASI_SIGCONT = 4;
-export const WASI_SIGFPE = 5;
-export const WASI_SIGHUP = 6;
-export const WASI_SIGILL = 7;
-export const WASI_SIGINT = 8;
+// signal numbers of wasi_snapshot_preview1
+export const WASI_SIGHUP = 1;
+export const WASI_SIGINT = 2;
+export const WASI_SIGQUIT = 3;
+export const WASI_SIGILL = 4;
+export const WASI_SIGTRAP = 5;
+export const WASI_SIGABRT = 6;
+export const WASI_SIGBUS = 7;
+export const WASI_SIGFPE = 8;
 export const WASI_SIGKILL = 9;
-export const WASI_SIGPIPE = 10;
-export const WASI_SIGQUIT = 11;
-export const WASI_SIGSEGV = 12;
-export const WASI_SIGSTOP = 13;
-export const WASI_SIGTERM = 14;
-export const WASI_SIGTRAP = 15;
-export const WASI_SIGTSTP = 16;
-export const WASI_SIGTTIN = 17;
-export const WASI_SIGTTOU = 18;
-export const WASI_SIGURG = 19;
-export const WASI_SIGUSR1 = 20;
-export const WASI_SIGUSR2 = 21;
-export const WASI_SIGVTALRM = 22;
+export const WASI_SIGUSR1 = 10;
+export const WASI_SIGSEGV = 11;
+export const WASI_SIGUSR2 = 12;
+export const WASI_SIGPIPE = 13;
+export const WASI_SIGALRM = 14;
+export const WASI_SIGTERM = 15;
+export const WASI_SIGCHLD = 16;
+export const WASI_SIGCONT = 17;
+export const WASI_SIGSTOP = 18;
+export const WASI_SIGTSTP = 19;
+export const WASI_SIGTTIN = 20;
+export const WASI_SIGTTOU = 21;
+export const WASI_SIGURG = 22;
 export const WASI_SIGXCPU = 23;
 export const WASI_SIGXFSZ = 24;
+export const WASI_SIGVTALRM = 25;
 
 export const WASI_FILETYPE_UNKNOWN = 0;
 export const WASI_FILETYPE_BLOCK_DEVICE = 1;
@@ -215,6 +216,8 @@ export const WASI_EVENTTYPE_CLOCK = 0;
 export const WASI_EVENTTYPE_FD_READ = 1;
 export const WASI_EVENTTYPE_FD_WRITE = 2;
 
+export const WASI_SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME = 1;
+
 export const WASI_FILESTAT_SET_ATIM = 1;
 export const WASI_FILESTAT_SET_ATIM_NOW = 2;
 export const WASI_FILESTAT_SET_MTIM = 4;
@@ -237,6 +240,13 @@ export const WASI_WHENCE_SET = 0;
 export const WASI_WHENCE_CUR = 1;
 export const WASI_WHENCE_END = 2;
 
+export const WASI_ADVICE_NORMAL = 0;
+export const WASI_ADVICE_SEQUENTIAL = 1;
+export const WASI_ADVICE_RANDOM = 2;
+export const WASI_ADVICE_WILLNEED = 3;
+export const WASI_ADVICE_DONTNEED = 4;
+export const WASI_ADVICE_NOREUSE = 5;
+
 export const WASI_FSTFLAGS_ATIM = 0x0001;
 export const WASI_FSTFLAGS_ATIM_NOW = 0x0002;
 export const WASI_FSTFLAGS_MTIM = 0x0004;
@@ -107,7 +107,7 @@ export class EventSource
       if (this.events !== constants.WASI_EXT_NO_EVENT) {
         resolve({
           userdata,
-          eventType: this.events,
+          eventType,
           nbytes: 4n,
           error: constants.WASI_ESUCCESS,
         });
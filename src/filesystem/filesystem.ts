@@ -239,6 +239,13 @@ export interface Descriptor {
    */
   truncate(size: bigint): Promise<number>;
 
+  /*
+   * Writes data that is not stored yet to the underlying file
+   *
+   * @returns status code
+   */
+  sync(): Promise<number>;
+
   /*
    * Manipulates the underlying device parameters of special files
    *
@@ -328,6 +335,10 @@ export abstract class AbstractDescriptor implements Descriptor {
 
   duplicateFd() {}
 
+  async sync(): Promise<number> {
+    return constants.WASI_ESUCCESS;
+  }
+
   abstract getFilestat(): Promise<{ err: number; filestat: Filestat }>;
   abstract setFilestatTimes(atim: Timestamp, mtim: Timestamp): Promise<number>;
   abstract close(): Promise<number>;
@@ -488,11 +499,12 @@ export abstract class AbstractDeviceDescriptor extends AbstractDescriptor {
     return { err: constants.WASI_EBADF, content: undefined };
   }
 
+  // devices are not seekable so positional I/O is not supported by default
   async pread(
     _len: number,
     _pos: bigint
   ): Promise<{ err: number; buffer: ArrayBuffer }> {
-    return { err: constants.WASI_EBADF, buffer: undefined };
+    return { err: constants.WASI_ESPIPE, buffer: undefined };
   }
 
   async arrayBuffer(): Promise<{ err: number; buffer: ArrayBuffer }> {
@@ -507,7 +519,7 @@ export abstract class AbstractDeviceDescriptor extends AbstractDescriptor {
     _buffer: ArrayBuffer,
     _offset: bigint
   ): Promise<{ err: number; written: bigint }> {
-    return { err: constants.WASI_EBADF, written: -1n };
+    return { err: constants.WASI_ESPIPE, written: -1n };
   }
 
   async seek(
@@ -527,8 +539,13 @@ export abstract class AbstractDeviceDescriptor extends AbstractDescriptor {
     return { err: constants.WASI_EBADF, stream: undefined };
   }
 
+  // devices have no size and nothing to store
   async truncate(_size: bigint): Promise<number> {
-    return constants.WASI_EBADF;
+    return constants.WASI_EINVAL;
+  }
+
+  override async sync(): Promise<number> {
+    return constants.WASI_EINVAL;
   }
 
   addPollSub(
@@ -549,7 +566,6 @@ export interface Filesystem {
   fsname(): string;
   mkdirat(desc: Descriptor | undefined, path: string): Promise<number>;
   getFilestat(path: string): Promise<{ err: number; filestat: Filestat }>;
-  // missing path_link
   open(
     path: string,
     dirflags: LookupFlags,
@@ -566,6 +582,12 @@ export interface Filesystem {
     newDesc: Descriptor,
     newPath: string
   ): Promise<number>;
+  linkat(
+    oldDesc: Descriptor,
+    oldPath: string,
+    newDesc: Descriptor,
+    newPath: string
+  ): Promise<number>;
   symlinkat(
     target: string,
     desc: Descriptor,
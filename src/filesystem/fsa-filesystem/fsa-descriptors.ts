@@ -14,6 +14,7 @@ import {
 import { initMetadataPath, mapErr } from "./utils.js";
 import { getStoredData, setStoredData } from "./metadata.js";
 import { UserData, EventType, PollEvent } from "../../types.js";
+import { msToNs } from "../../utils.js";
 
 
 interface FsaDescriptor extends AbstractDescriptor {
@@ -94,6 +95,11 @@ export class FsaFileDescriptor
   // Caused by invalid types, can be fixed by using @types/wicg-file-system-access
   // @ts-ignore
   private writer: FileSystemWritableFileStream;
+  // size of the file including writes that are not flushed yet, undefined
+  // while the writer is open if writes bypass the descriptor
+  private writerSize: bigint;
+  // the writer holds data that getFile doesn't return until it is closed
+  private dirty: boolean;
   private file: File;
 
   constructor(
@@ -105,6 +111,7 @@ export class FsaFileDescriptor
   ) {
     super();
     this.cursor = 0n;
+    this.dirty = false;
     initFsaDesc(
       this,
       fs_flags,
@@ -121,7 +128,7 @@ export class FsaFileDescriptor
 
     await initializeFsaDesc(this);
 
-    const size = BigInt((await this.__getFile()).file?.size);
+    const size = await this.__getSize();
     let filetype;
     if (this.keepMetadata) {
       const filestat = await getStoredData(this.metadataPath);
@@ -147,6 +154,7 @@ export class FsaFileDescriptor
       this.writer = await this.handle.createWritable({
         keepExistingData: true,
       });
+      this.writerSize = BigInt((await this.handle.getFile()).size);
     }
     return this.writer;
   }
@@ -155,6 +163,8 @@ export class FsaFileDescriptor
    * Auxiliary function for getting a file from a handle and handling errors
    */
   private async __getFile(): Promise<{ err: number; file: File }> {
+    // data written by the writer becomes visible once it is closed
+    if (this.dirty) await this.flush();
     if (!this.file) {
       try {
         const file = await this.handle.getFile();
@@ -167,6 +177,14 @@ export class FsaFileDescriptor
     return { err: constants.WASI_ESUCCESS, file: this.file };
   }
 
+  /**
+   * Auxiliary function for getting the file size without flushing the writer
+   */
+  private async __getSize(): Promise<bigint> {
+    if (this.writer && this.writerSize !== undefined) return this.writerSize;
+    return BigInt((await this.__getFile()).file?.size);
+  }
+
   async read(len: number): Promise<{ err: number; buffer: ArrayBuffer }> {
     const { err, file } = await this.__getFile();
     if (err !== constants.WASI_ESUCCESS) {
@@ -200,8 +218,8 @@ export class FsaFileDescriptor
     if (err !== constants.WASI_ESUCCESS) {
       return { err, buffer: undefined };
     }
-    const size = BigInt((await this.__getFile()).file?.size);
-    const end = size < pos + BigInt(len) ? size : this.cursor + BigInt(len);
+    const size = BigInt(file.size);
+    const end = size < pos + BigInt(len) ? size : pos + BigInt(len);
     return {
       err: constants.WASI_ESUCCESS,
       buffer: await file.slice(Number(pos), Number(end)).arrayBuffer(),
@@ -212,7 +230,7 @@ export class FsaFileDescriptor
     offset: bigint,
     whence: Whence
   ): Promise<{ err: number; offset: bigint }> {
-    const size = BigInt((await this.__getFile()).file?.size);
+    const size = await this.__getSize();
     switch (whence) {
       case constants.WASI_WHENCE_CUR:
         if (this.cursor + offset < 0n) {
@@ -243,15 +261,19 @@ export class FsaFileDescriptor
   }
 
   async write(buffer: ArrayBuffer): Promise<{ err: number; written: bigint }> {
-    await (
-      await this.getWriter()
-    ).write({
+    // in append mode data always lands at the end of the file
+    if (this.fdstat.fs_flags & constants.WASI_FDFLAG_APPEND)
+      this.cursor = await this.__getSize();
+    const writer = await this.getWriter();
+    await writer.write({
       type: "write",
       position: Number(this.cursor),
       data: buffer,
     });
+    this.dirty = true;
     let written = BigInt(buffer.byteLength);
     this.cursor += written;
+    if (this.cursor > this.writerSize) this.writerSize = this.cursor;
     return { err: constants.WASI_ESUCCESS, written };
   }
 
@@ -266,12 +288,18 @@ export class FsaFileDescriptor
       position: Number(offset),
       data: buffer,
     });
+    this.dirty = true;
     let written = BigInt(buffer.byteLength);
+    if (offset + written > this.writerSize)
+      this.writerSize = offset + written;
     return { err: constants.WASI_ESUCCESS, written };
   }
 
   async writableStream(): Promise<{ err: number; stream: WritableStream }> {
-    return { err: constants.WASI_ESUCCESS, stream: await this.getWriter() };
+    const stream = await this.getWriter();
+    this.dirty = true;
+    this.writerSize = undefined;
+    return { err: constants.WASI_ESUCCESS, stream };
   }
 
   async truncate(size: bigint): Promise<number> {
@@ -286,7 +314,17 @@ export class FsaFileDescriptor
       return constants.WASI_EINVAL;
     }
     await this.flush();
-    this.cursor = 0n;
+
+    if (this.keepMetadata) {
+      const filestat = await getStoredData(this.metadataPath);
+      filestat.mtim = filestat.ctim = msToNs(Date.now());
+      await setStoredData(this.metadataPath, filestat);
+    }
+    return constants.WASI_ESUCCESS;
+  }
+
+  override async sync(): Promise<number> {
+    await this.flush();
     return constants.WASI_ESUCCESS;
   }
 
@@ -300,11 +338,14 @@ export class FsaFileDescriptor
       const writer = this.writer;
 
       this.writer = null;
+      this.writerSize = undefined;
+      this.dirty = false;
       // prevent other processes from closing the same descriptor
       // TODO: is mutex necessary here?
       try {
         await writer?.close();
       } catch (_) { }
+      this.file = undefined;
     }
   }
   async close(): Promise<number> {
@@ -321,7 +362,7 @@ export class FsaFileDescriptor
     if (filestat === undefined)
       return { err: constants.WASI_ENOTRECOVERABLE, filestat: undefined };
 
-    filestat.size = BigInt((await this.__getFile()).file?.size);
+    filestat.size = await this.__getSize();
     return { err: constants.WASI_ESUCCESS, filestat };
   }
 
@@ -332,9 +373,10 @@ export class FsaFileDescriptor
     eventType: EventType,
     _workerId: number
   ): Promise<PollEvent> {
-    const nbytes = BigInt(
-      this.file ? this.file.size : (await this.__getFile()).file.size
-    );
+    const nbytes =
+      this.file && !this.dirty
+        ? BigInt(this.file.size)
+        : await this.__getSize();
     return {
       userdata,
       error: constants.WASI_ESUCCESS,
@@ -414,33 +456,40 @@ export class FsaDirectoryDescriptor
     if (refresh || this.entries.length === 0) {
       this.entries = [];
       var i = 1n;
-      // Caused by invalid types, can be fixed by using @types/wicg-file-system-access
-      // @ts-ignore
-      for await (const [name, handle] of this.handle.entries()) {
-        if (name.endsWith(".crswap")) {
-          continue;
-        }
-
-        let filestat;
-        if (this.keepMetadata) {
-          filestat = await getStoredData(`${this.metadataPath}/${name}`);
-        } else {
-          filestat =
-            handle instanceof FileSystemDirectoryHandle
-              ? FsaDirectoryDescriptor.defaultFilestat
-              : FsaFileDescriptor.defaultFilestat;
+      try {
+        // Caused by invalid types, can be fixed by using @types/wicg-file-system-access
+        // @ts-ignore
+        for await (const [name, handle] of this.handle.entries()) {
+          if (name.endsWith(".crswap")) {
+            continue;
+          }
+
+          let filestat;
+          if (this.keepMetadata) {
+            filestat = await getStoredData(`${this.metadataPath}/${name}`);
+          } else {
+            filestat =
+              handle instanceof FileSystemDirectoryHandle
+                ? FsaDirectoryDescriptor.defaultFilestat
+                : FsaFileDescriptor.defaultFilestat;
+          }
+
+          // TODO: revisit errno choice
+          if (filestat === undefined) {
+            err = constants.WASI_ENOTRECOVERABLE;
+          } else {
+            this.entries.push({
+              d_next: i++,
+              d_ino: filestat.ino,
+              name,
+              d_type: filestat.filetype,
+            });
+          }
         }
-
-        // TODO: revisit errno choice
-        if (filestat === undefined) {
-          err = constants.WASI_ENOTRECOVERABLE;
-        } else {
-          this.entries.push({
-            d_next: i++,
-            d_ino: filestat.ino,
-            name,
-            d_type: filestat.filetype,
-          });
+      } catch (e) {
+        // a removed directory has no entries, like on unix
+        if (!(e instanceof DOMException && e.name === "NotFoundError")) {
+          err = mapErr(e as DOMException, true);
         }
       }
     }
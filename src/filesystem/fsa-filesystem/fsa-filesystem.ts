@@ -145,7 +145,7 @@ export class FsaFilesystem implements Filesystem {
       if (__err !== constants.WASI_ESUCCESS) {
         return __err;
       }
-      (handle as FileSystemDirectoryHandle).removeEntry(name, {
+      await (handle as FileSystemDirectoryHandle).removeEntry(name, {
         recursive: false,
       });
       await delStoredData(`${await initMetadataPath(handle)}/${path}`);
@@ -216,6 +216,16 @@ export class FsaFilesystem implements Filesystem {
     return constants.WASI_ESUCCESS;
   }
 
+  async linkat(
+    _oldDesc: Descriptor,
+    _oldPath: string,
+    _newDesc: Descriptor,
+    _newPath: string
+  ): Promise<number> {
+    // Filesystem Access API has no notion of hard links
+    return constants.WASI_EPERM;
+  }
+
   async symlinkat(
     target: string,
     desc: Descriptor,
@@ -142,6 +142,15 @@ export class ProcFilesystem implements Filesystem {
     return Promise.resolve(constants.WASI_EACCES);
   }
 
+  linkat(
+    _oldDesc: Descriptor,
+    _oldPath: string,
+    _newDesc: Descriptor,
+    _newPath: string
+  ): Promise<number> {
+    return Promise.resolve(constants.WASI_EACCES);
+  }
+
   symlinkat(
     _target: string,
     _desc: Descriptor,
@@ -293,6 +293,17 @@ export class TopLevelFs {
     workerId: number = -1
   ): Promise<number> {
     let __path = this.abspath(desc, path);
+
+    // Trailing slashes are only allowed in paths to directories
+    const trailingSlash = __path.length > 1 && __path.endsWith("/");
+    while (__path.length > 1 && __path.endsWith("/"))
+      __path = __path.slice(0, -1);
+
+    // Mount points cannot be removed while they are mounted, unlinking
+    // one fails the same way as unlinking any other directory
+    if (this.mounts[realpath(__path)] !== undefined)
+      return is_dir ? constants.WASI_EBUSY : constants.WASI_EISDIR;
+
     const {
       desc: __desc,
       fs,
@@ -303,7 +314,15 @@ export class TopLevelFs {
       constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW
     );
     if (err !== constants.WASI_ESUCCESS) return err;
-    // TODO: this could potentially remove a mount point while it is still mounted
+
+    if (trailingSlash && !is_dir) {
+      const { desc: __entry, err } = await this.getDescInfo(__path, workerId);
+      if (err !== constants.WASI_ESUCCESS) return err;
+      const filetype = __entry.getFdstat().fs_filetype;
+      await __entry.close();
+      if (filetype !== constants.WASI_FILETYPE_DIRECTORY)
+        return constants.WASI_ENOTDIR;
+    }
     return await fs.unlinkat(__desc, basename(__path), is_dir);
   }
 
@@ -314,20 +333,19 @@ export class TopLevelFs {
     target: string,
     workerId: number = -1
   ): Promise<number> {
-    const __source = this.abspath(desc_s, source);
+    const __source = realpath(this.abspath(desc_s, source));
     const __source_dirname = dirname(__source);
-    const __target = this.abspath(desc_t, target);
+    const __target = realpath(this.abspath(desc_t, target));
     const __target_dirname = dirname(__target);
 
-    const isTargetSubdirOfSource = __target === __source || __target.startsWith(__source + "/");
+    // A directory cannot become a subdirectory of itself
+    if (__target.startsWith(__source + "/")) return constants.WASI_EINVAL;
 
-    // If any mount point starts with the source path
-    // or if target path is starts with source path, return EBUSY
+    // If the source path is a mount point or contains one, return EBUSY
     if (
-      Object.keys(this.mounts).some((key) => {
-        key.startsWith(__source);
-      }) ||
-      isTargetSubdirOfSource
+      Object.keys(this.mounts).some(
+        (key) => key === __source || key.startsWith(__source + "/")
+      )
     )
       return constants.WASI_EBUSY;
 
@@ -342,16 +360,19 @@ export class TopLevelFs {
     // return with error
     if (dinfo1.err !== constants.WASI_ESUCCESS) return dinfo1.err;
 
+    // The last component is not followed, symlinks are renamed themselves
     const __dinfo1 = await this.getDescInfo(
-      __source,
-      workerId,
-      constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW
+      `${dinfo1.path}/${basename(__source)}`,
+      workerId
     );
 
     // If the source path doesn't correspond to an existing filesystem entry,
     // return with error
     if (__dinfo1.err !== constants.WASI_ESUCCESS) return __dinfo1.err;
 
+    // Renaming an existing entry to itself does nothing
+    if (__target === __source) return constants.WASI_ESUCCESS;
+
     const __res = await __dinfo1.desc.getFilestat();
     if (__res.err !== constants.WASI_ESUCCESS) return __res.err;
     const filestat1 = __res.filestat;
@@ -367,10 +388,12 @@ export class TopLevelFs {
     // return with error
     if (dinfo2.err !== constants.WASI_ESUCCESS) return dinfo2.err;
 
+    // if paths are on different mount points, return EXDEV
+    if (dinfo1.fs !== dinfo2.fs) return constants.WASI_EXDEV;
+
     const __dinfo2 = await this.getDescInfo(
-      __target,
-      workerId,
-      constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW
+      `${dinfo2.path}/${basename(__target)}`,
+      workerId
     );
 
     // target path exists, additional checks need to be performed to check
@@ -380,9 +403,6 @@ export class TopLevelFs {
       if (__res.err !== constants.WASI_ESUCCESS) return __res.err;
       const filestat2 = __res.filestat;
 
-      // if paths are on different mount points, return EXDEV
-      if (filestat1.dev !== filestat2.dev) return constants.WASI_EXDEV;
-
       if (filestat2.filetype === constants.WASI_FILETYPE_DIRECTORY) {
         // If target is a directory and the source isn't, return with ENOTDIR
         if (filestat1.filetype !== constants.WASI_FILETYPE_DIRECTORY)
@@ -395,6 +415,9 @@ export class TopLevelFs {
 
         // if the target directory is not empty, return ENOTEMPTY
         if (dirents.length !== 0) return constants.WASI_ENOTEMPTY;
+      } else if (filestat1.filetype === constants.WASI_FILETYPE_DIRECTORY) {
+        // If source is a directory and the target isn't, return with ENOTDIR
+        return constants.WASI_ENOTDIR;
       }
     }
     return dinfo2.fs.renameat(
@@ -405,6 +428,78 @@ export class TopLevelFs {
     );
   }
 
+  async link(
+    desc_s: Descriptor,
+    source: string,
+    lookupFlags: LookupFlags,
+    desc_t: Descriptor,
+    target: string,
+    workerId: number = -1
+  ): Promise<number> {
+    const __source = realpath(this.abspath(desc_s, source));
+    const __target = realpath(this.abspath(desc_t, target));
+
+    const dinfo1 = await this.getDescInfo(
+      dirname(__source),
+      workerId,
+      constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW,
+      constants.WASI_O_DIRECTORY
+    );
+
+    // If parent directory descriptor of the source path cannot be found,
+    // return with error
+    if (dinfo1.err !== constants.WASI_ESUCCESS) return dinfo1.err;
+
+    // The last component is followed only if requested, otherwise the
+    // new link points to the symlink itself
+    const __dinfo1 = await this.getDescInfo(
+      `${dinfo1.path}/${basename(__source)}`,
+      workerId,
+      lookupFlags & constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW
+    );
+    if (__dinfo1.err !== constants.WASI_ESUCCESS) return __dinfo1.err;
+
+    const __res = await __dinfo1.desc.getFilestat();
+    if (__res.err !== constants.WASI_ESUCCESS) return __res.err;
+
+    // Directories cannot be hard linked
+    if (__res.filestat.filetype === constants.WASI_FILETYPE_DIRECTORY)
+      return constants.WASI_EPERM;
+
+    // A followed symlink can point to an entry in another directory
+    const sourceDir =
+      lookupFlags & constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW
+        ? await this.getDescInfo(
+            dirname(__dinfo1.path),
+            workerId,
+            constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW,
+            constants.WASI_O_DIRECTORY
+          )
+        : dinfo1;
+    if (sourceDir.err !== constants.WASI_ESUCCESS) return sourceDir.err;
+
+    const dinfo2 = await this.getDescInfo(
+      dirname(__target),
+      workerId,
+      constants.WASI_LOOKUPFLAGS_SYMLINK_FOLLOW,
+      constants.WASI_O_DIRECTORY
+    );
+
+    // If parent directory descriptor of the target path cannot be found,
+    // return with error
+    if (dinfo2.err !== constants.WASI_ESUCCESS) return dinfo2.err;
+
+    // if paths are on different mount points, return EXDEV
+    if (sourceDir.fs !== dinfo2.fs) return constants.WASI_EXDEV;
+
+    return dinfo2.fs.linkat(
+      sourceDir.desc,
+      basename(__dinfo1.path),
+      dinfo2.desc,
+      basename(__target)
+    );
+  }
+
   async addMount(
     sourceDesc: Descriptor,
     sourcePath: string,
@@ -418,6 +513,7 @@ export class TopLevelFs {
     workerId: number = -1
   ): Promise<number> {
     const __targetPath = this.abspath(targetDesc, targetPath);
+    if (this.mounts[__targetPath] !== undefined) return constants.WASI_EBUSY;
 
     let dinfoTarget;
     if (__targetPath !== "/") {
@@ -432,8 +528,6 @@ export class TopLevelFs {
 
       const dirents = await dinfoTarget.desc.readdir(true);
       if (dirents.dirents.length !== 0) return constants.WASI_ENOTEMPTY;
-    } else if (this.mounts["/"] !== undefined) {
-      return constants.WASI_EBUSY;
     }
 
     const __sourcePath = this.abspath(sourceDesc, sourcePath);
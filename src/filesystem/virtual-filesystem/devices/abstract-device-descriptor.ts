@@ -10,6 +10,15 @@ import { wasiFilestat } from "../virtual-filesystem.js";
 import * as vfs from "../../../third_party/vfs.js";
 import * as constants from "../../../constants.js";
 
+// Ioctl requests understood by every virtual device. They use their own driver
+// number and are handled by the kernel, so they don't collide with requests of
+// particular devices, which only receive the function number.
+export const DEVICE_IOCTL_DRIVER = 0x44;
+
+export const enum deviceIoctlRequests {
+  DEVGETRDEV = 0x01, // Get device number of a character device
+}
+
 export abstract class AbstractVirtualDeviceDescriptor extends AbstractDeviceDescriptor {
   constructor(
     fs_flags: Fdflags,
@@ -26,4 +35,23 @@ export abstract class AbstractVirtualDeviceDescriptor extends AbstractDeviceDesc
       filestat: wasiFilestat(this.ino._metadata),
     };
   }
+
+  async deviceIoctl(request: number, buf: Uint8Array): Promise<number> {
+    switch (request) {
+      case deviceIoctlRequests.DEVGETRDEV: {
+        // fifos are served by the same descriptors but have no device number
+        if (!this.ino._metadata.isCharacterDevice())
+          return constants.WASI_ENOTTY;
+        if (buf.byteLength < 8)
+          return constants.WASI_ENOBUFS;
+
+        new DataView(buf.buffer, buf.byteOffset).setBigUint64(
+          0, BigInt(this.ino._metadata.rdev), true
+        );
+        return constants.WASI_ESUCCESS;
+      }
+      default:
+        return constants.WASI_EINVAL;
+    }
+  }
 }
@@ -62,6 +62,18 @@ export class FifoDescriptor
   }
 
   override async read(len: number): Promise<{ err: number; buffer: ArrayBuffer }> {
+    // empty fifo with a writer would block, a closed writer means EOF
+    if (
+      this.fdstat.fs_flags & constants.WASI_FDFLAG_NONBLOCK &&
+      this.ino.messages.length === 0 &&
+      this.ino.writer !== fifoPeerState.CLOSED
+    ) {
+      return {
+        err: constants.WASI_EAGAIN,
+        buffer: new ArrayBuffer(0),
+      };
+    }
+
     let buf = await this.ino.read(len);
 
     return {
@@ -84,6 +96,16 @@ export class FifoDescriptor
     eventType: EventType,
     _workerId: number
   ): Promise<PollEvent> {
+    // writes never block so the write end is always ready
+    if (eventType === constants.WASI_EVENTTYPE_FD_WRITE) {
+      return {
+        userdata,
+        error: constants.WASI_ESUCCESS,
+        eventType,
+        nbytes: 0n,
+      };
+    }
+
     const nbytes = BigInt(await this.ino.addPollSub());
 
     return {
@@ -6,6 +6,8 @@ import { DeviceDriver } from "./driver-manager.js";
 import * as constants from "../../../constants.js";
 import { AbstractVirtualDeviceDescriptor } from "./abstract-device-descriptor.js";
 
+const URANDOM_CHUNK = 65536;
+
 export const enum minor {
   DEV_NULL = 0,
   DEV_ZERO = 1,
@@ -155,9 +157,11 @@ class VirtualUrandomDescriptor extends VirtualNullDescriptor {
     _workerId?: number
   ): Promise<{ err: number; buffer: ArrayBuffer }> {
     let __buf = new ArrayBuffer(len);
-    let __view8 = new Uint8Array(__buf);
-    for (var i = 0; i < len; i++) {
-      __view8.set([Math.floor(Math.random() * 256)], i);
+    // getRandomValues fills at most 64KiB at once
+    for (let i = 0; i < len; i += URANDOM_CHUNK) {
+      crypto.getRandomValues(
+        new Uint8Array(__buf, i, Math.min(URANDOM_CHUNK, len - i))
+      );
     }
 
     return {
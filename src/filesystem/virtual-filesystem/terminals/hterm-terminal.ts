@@ -194,7 +194,8 @@ export class HtermDeviceDriver implements TerminalDriver {
                 3: new DescriptorEntry(
                   (
                     await processManager.filesystem.open("/")
-                  ).desc
+                  ).desc,
+                  true
                 ),
               }),
               [
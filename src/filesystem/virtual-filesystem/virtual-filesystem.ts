@@ -151,6 +151,8 @@ export class VirtualFilesystem implements Filesystem {
             return constants.WASI_ENOTEMPTY;
           }
         }
+      } else if (navigated.target instanceof vfs.Directory) {
+        return constants.WASI_EISDIR;
       }
 
       navigated.dir.deleteEntry(navigated.name);
@@ -306,17 +308,65 @@ export class VirtualFilesystem implements Filesystem {
       return constants.WASI_ENOENT;
     }
 
+    const index = oldNavigated.dir.getEntryIndex(oldNavigated.name);
     if (!newNavigated.target) {
       if (newNavigated.remaining.length !== 0) {
         return constants.WASI_ENOENT;
       }
+    } else if (newNavigated.dir.getEntryIndex(newNavigated.name) === index) {
+      // both paths are links to the same inode, nothing is renamed
+      return constants.WASI_ESUCCESS;
     } else {
+      // existing target is replaced, the caller checks if its type allows it
+      newNavigated.dir.deleteEntry(newNavigated.name);
+    }
+
+    newNavigated.dir.addEntry(newNavigated.name, index);
+    oldNavigated.dir.deleteEntry(oldNavigated.name, index);
+    return constants.WASI_ESUCCESS;
+  }
+
+  async linkat(
+    oldDesc: Descriptor,
+    oldPath: string,
+    newDesc: Descriptor,
+    newPath: string
+  ): Promise<number> {
+    let oldNavigated;
+    if (oldDesc === undefined) {
+      oldNavigated = this.virtualFs._navigate(oldPath, false);
+    } else if (oldDesc instanceof VirtualFilesystemDirectoryDescriptor) {
+      oldNavigated = this.virtualFs._navigateFrom(oldDesc.dir, oldPath, false);
+    } else {
+      return constants.WASI_EINVAL;
+    }
+
+    let newNavigated;
+    if (newDesc === undefined) {
+      newNavigated = this.virtualFs._navigate(newPath, false);
+    } else if (newDesc instanceof VirtualFilesystemDirectoryDescriptor) {
+      newNavigated = this.virtualFs._navigateFrom(newDesc.dir, newPath, false);
+    } else {
+      return constants.WASI_EINVAL;
+    }
+
+    if (!oldNavigated.target) {
+      return constants.WASI_ENOENT;
+    }
+
+    if (oldNavigated.target instanceof vfs.Directory) {
+      return constants.WASI_EPERM;
+    }
+
+    if (newNavigated.target) {
       return constants.WASI_EEXIST;
+    } else if (newNavigated.remaining.length !== 0) {
+      return constants.WASI_ENOENT;
     }
 
+    // adding an entry increments the link count of the inode
     const index = oldNavigated.dir.getEntryIndex(oldNavigated.name);
     newNavigated.dir.addEntry(newNavigated.name, index);
-    oldNavigated.dir.deleteEntry(oldNavigated.name, index);
     return constants.WASI_ESUCCESS;
   }
 
@@ -465,7 +515,11 @@ class VirtualFilesystemFileDescriptor
   }
 
   async write(buffer: ArrayBuffer): Promise<{ err: number; written: bigint }> {
+    // in append mode data always lands at the end of the file
+    if (this.fdstat.fs_flags & constants.WASI_FDFLAG_APPEND)
+      this.cursor = this.desc._iNode._data.byteLength;
     try {
+      this.fillGap(this.cursor);
       const written = await this.desc._iNode.write(buffer, this.cursor);
       this.cursor += written;
       return {
@@ -482,12 +536,22 @@ class VirtualFilesystemFileDescriptor
     offset: bigint
   ): Promise<{ err: number; written: bigint }> {
     try {
+      this.fillGap(Number(offset));
       return {
         err: constants.WASI_ESUCCESS,
         written: await this.desc._iNode.write(buffer, Number(offset)),
       };
     } catch (e: vfs.VirtualFSError) {
-      return e.errno;
+      return { err: e.errno, written: 0n };
+    }
+  }
+
+  // vfs moves writes starting past the end of file to its end, the gap
+  // has to be filled with zeros first
+  private fillGap(position: number) {
+    const size = this.desc._iNode._data.byteLength;
+    if (position > size) {
+      this.desc._iNode.write(new ArrayBuffer(position - size), size);
     }
   }
 
@@ -527,12 +591,19 @@ class VirtualFilesystemFileDescriptor
   }
 
   async truncate(size: bigint): Promise<number> {
-    try {
-      this.desc._iNode._data.resize(Number(size));
-      return constants.WASI_ESUCCESS;
-    } catch (e: vfs.VirtualFSError) {
-      return e.errno;
+    const iNode = this.desc._iNode;
+    const __size = Number(size);
+    if (__size < iNode._data.byteLength) {
+      iNode._data.resize(__size);
+    } else {
+      // writing zeros reallocates the buffer if it can't be resized
+      this.fillGap(__size);
     }
+    const now = new Date();
+    iNode._metadata.size = __size;
+    iNode._metadata.mtime = now;
+    iNode._metadata.ctime = now;
+    return constants.WASI_ESUCCESS;
   }
 
   async writableStream(): Promise<{
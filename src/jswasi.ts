@@ -219,7 +219,7 @@ export class Jswasi {
       null, // parent_lock
       config.init,
       new FdTable({
-        3: new DescriptorEntry(res.desc),
+        3: new DescriptorEntry(res.desc, true),
       }),
       config.initArgs,
       DEFAULT_ENV,
@@ -38,13 +38,18 @@ export const SHELL_SCRIPT_MAGICNUMBER = 8483; // bytes of a shebang
 export class DescriptorEntry {
   desc: Descriptor;
   fdFlags: Fdflags;
+  // preopened directories are reported by fd_prestat_get, the status
+  // follows the descriptor when it is duplicated or inherited
+  preopen: boolean;
 
-  constructor(desc: Descriptor) {
+  constructor(desc: Descriptor, preopen: boolean = false) {
     if (desc === undefined) {
       throw "DescriptorEntry must not contain undefined descriptor!";
     }
     this.desc = desc;
-    this.fdFlags = desc.getFdstat().fs_flags;
+    // standard flags are kept by the descriptor itself
+    this.fdFlags = desc.getFdstat().fs_flags & constants.WASI_EXT_FDFLAG_MASK;
+    this.preopen = preopen;
   }
 }
 
@@ -64,7 +69,10 @@ export class FdTable {
 
     for (let key in this.fdt) {
       if ((this.fdt[key].fdFlags & constants.WASI_EXT_FDFLAG_CLOEXEC) === 0) {
-        fdTable.fdt[key] = new DescriptorEntry(this.fdt[key].desc);
+        fdTable.fdt[key] = new DescriptorEntry(
+          this.fdt[key].desc,
+          this.fdt[key].preopen
+        );
         fdTable.fdt[key].desc.duplicateFd();
       } else {
         fdTable.freeFds.push(Number(key));
@@ -138,7 +146,10 @@ export class FdTable {
     if (this.fdt[dstFd] !== undefined) {
       console.log(`duplicateFd: overwrite opened fd = ${dstFd}`);
     }
-    this.fdt[dstFd] = new DescriptorEntry(this.fdt[srcFd].desc);
+    this.fdt[dstFd] = new DescriptorEntry(
+      this.fdt[srcFd].desc,
+      this.fdt[srcFd].preopen
+    );
     this.fdt[dstFd].desc.duplicateFd();
   }
 
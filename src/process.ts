@@ -43,6 +43,11 @@ import {
   EventType,
   POLL_EVENT_BUFSIZE,
   FdFdstatSetFlagsArgs,
+  FdFdstatSetRightsArgs,
+  FdFilestatSetSizeArgs,
+  FdAllocateArgs,
+  FdSyncArgs,
+  FdAdviseArgs,
   UmountArgs,
   MknodArgs,
   UnameArgs,
@@ -88,13 +93,22 @@ type WASICallbacks = {
     newOffset: ptr,
   ) => number;
   fd_close: (fd: number) => number;
-  fd_datasync: any;
-  fd_filestat_set_size: any;
-  fd_sync: any;
-  fd_advise: any;
-  fd_allocate: any;
+  fd_datasync: (fd: number) => number;
+  fd_filestat_set_size: (fd: number, size: bigint) => number;
+  fd_sync: (fd: number) => number;
+  fd_advise: (
+    fd: number,
+    offset: bigint,
+    len: bigint,
+    advice: number,
+  ) => number;
+  fd_allocate: (fd: number, offset: bigint, len: bigint) => number;
   fd_fdstat_set_flags: any;
-  fd_fdstat_set_rights: any;
+  fd_fdstat_set_rights: (
+    fd: number,
+    rightsBase: bigint,
+    rightsInheriting: bigint,
+  ) => number;
   fd_tell: (fd: number, pos: ptr) => number;
   fd_pread: (
     fd: number,
@@ -110,7 +124,13 @@ type WASICallbacks = {
     fst_flags: number,
   ) => number;
   fd_advice: any;
-  fd_pwrite: any;
+  fd_pwrite: (
+    fd: number,
+    iovs: ptr,
+    iovsLen: number,
+    offset: bigint,
+    nWritten: ptr,
+  ) => number;
   fd_renumber: any;
 
   path_filestat_set_times: (
@@ -185,8 +205,8 @@ type WASICallbacks = {
   sched_yield: any;
   poll_oneoff: any;
   random_get: (bufPtr: ptr, bufLen: number) => number;
-  clock_time_get: (clockId: number, precision: number, time: ptr) => number;
-  clock_res_get: (clock_id: number) => number;
+  clock_time_get: (clockId: number, precision: bigint, time: ptr) => number;
+  clock_res_get: (clockId: number, resolution: ptr) => number;
   proc_exit: (exitCode: number) => void;
 };
 
@@ -422,15 +442,28 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
     return constants.WASI_ESUCCESS;
   }
 
-  function clock_res_get(_clock_id: number) {
-    return placeholder();
+  function clock_res_get(clockId: number, resolution: ptr) {
+    workerConsoleLog(`clock_res_get(${clockId}, ${resolution})`);
+    const res = utils.clockRes(clockId);
+    if (res === undefined) {
+      return constants.WASI_EINVAL;
+    }
+
+    const view = new DataView(memory.buffer);
+    view.setBigUint64(resolution, res, true);
+    return constants.WASI_ESUCCESS;
   }
 
-  function clock_time_get(clockId: number, precision: number, time: ptr) {
+  // precision is only a hint, clocks are always read with their full resolution
+  function clock_time_get(clockId: number, precision: bigint, time: ptr) {
     workerConsoleLog(`clock_time_get(${clockId}, ${precision}, ${time})`);
+    const n = utils.now(clockId, CPUTIME_START);
+    if (n === undefined) {
+      return constants.WASI_EINVAL;
+    }
     const view = new DataView(memory.buffer);
 
-    view.setBigUint64(time, utils.now(clockId, CPUTIME_START), true);
+    view.setBigUint64(time, n, true);
     return constants.WASI_ESUCCESS;
   }
 
@@ -531,6 +564,8 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
 
       view8.set(readBuf, addr);
       read += readLen[0];
+      // don't wait for more data once a buffer is not filled
+      if (readLen[0] < len) break;
     }
     if (fd > 2) workerConsoleLog(`fd_read read ${read} bytes.`);
     view.setUint32(nRead, read, true);
@@ -560,9 +595,10 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
       lck[0] = -1;
       const readLen = new Int32Array(sharedBuffer, 4, 1);
       const readBuf = new Uint8Array(sharedBuffer, 8, len);
+      // every buffer is read from where the previous one ended
       sendToKernel([
         "fd_pread",
-        { sharedBuffer, fd, len, offset } as FdReadArgs,
+        { sharedBuffer, fd, len, offset: offset + BigInt(read) } as FdReadArgs,
       ]);
       Atomics.wait(lck, 0, -1);
 
@@ -573,6 +609,7 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
 
       view8.set(readBuf, addr);
       read += readLen[0];
+      if (readLen[0] < len) break;
     }
     if (fd > 2) workerConsoleLog(`fd_pread read ${read} bytes.`);
     view.setUint32(nRead, read, true);
@@ -1220,7 +1257,7 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
           fd: number;
           cmd: number;
         } = JSON.parse(json);
-        const { size, rw, func } = utils.decodeIoctlRequest(BigInt(cmd));
+        const { size, rw, func, driver } = utils.decodeIoctlRequest(BigInt(cmd));
 
         // lock + arg buffer size used + arg buffer
         const sharedBuffer = new SharedArrayBuffer(4 + size);
@@ -1235,7 +1272,7 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
 
         sendToKernel([
           "ioctl",
-          { sharedBuffer, fd, command: func } as IoctlArgs,
+          { sharedBuffer, fd, driver, command: func } as IoctlArgs,
         ]);
 
         Atomics.wait(lck, 0, -1);
@@ -1641,16 +1678,58 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
     return err;
   }
 
-  function fd_datasync() {
-    return placeholder();
+  function fd_datasync(fd: number) {
+    workerConsoleLog(`fd_datasync(${fd})`);
+
+    const sharedBuffer = new SharedArrayBuffer(4); // lock
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_datasync",
+      { sharedBuffer, fd, dataOnly: true } as FdSyncArgs,
+    ]);
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    workerConsoleLog(`fd_datasync returned ${err}`);
+    return err;
   }
 
-  function fd_filestat_set_size() {
-    return placeholder();
+  function fd_filestat_set_size(fd: number, size: bigint) {
+    workerConsoleLog(`fd_filestat_set_size(${fd}, ${size})`);
+
+    const sharedBuffer = new SharedArrayBuffer(4); // lock
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_filestat_set_size",
+      { sharedBuffer, fd, size } as FdFilestatSetSizeArgs,
+    ]);
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    workerConsoleLog(`fd_filestat_set_size returned ${err}`);
+    return err;
   }
 
-  function fd_sync() {
-    return placeholder();
+  function fd_sync(fd: number) {
+    workerConsoleLog(`fd_sync(${fd})`);
+
+    const sharedBuffer = new SharedArrayBuffer(4); // lock
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_sync",
+      { sharedBuffer, fd, dataOnly: false } as FdSyncArgs,
+    ]);
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    workerConsoleLog(`fd_sync returned ${err}`);
+    return err;
   }
 
   function path_symlink(
@@ -1750,12 +1829,16 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
     workerConsoleLog(
       `poll_oneoff(${subscriptionsPtr}, ${eventsPtr}, ${nSubscriptions}, ${nEvents})`,
     );
+    if (nSubscriptions === 0) {
+      return constants.WASI_EINVAL;
+    }
+
     const view = new DataView(memory.buffer);
     // Buffer for sending occured events from kernel back to the process
     // it can hold up to nSubscriptions events
     const eventBuf = new SharedArrayBuffer(POLL_EVENT_BUFSIZE * nSubscriptions);
 
-    var minWaitEnd = BigInt((1n << 63n) - 1n);
+    var minWait = BigInt((1n << 63n) - 1n);
 
     const fdSubs = new Array<FdEventSub>();
 
@@ -1781,20 +1864,33 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
           const subClockFlags = view.getUint16(subscriptionsPtr, true);
           subscriptionsPtr += 8; // flags offset + padding to 8
 
-          const absolute = subClockFlags === 1;
+          const absolute =
+            (subClockFlags &
+              constants.WASI_SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME) !==
+            0;
 
           workerConsoleLog(
             `clockId = ${clockId}, timeout = ${timeout}, precision = ${precision}, absolute = ${absolute}`,
           );
 
           const n = utils.now(clockId, CPUTIME_START);
-          const end = absolute ? timeout : n + timeout;
+          if (n === undefined) {
+            return constants.WASI_EINVAL;
+          }
 
-          if (minWaitEnd > end) {
-            minWaitEnd = end;
+          // Clocks are compared by the time left until they expire so that
+          // subscriptions to different clocks can be mixed, the kernel only
+          // gets the relative timeout of the earliest one
+          let wait = timeout;
+          if (absolute) {
+            wait = timeout > n ? timeout - n : 0n;
+          }
+
+          if (minWait > wait) {
+            minWait = wait;
             lastClock = {
               clockId,
-              timeout,
+              timeout: wait,
               precision,
               flags: subClockFlags,
             } as ClockSub;
@@ -1843,7 +1939,7 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
           sharedBuffer,
           subs: fdSubs,
           eventBuf,
-          timeout: minWaitEnd,
+          timeout: minWait,
         } as PollOneoffArgs,
       ]);
       Atomics.wait(lock, 0, -1);
@@ -1869,12 +1965,51 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
     return placeholder();
   }
 
-  function fd_allocate() {
-    return placeholder();
+  function fd_allocate(fd: number, offset: bigint, len: bigint) {
+    workerConsoleLog(`fd_allocate(${fd}, ${offset}, ${len})`);
+
+    const sharedBuffer = new SharedArrayBuffer(4); // lock
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_allocate",
+      { sharedBuffer, fd, offset, len } as FdAllocateArgs,
+    ]);
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    workerConsoleLog(`fd_allocate returned ${err}`);
+    return err;
   }
 
-  function fd_fdstat_set_rights() {
-    return placeholder();
+  function fd_fdstat_set_rights(
+    fd: number,
+    rightsBase: bigint,
+    rightsInheriting: bigint,
+  ) {
+    workerConsoleLog(
+      `fd_fdstat_set_rights(${fd}, ${rightsBase}, ${rightsInheriting})`,
+    );
+
+    const sharedBuffer = new SharedArrayBuffer(4); // lock
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_fdstat_set_rights",
+      {
+        sharedBuffer,
+        fd,
+        rightsBase,
+        rightsInheriting,
+      } as FdFdstatSetRightsArgs,
+    ]);
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    workerConsoleLog(`fd_fdstat_set_rights returned ${err}`);
+    return err;
   }
 
   function fd_fdstat_set_flags(fd: number, flags: number) {
@@ -1896,8 +2031,52 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
     return err;
   }
 
-  function fd_pwrite() {
-    return placeholder();
+  function fd_pwrite(
+    fd: number,
+    iovs: ptr,
+    iovsLen: number,
+    offset: bigint,
+    nWritten: ptr,
+  ) {
+    workerConsoleLog(
+      `fd_pwrite(${fd}, ${iovs}, ${iovsLen}, ${offset}, ${nWritten})`,
+    );
+    const view = new DataView(memory.buffer);
+
+    let writeLen = 0;
+    const contents: Uint8Array[] = [];
+
+    for (let i = 0; i < iovsLen; i += 1) {
+      const ptr_pos = iovs + i * 8;
+      const buf = view.getUint32(ptr_pos, true);
+      const bufLen = view.getUint32(ptr_pos + 4, true);
+
+      const content = new Uint8Array(memory.buffer, buf, bufLen);
+      contents.push(content);
+
+      writeLen += content.byteLength;
+    }
+
+    const sharedBuffer = new SharedArrayBuffer(4 + 4 + writeLen); // lock + written + content
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    const written = new Int32Array(sharedBuffer, 4, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_pwrite",
+      { sharedBuffer, fd, contents, size: writeLen, offset } as FdWriteArgs,
+    ]);
+
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    if (err === 0) {
+      workerConsoleLog(`fd_pwrite written ${written[0]} bytes.`);
+      view.setUint32(nWritten, written[0], true);
+    } else {
+      workerConsoleLog(`fd_pwrite returned ${err}.`);
+    }
+    return err;
   }
 
   function fd_renumber(fd: number, newFd: number) {
@@ -1997,8 +2176,22 @@ function WASI(snapshot0: boolean = false): WASICallbacks {
     return placeholder();
   }
 
-  function fd_advise() {
-    return placeholder();
+  function fd_advise(fd: number, offset: bigint, len: bigint, advice: number) {
+    workerConsoleLog(`fd_advise(${fd}, ${offset}, ${len}, ${advice})`);
+
+    const sharedBuffer = new SharedArrayBuffer(4); // lock
+    const lck = new Int32Array(sharedBuffer, 0, 1);
+    lck[0] = -1;
+
+    sendToKernel([
+      "fd_advise",
+      { sharedBuffer, fd, offset, len, advice } as FdAdviseArgs,
+    ]);
+    Atomics.wait(lck, 0, -1);
+
+    const err = Atomics.load(lck, 0);
+    workerConsoleLog(`fd_advise returned ${err}`);
+    return err;
   }
 
   function fd_filestat_set_times(
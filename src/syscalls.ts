@@ -36,6 +36,11 @@ import {
   FdReadWriteSub,
   FdRenumberArgs,
   FdFdstatSetFlagsArgs,
+  FdFdstatSetRightsArgs,
+  FdFilestatSetSizeArgs,
+  FdAllocateArgs,
+  FdSyncArgs,
+  FdAdviseArgs,
   MountArgs,
   UmountArgs,
   MknodArgs,
@@ -47,6 +52,10 @@ import ProcessManager, { DescriptorEntry } from "./process-manager.js";
 import { EventSource } from "./devices.js";
 import { basename, msToNs } from "./utils.js";
 import { FsaFilesystem } from "./filesystem/fsa-filesystem/fsa-filesystem.js";
+import {
+  AbstractVirtualDeviceDescriptor,
+  DEVICE_IOCTL_DRIVER,
+} from "./filesystem/virtual-filesystem/devices/abstract-device-descriptor.js";
 
 declare global {
   interface Window {
@@ -148,7 +157,12 @@ export default async function syscallCallback(
     case "set_env": {
       const { key, value, sharedBuffer } = data as SetEnvArgs;
       const lock = new Int32Array(sharedBuffer, 0, 1);
-      processManager.processInfos[processId].env[key] = value;
+      const { env } = processManager.processInfos[processId];
+      if (value === undefined) {
+        delete env[key];
+      } else {
+        env[key] = value;
+      }
 
       Atomics.store(lock, 0, 0);
       Atomics.notify(lock, 0);
@@ -360,9 +374,10 @@ export default async function syscallCallback(
 
       let err;
       const { fds } = processManager.processInfos[processId];
-      if (fd > 2 && fd < 4) {
+      const fdEntry = fds.getFdEntry(fd);
+      if (fdEntry !== undefined && fdEntry.preopen) {
         preopenType[0] = constants.WASI_PREOPENTYPE_DIR;
-        nameLen[0] = basename(fds.getDesc(fd).getPath()).length;
+        nameLen[0] = basename(fdEntry.desc.getPath()).length;
         err = constants.WASI_ESUCCESS;
       } else {
         err = constants.WASI_EBADF;
@@ -382,6 +397,12 @@ export default async function syscallCallback(
       let err;
       if (fds.getDesc(linkFd) === undefined) {
         err = constants.WASI_EBADF;
+      } else if (
+        (fds.getDesc(linkFd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_SYMLINK) ===
+        0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
       } else {
         err = await processManager.filesystem.addSymlink(
           targetPath,
@@ -397,21 +418,32 @@ export default async function syscallCallback(
     }
 
     case "path_link": {
-      // eslint-disable-next-line @typescript-eslint/no-unused-vars
-      const { sharedBuffer, oldFd } = data as PathLinkArgs;
+      const { sharedBuffer, oldFd, oldFlags, oldPath, newFd, newPath } =
+        data as PathLinkArgs;
       const lck = new Int32Array(sharedBuffer, 0, 1);
 
       let err;
       const { fds } = processManager.processInfos[processId];
-      if (fds.getDesc(oldFd) !== undefined) {
-        const arr = new TextEncoder().encode("hard links are not supported\n");
-        err = (
-          await fds
-            .getDesc(constants.WASI_FD_STDERR)
-            .write(arr.buffer as ArrayBuffer)
-        ).err;
-      } else {
+      if (fds.getDesc(oldFd) === undefined || fds.getDesc(newFd) === undefined) {
         err = constants.WASI_EBADF;
+      } else if (
+        (fds.getDesc(oldFd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_LINK_SOURCE) ===
+          0n ||
+        (fds.getDesc(newFd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_LINK_TARGET) ===
+          0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else {
+        err = await processManager.filesystem.link(
+          fds.getDesc(oldFd),
+          oldPath,
+          oldFlags,
+          fds.getDesc(newFd),
+          newPath,
+          processId
+        );
       }
 
       Atomics.store(lck, 0, err);
@@ -427,7 +459,14 @@ export default async function syscallCallback(
 
       let err = constants.WASI_ESUCCESS;
       const { fds } = processManager.processInfos[processId];
-      if (fds.getDesc(fd) !== undefined) {
+      if (
+        fds.getDesc(fd) !== undefined &&
+        (fds.getDesc(fd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_READLINK) ===
+          0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else if (fds.getDesc(fd) !== undefined) {
         let __path;
         ({ err, path: __path } = await processManager.filesystem.readLink(
           fds.getDesc(fd),
@@ -460,9 +499,10 @@ export default async function syscallCallback(
 
       let err;
       const { fds } = processManager.processInfos[processId];
-      if (fds.getDesc(fd) !== undefined) {
+      const fdEntry = fds.getFdEntry(fd);
+      if (fdEntry !== undefined && fdEntry.preopen) {
         path.set(
-          new TextEncoder().encode(basename(fds.getDesc(fd).getPath())),
+          new TextEncoder().encode(basename(fdEntry.desc.getPath())),
           0
         );
         err = constants.WASI_ESUCCESS;
@@ -475,8 +515,10 @@ export default async function syscallCallback(
       break;
     }
 
+    case "fd_pwrite":
     case "fd_write": {
-      const { sharedBuffer, fd, contents, size } = data as FdWriteArgs;
+      const { sharedBuffer, fd, contents, size, offset } =
+        data as FdWriteArgs;
 
       const lck = new Int32Array(sharedBuffer, 0, 1);
       const written = new Int32Array(sharedBuffer, 4, 1);
@@ -495,17 +537,20 @@ export default async function syscallCallback(
           (fdstat.fs_rights_base & constants.WASI_RIGHT_FD_WRITE) ===
           0n
         ) {
-          err = constants.WASI_EACCES;
+          err = constants.WASI_ENOTCAPABLE;
         } else {
           const writeBuffer = new Uint8Array(size);
 
-          let offset = 0;
+          let bufOffset = 0;
           for (const content of contents) {
-            writeBuffer.set(content, offset);
-            offset += content.byteLength;
+            writeBuffer.set(content, bufOffset);
+            bufOffset += content.byteLength;
           }
 
-          const __res = await fds.getDesc(fd).write(writeBuffer.buffer);
+          const __res =
+            offset !== undefined
+              ? await fds.getDesc(fd).pwrite(writeBuffer.buffer, offset)
+              : await fds.getDesc(fd).write(writeBuffer.buffer);
           err = __res.err;
           written[0] = Number(__res.written);
         }
@@ -533,7 +578,7 @@ export default async function syscallCallback(
       } else {
         fdstat = fds.getDesc(fd).getFdstat();
         if ((fdstat.fs_rights_base & constants.WASI_RIGHT_FD_READ) == 0n) {
-          err = constants.WASI_EACCES;
+          err = constants.WASI_ENOTCAPABLE;
         } else if (fdstat.fs_filetype === constants.WASI_FILETYPE_DIRECTORY) {
           err = constants.WASI_EISDIR;
         } else if (
@@ -542,14 +587,16 @@ export default async function syscallCallback(
           err = constants.WASI_EINVAL;
         } else {
           let res;
-          if (offset) {
+          if (offset !== undefined) {
             res = await fds.getDesc(fd).pread(len, offset);
           } else {
             res = await fds.getDesc(fd).read(len, processId);
           }
           err = res.err;
-          readBuf.set(new Uint8Array(res.buffer));
-          readLen[0] = res.buffer.byteLength;
+          if (err === constants.WASI_ESUCCESS) {
+            readBuf.set(new Uint8Array(res.buffer));
+            readLen[0] = res.buffer.byteLength;
+          }
         }
       }
       Atomics.store(lck, 0, err);
@@ -574,12 +621,23 @@ export default async function syscallCallback(
       let err;
       const { fds } = processManager.processInfos[processId];
       if (fds.getDesc(dirFd) !== undefined) {
-        if (
+        const dirFdstat = fds.getDesc(dirFd).getFdstat();
+        let neededRights = constants.WASI_RIGHT_PATH_OPEN;
+        if (openFlags & constants.WASI_O_CREAT) {
+          neededRights |= constants.WASI_RIGHT_PATH_CREATE_FILE;
+        }
+        if (openFlags & constants.WASI_O_TRUNC) {
+          neededRights |= constants.WASI_RIGHT_PATH_FILESTAT_SET_SIZE;
+        }
+        if ((dirFdstat.fs_rights_base & neededRights) !== neededRights) {
+          err = constants.WASI_ENOTCAPABLE;
+        } else if (
           !(
             openFlags & constants.WASI_O_CREAT &&
             openFlags & constants.WASI_O_DIRECTORY
           )
         ) {
+          // descendants never get more rights than the directory passes down
           let desc;
           ({ err, desc } = await processManager.filesystem.openat(
             fds.getDesc(dirFd),
@@ -587,8 +645,8 @@ export default async function syscallCallback(
             lookupFlags,
             openFlags,
             fdFlags,
-            fsRightsBase,
-            fsRightsInheriting,
+            fsRightsBase & dirFdstat.fs_rights_inheriting,
+            fsRightsInheriting & dirFdstat.fs_rights_inheriting,
             processId
           ));
           if (err === constants.WASI_ESUCCESS) {
@@ -691,7 +749,7 @@ export default async function syscallCallback(
               await __desc.close();
           }
         } else {
-          err = constants.WASI_EACCES;
+          err = constants.WASI_ENOTCAPABLE;
         }
       }
 
@@ -719,7 +777,7 @@ export default async function syscallCallback(
             err = constants.WASI_EBADF;
           }
         } else {
-          err = constants.WASI_EACCES;
+          err = constants.WASI_ENOTCAPABLE;
         }
       }
 
@@ -737,6 +795,13 @@ export default async function syscallCallback(
       let err;
       const { fds } = processManager.processInfos[processId];
       if (
+        fds.getDesc(fd) !== undefined &&
+        (fds.getDesc(fd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_FD_READDIR) ===
+          0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else if (
         fds.getDesc(fd) !== undefined &&
         fds.getDesc(fd).getFdstat().fs_filetype ===
         constants.WASI_FILETYPE_DIRECTORY
@@ -802,7 +867,16 @@ export default async function syscallCallback(
 
       let err;
       const { fds } = processManager.processInfos[processId];
-      if (fds.getDesc(fd) !== undefined) {
+      const right =
+        action === "path_unlink_file"
+          ? constants.WASI_RIGHT_PATH_UNLINK_FILE
+          : constants.WASI_RIGHT_PATH_REMOVE_DIRECTORY;
+      if (
+        fds.getDesc(fd) !== undefined &&
+        (fds.getDesc(fd).getFdstat().fs_rights_base & right) === 0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else if (fds.getDesc(fd) !== undefined) {
         err = await processManager.filesystem.removeEntry(
           path,
           action !== "path_unlink_file",
@@ -823,7 +897,14 @@ export default async function syscallCallback(
 
       let err;
       const { fds } = processManager.processInfos[processId];
-      if (fds.getDesc(fd) !== undefined) {
+      if (
+        fds.getDesc(fd) !== undefined &&
+        (fds.getDesc(fd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_CREATE_DIRECTORY) ===
+          0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else if (fds.getDesc(fd) !== undefined) {
         err = await processManager.filesystem.createDir(
           path,
           fds.getDesc(fd),
@@ -851,7 +932,8 @@ export default async function syscallCallback(
       if (fdEntry !== undefined) {
         const fdstat = fdEntry.desc.getFdstat();
         fileType[0] = fdstat.fs_filetype;
-        fdFlags[0] = fdstat.fs_flags | fdEntry.fdFlags;
+        fdFlags[0] =
+          (fdstat.fs_flags & constants.WASI_STD_FDFLAG_MASK) | fdEntry.fdFlags;
         rightsBase[0] = fdstat.fs_rights_base;
         rightsInheriting[0] = fdstat.fs_rights_inheriting;
         err = constants.WASI_ESUCCESS;
@@ -868,13 +950,27 @@ export default async function syscallCallback(
       const lck = new Int32Array(sharedBuffer, 0, 1);
       const { fds } = processManager.processInfos[processId];
 
-      let err = await processManager.filesystem.move(
-        fds.getDesc(oldFd),
-        oldPath,
-        fds.getDesc(newFd),
-        newPath,
-        processId
-      );
+      let err;
+      if (fds.getDesc(oldFd) === undefined || fds.getDesc(newFd) === undefined) {
+        err = constants.WASI_EBADF;
+      } else if (
+        (fds.getDesc(oldFd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_RENAME_SOURCE) ===
+          0n ||
+        (fds.getDesc(newFd).getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_PATH_RENAME_TARGET) ===
+          0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else {
+        err = await processManager.filesystem.move(
+          fds.getDesc(oldFd),
+          oldPath,
+          fds.getDesc(newFd),
+          newPath,
+          processId
+        );
+      }
 
       Atomics.store(lck, 0, err);
       Atomics.notify(lck, 0);
@@ -890,10 +986,8 @@ export default async function syscallCallback(
       if (fds.getDesc(fd) !== undefined) {
         let fdstat = fds.getDesc(fd).getFdstat();
         if ((fdstat.fs_rights_base & constants.WASI_RIGHT_FD_TELL) !== 0n) {
-          if (
-            fdstat.fs_filetype === constants.WASI_FILETYPE_REGULAR_FILE ||
-            fdstat.fs_filetype === constants.WASI_FILETYPE_SYMBOLIC_LINK
-          ) {
+          // devices decide themselves whether they have a position, like in fd_seek
+          if (fdstat.fs_filetype !== constants.WASI_FILETYPE_DIRECTORY) {
             const result = await fds.getDesc(fd).seek(0n, constants.WASI_WHENCE_CUR);
             offset[0] = BigInt(result.offset);
             err = result.err;
@@ -901,7 +995,7 @@ export default async function syscallCallback(
             err = constants.WASI_EBADF;
           }
         } else {
-          err = constants.WASI_EACCES;
+          err = constants.WASI_ENOTCAPABLE;
         }
       } else {
         err = constants.WASI_EBADF;
@@ -920,42 +1014,42 @@ export default async function syscallCallback(
 
       const { fds } = processManager.processInfos[processId];
       let desc = fds.getDesc(fd);
-      if (desc && path !== undefined) {
+      const right =
+        path !== undefined
+          ? constants.WASI_RIGHT_PATH_FILESTAT_SET_TIMES
+          : constants.WASI_RIGHT_FD_FILESTAT_SET_TIMES;
+      if (desc && !(desc.getFdstat().fs_rights_base & right)) {
+        err = constants.WASI_ENOTCAPABLE;
+        desc = undefined;
+      } else if (desc && path !== undefined) {
         const res = await processManager.filesystem.openat(desc, path, flags);
         desc = res.desc;
         err = res.err;
       }
 
       if (desc) {
-        let fdstat = desc.getFdstat();
         if (
-          !(fdstat.fs_rights_base & constants.WASI_RIGHT_FD_FILESTAT_SET_TIMES)
+          !(
+            ((fst_flags & constants.WASI_FSTFLAGS_ATIM_NOW) !== 0 &&
+              (fst_flags & constants.WASI_FSTFLAGS_ATIM) !== 0) ||
+            ((fst_flags & constants.WASI_FSTFLAGS_MTIM_NOW) !== 0 &&
+              (fst_flags & constants.WASI_FSTFLAGS_MTIM) !== 0)
+          )
         ) {
-          err = constants.WASI_EACCES;
-        } else {
-          if (
-            !(
-              ((fst_flags & constants.WASI_FSTFLAGS_ATIM_NOW) !== 0 &&
-                (fst_flags & constants.WASI_FSTFLAGS_ATIM) !== 0) ||
-              ((fst_flags & constants.WASI_FSTFLAGS_MTIM_NOW) !== 0 &&
-                (fst_flags & constants.WASI_FSTFLAGS_MTIM) !== 0)
-            )
-          ) {
-            let __mtim, __atim;
-            if ((fst_flags & constants.WASI_FSTFLAGS_ATIM) !== 0) {
-              __atim = st_atim;
-            } else if ((fst_flags & constants.WASI_FSTFLAGS_ATIM_NOW) !== 0) {
-              __atim = msToNs(performance.now());
-            }
-            if ((fst_flags & constants.WASI_FSTFLAGS_MTIM) !== 0) {
-              __mtim = st_mtim;
-            } else if ((fst_flags & constants.WASI_FSTFLAGS_MTIM_NOW) !== 0) {
-              __mtim = msToNs(performance.now());
-            }
-            err = await desc.setFilestatTimes(__atim, __mtim);
-          } else {
-            err = constants.WASI_EINVAL;
+          let __mtim, __atim;
+          if ((fst_flags & constants.WASI_FSTFLAGS_ATIM) !== 0) {
+            __atim = st_atim;
+          } else if ((fst_flags & constants.WASI_FSTFLAGS_ATIM_NOW) !== 0) {
+            __atim = msToNs(performance.now());
+          }
+          if ((fst_flags & constants.WASI_FSTFLAGS_MTIM) !== 0) {
+            __mtim = st_mtim;
+          } else if ((fst_flags & constants.WASI_FSTFLAGS_MTIM_NOW) !== 0) {
+            __mtim = msToNs(performance.now());
           }
+          err = await desc.setFilestatTimes(__atim, __mtim);
+        } else {
+          err = constants.WASI_EINVAL;
         }
       }
       Atomics.store(lck, 0, err);
@@ -989,6 +1083,145 @@ export default async function syscallCallback(
 
       break;
     }
+    case "fd_fdstat_set_rights": {
+      const { sharedBuffer, fd, rightsBase, rightsInheriting } =
+        data as FdFdstatSetRightsArgs;
+      const lck = new Int32Array(sharedBuffer, 0, 1);
+      const { fds } = processManager.processInfos[processId];
+
+      let err;
+      const desc = fds.getDesc(fd);
+      if (desc === undefined) {
+        err = constants.WASI_EBADF;
+      } else {
+        // rights can only be dropped, never regained
+        const fdstat = desc.getFdstat();
+        if (
+          (rightsBase & ~fdstat.fs_rights_base) !== 0n ||
+          (rightsInheriting & ~fdstat.fs_rights_inheriting) !== 0n
+        ) {
+          err = constants.WASI_ENOTCAPABLE;
+        } else {
+          err = await desc.setFdstatRights(rightsBase, rightsInheriting);
+        }
+      }
+
+      Atomics.store(lck, 0, err);
+      Atomics.notify(lck, 0);
+      break;
+    }
+    case "fd_filestat_set_size": {
+      const { sharedBuffer, fd, size } = data as FdFilestatSetSizeArgs;
+      const lck = new Int32Array(sharedBuffer, 0, 1);
+      const { fds } = processManager.processInfos[processId];
+
+      let err;
+      const desc = fds.getDesc(fd);
+      if (desc === undefined) {
+        err = constants.WASI_EBADF;
+      } else if (
+        (desc.getFdstat().fs_rights_base &
+          constants.WASI_RIGHT_FD_FILESTAT_SET_SIZE) ===
+        0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else {
+        err = await desc.truncate(size);
+      }
+
+      Atomics.store(lck, 0, err);
+      Atomics.notify(lck, 0);
+      break;
+    }
+    case "fd_allocate": {
+      const { sharedBuffer, fd, offset, len } = data as FdAllocateArgs;
+      const lck = new Int32Array(sharedBuffer, 0, 1);
+      const { fds } = processManager.processInfos[processId];
+
+      let err;
+      const desc = fds.getDesc(fd);
+      if (desc === undefined) {
+        err = constants.WASI_EBADF;
+      } else if (
+        (desc.getFdstat().fs_rights_base & constants.WASI_RIGHT_FD_ALLOCATE) ===
+        0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else if (
+        desc.getFdstat().fs_filetype === constants.WASI_FILETYPE_DIRECTORY
+      ) {
+        err = constants.WASI_EISDIR;
+      } else if (
+        desc.getFdstat().fs_filetype !== constants.WASI_FILETYPE_REGULAR_FILE
+      ) {
+        err = constants.WASI_EINVAL;
+      } else {
+        // files only grow, data that is already there is left untouched
+        const res = await desc.getFilestat();
+        err = res.err;
+        if (
+          err === constants.WASI_ESUCCESS &&
+          res.filestat.size < offset + len
+        ) {
+          err = await desc.truncate(offset + len);
+        }
+      }
+
+      Atomics.store(lck, 0, err);
+      Atomics.notify(lck, 0);
+      break;
+    }
+    case "fd_datasync":
+    case "fd_sync": {
+      const { sharedBuffer, fd, dataOnly } = data as FdSyncArgs;
+      const lck = new Int32Array(sharedBuffer, 0, 1);
+      const { fds } = processManager.processInfos[processId];
+
+      let err;
+      const desc = fds.getDesc(fd);
+      const right = dataOnly
+        ? constants.WASI_RIGHT_FD_DATASYNC
+        : constants.WASI_RIGHT_FD_SYNC;
+      if (desc === undefined) {
+        err = constants.WASI_EBADF;
+      } else if ((desc.getFdstat().fs_rights_base & right) === 0n) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else {
+        err = await desc.sync();
+      }
+
+      Atomics.store(lck, 0, err);
+      Atomics.notify(lck, 0);
+      break;
+    }
+    case "fd_advise": {
+      const { sharedBuffer, fd, advice } = data as FdAdviseArgs;
+      const lck = new Int32Array(sharedBuffer, 0, 1);
+      const { fds } = processManager.processInfos[processId];
+
+      // advice is only a hint, it is validated but not acted upon
+      let err;
+      const desc = fds.getDesc(fd);
+      if (desc === undefined) {
+        err = constants.WASI_EBADF;
+      } else if (
+        (desc.getFdstat().fs_rights_base & constants.WASI_RIGHT_FD_ADVISE) ===
+        0n
+      ) {
+        err = constants.WASI_ENOTCAPABLE;
+      } else if (
+        advice < constants.WASI_ADVICE_NORMAL ||
+        advice > constants.WASI_ADVICE_NOREUSE
+      ) {
+        err = constants.WASI_EINVAL;
+      } else {
+        err = constants.WASI_ESUCCESS;
+      }
+
+      Atomics.store(lck, 0, err);
+      Atomics.notify(lck, 0);
+      break;
+    }
     case "fd_renumber": {
       const { sharedBuffer, fd, newFd } = data as FdRenumberArgs;
       const lck = new Int32Array(sharedBuffer, 0, 1);
@@ -1000,7 +1233,7 @@ export default async function syscallCallback(
 
       if (srcEntry === undefined) {
         err = constants.WASI_EBADF;
-      } else {
+      } else if (fd !== newFd) {
         if (dstEntry !== undefined) {
           // ignore close errors
           await dstEntry.desc.close();
@@ -1051,6 +1284,17 @@ export default async function syscallCallback(
                   eventType: sub.eventType,
                   nbytes: 0n,
                 });
+              } else if (
+                (fd.getFdstat().fs_rights_base &
+                  constants.WASI_RIGHT_POLL_FD_READWRITE) ===
+                0n
+              ) {
+                __subPromise = Promise.resolve({
+                  userdata: sub.userdata,
+                  error: constants.WASI_ENOTCAPABLE,
+                  eventType: sub.eventType,
+                  nbytes: 0n,
+                });
               } else {
                 __subPromise = fd.addPollSub(
                   sub.userdata,
@@ -1158,8 +1402,11 @@ export default async function syscallCallback(
       const lck = new Int32Array(sharedBuffer, 0, 1);
 
       let exitStatus = constants.WASI_ESUCCESS;
-      if (signalNumber !== constants.WASI_SIGKILL) {
-        // For now, we support SigKill only
+      if (
+        signalNumber !== constants.WASI_SIGKILL &&
+        signalNumber !== constants.WASI_SIGINT
+      ) {
+        // For now, we support SigKill and SigInt only
         console.log(
           `Process=${processId} send usupported singnal: ${signalNumber}!`
         );
@@ -1169,6 +1416,10 @@ export default async function syscallCallback(
           `Process=${processId} send singnal to process ${processId} that does not exist!`
         );
         exitStatus = constants.WASI_ESRCH;
+      } else if (signalNumber === constants.WASI_SIGINT) {
+        // SigInt is delivered the same way as Ctrl-C in a terminal, processes
+        // that attached an event source receive it instead of being terminated
+        processManager.publishEvent(constants.WASI_EXT_EVENT_SIGINT, processId);
       } else {
         // In bash:
         // When a command terminates on a fatal signal whose number is N,
@@ -1181,7 +1432,7 @@ export default async function syscallCallback(
       break;
     }
     case "ioctl": {
-      const { sharedBuffer, fd, command } = data as IoctlArgs;
+      const { sharedBuffer, fd, driver, command } = data as IoctlArgs;
 
       const lck = new Int32Array(sharedBuffer, 0, 1);
       const argBuffer = new Uint8Array(sharedBuffer, 4);
@@ -1189,13 +1440,20 @@ export default async function syscallCallback(
       const { fds } = processManager.processInfos[processId];
       let desc = fds.getDesc(fd);
 
-      if (fd === undefined) {
+      if (desc === undefined) {
         Atomics.store(lck, 0, constants.WASI_EBADF);
         Atomics.notify(lck, 0);
         break;
       }
 
-      const err = await desc.ioctl(command, argBuffer);
+      let err;
+      if (driver !== DEVICE_IOCTL_DRIVER) {
+        err = await desc.ioctl(command, argBuffer);
+      } else if (desc instanceof AbstractVirtualDeviceDescriptor) {
+        err = await desc.deviceIoctl(command, argBuffer);
+      } else {
+        err = constants.WASI_ENOTTY;
+      }
 
       Atomics.store(lck, 0, err);
       Atomics.notify(lck, 0);
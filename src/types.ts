@@ -18,6 +18,7 @@ export type FdWriteArgs = {
   fd: number;
   contents: Uint8Array[];
   size: number;
+  offset?: bigint;
 };
 
 export type FdCloseArgs = { sharedBuffer: SharedArrayBuffer; fd: number };
@@ -213,6 +214,7 @@ export type KillArgs = {
 export type IoctlArgs = {
   sharedBuffer: SharedArrayBuffer;
   fd: number;
+  driver: number;
   command: number;
 };
 
@@ -228,6 +230,40 @@ export type FdFdstatSetFlagsArgs = {
   flags: number;
 };
 
+export type FdFdstatSetRightsArgs = {
+  sharedBuffer: SharedArrayBuffer;
+  fd: number;
+  rightsBase: bigint;
+  rightsInheriting: bigint;
+};
+
+export type FdFilestatSetSizeArgs = {
+  sharedBuffer: SharedArrayBuffer;
+  fd: number;
+  size: bigint;
+};
+
+export type FdAllocateArgs = {
+  sharedBuffer: SharedArrayBuffer;
+  fd: number;
+  offset: bigint;
+  len: bigint;
+};
+
+export type FdSyncArgs = {
+  sharedBuffer: SharedArrayBuffer;
+  fd: number;
+  dataOnly: boolean;
+};
+
+export type FdAdviseArgs = {
+  sharedBuffer: SharedArrayBuffer;
+  fd: number;
+  offset: bigint;
+  len: bigint;
+  advice: number;
+};
+
 export type MountArgs = {
   sharedBuffer: SharedArrayBuffer;
   sourceFd: number;
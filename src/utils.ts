@@ -55,7 +55,11 @@ export function msToNs(ms: number): bigint {
   return ns + decimal;
 }
 
-export function now(clockId: number, cpuTimeStart: bigint): bigint {
+// Returns undefined for clock ids that don't exist, callers return EINVAL
+export function now(
+  clockId: number,
+  cpuTimeStart: bigint
+): bigint | undefined {
   switch (clockId) {
     case constants.WASI_CLOCK_MONOTONIC:
       return msToNs(performance.now());
@@ -65,8 +69,22 @@ export function now(clockId: number, cpuTimeStart: bigint): bigint {
     case constants.WASI_CLOCK_THREAD_CPUTIME_ID:
       return msToNs(performance.now()) - cpuTimeStart;
     default:
-      // TODO: that a temporary fix as we get clockId = 10^9
-      return msToNs(performance.now()) - cpuTimeStart;
+      return undefined;
+  }
+}
+
+// Browsers coarsen performance.now() to 5us in cross-origin isolated contexts, which
+// SharedArrayBuffer requires, and Date.now() only counts whole milliseconds.
+export function clockRes(clockId: number): bigint | undefined {
+  switch (clockId) {
+    case constants.WASI_CLOCK_REALTIME:
+      return msToNs(1);
+    case constants.WASI_CLOCK_MONOTONIC:
+    case constants.WASI_CLOCK_PROCESS_CPUTIME_ID:
+    case constants.WASI_CLOCK_THREAD_CPUTIME_ID:
+      return msToNs(0.005);
+    default:
+      return undefined;
   }
 }
 
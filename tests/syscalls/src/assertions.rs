@@ -0,0 +1,167 @@
+use std::cell::RefCell;
+use std::fmt::Debug;
+
+// Formats a call together with its arguments, e.g. call!("fd_seek", fd, 1, wasi::WHENCE_SET.raw())
+// gives "fd_seek(3, 1, 0)". Strings are printed quoted so empty and whitespace paths are visible.
+#[macro_export]
+macro_rules! call {
+    ($name:expr) => { format!("{}()", $name) };
+    ($name:expr, $($arg:expr),+) => {
+        format!("{}({})", $name, vec![$(format!("{:?}", $arg)),+].join(", "))
+    };
+}
+
+// Error codes returned by the wasi crate (wasi::Errno) and wasi_ext_lib (i32 exit codes)
+pub trait Errno: Copy {
+    fn code(&self) -> i32;
+}
+
+impl Errno for wasi::Errno {
+    fn code(&self) -> i32 { self.raw() as i32 }
+}
+
+impl Errno for i32 {
+    fn code(&self) -> i32 { *self }
+}
+
+// single sub-result of a test
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub call: String,
+    // outcome of the call (Ok, Err(errno) or whether the condition held), compared against
+    // a reference run in differential mode
+    pub observed: String,
+    // what the call returned, compared only if values are recorded as well
+    pub value: Option<String>,
+    pub passed: bool,
+    // why the check failed, or why it wasn't made for skipped checks that passed
+    pub message: Option<String>,
+}
+
+thread_local! {
+    static RECORDED: RefCell<Vec<Check>> = RefCell::new(Vec::new());
+}
+
+// returns checks recorded since the last call, used by the test runner after each test
+pub fn take_checks() -> Vec<Check> {
+    RECORDED.with(|r| r.borrow_mut().drain(..).collect())
+}
+
+// single test result summarizing failed checks
+pub fn join_failures<S: AsRef<str>>(failures: &[S]) -> Result<(), String> {
+    match failures.len() {
+        0 => Ok(()),
+        1 => Err(String::from(failures[0].as_ref())),
+        n => Err(format!(
+            "{} checks failed: {}", n,
+            failures.iter().map(|f| f.as_ref()).collect::<Vec<&str>>().join("; "))),
+    }
+}
+
+// Collects results of checks made by a test. A failed check doesn't stop the test,
+// all failures are reported by finish().
+pub struct Checks {
+    failures: Vec<String>,
+}
+
+impl Checks {
+    pub fn new() -> Self {
+        Checks { failures: Vec::new() }
+    }
+
+    fn record(&mut self, call: &str, observed: String, value: Option<String>, failure: Option<String>) -> bool {
+        let passed = failure.is_none();
+        let message = failure.map(|msg| format!("In {}: {}", call, msg));
+        if let Some(m) = &message {
+            self.failures.push(m.clone());
+        }
+        RECORDED.with(|r| r.borrow_mut().push(Check {
+            call: String::from(call),
+            observed,
+            value,
+            passed,
+            message,
+        }));
+        passed
+    }
+
+    // expect the call to succeed and return its output
+    pub fn success<T: Debug, E: Errno>(&mut self, call: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(v) => {
+                self.record(call, String::from("Ok"), Some(format!("{:?}", v)), None);
+                Some(v)
+            }
+            Err(e) => {
+                self.record(call, format!("Err({})", e.code()), None, Some(format!(
+                    "syscall failed unexpectedly (error code: {})", e.code())));
+                None
+            }
+        }
+    }
+
+    // expect the call to fail with the given error code, msg describes an unexpected success
+    pub fn error<T: Debug, E: Errno>(&mut self, call: &str, result: Result<T, E>, errno: E, msg: &str) -> bool {
+        match result {
+            Ok(v) => self.record(call, String::from("Ok"), Some(format!("{:?}", v)), Some(String::from(msg))),
+            Err(e) => {
+                let observed = format!("Err({})", e.code());
+                if e.code() == errno.code() {
+                    self.record(call, observed, None, None)
+                } else {
+                    self.record(call, observed, None, Some(format!(
+                        "unexpected error code (expected {}, got {})", errno.code(), e.code())))
+                }
+            }
+        }
+    }
+
+    // expect the call to succeed with the given output
+    pub fn output<T: PartialEq + Debug, E: Errno>(
+        &mut self,
+        call: &str,
+        result: Result<T, E>,
+        expected: T
+    ) -> bool {
+        match self.success(call, result) {
+            Some(got) => self.equal(call, "output", expected, got),
+            None => false,
+        }
+    }
+
+    // compare a value observed after the call, `what` names the compared property
+    pub fn equal<T: PartialEq + Debug>(&mut self, call: &str, what: &str, expected: T, got: T) -> bool {
+        let (observed, value) = (format!("{:?}", expected == got), Some(format!("{:?}", got)));
+        if expected == got {
+            self.record(call, observed, value, None)
+        } else {
+            self.record(call, observed, value, Some(format!(
+                "unexpected {} (expected {:?}, got {:?})", what, expected, got)))
+        }
+    }
+
+    // condition describes the outcome of the call, msg is reported if it doesn't hold
+    pub fn check(&mut self, call: &str, condition: bool, msg: &str) -> bool {
+        let observed = format!("{:?}", condition);
+        if condition {
+            self.record(call, observed, None, None)
+        } else {
+            self.record(call, observed, None, Some(String::from(msg)))
+        }
+    }
+
+    // records that checks of the call weren't made, e.g. because the environment lacks a service
+    pub fn skip(&mut self, call: &str, reason: &str) {
+        RECORDED.with(|r| r.borrow_mut().push(Check {
+            call: String::from(call),
+            observed: String::from("Skipped"),
+            value: None,
+            passed: true,
+            message: Some(format!("In {}: skipped, {}", call, reason)),
+        }));
+    }
+
+    pub fn finish(&self) -> Result<(), String> {
+        join_failures(&self.failures)
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use constants;
+
+// Multi-process tests spawn the syscalls_test binary again with this argument followed
+// by a helper role, e.g. `syscalls_test __child exit-with-code 3`.
+pub const CHILD_ARG: &str = "__child";
+
+// binary location can be overridden if syscalls_test is not installed in PATH
+const BIN_ENV: &str = "SYSCALLS_TEST_BIN";
+const DEFAULT_BIN: &str = "/usr/bin/syscalls_test";
+
+// highest descriptor reported by dump-fds
+const MAX_FD: wasi::Fd = 64;
+
+// write-N-bytes output is written in chunks so it can fill pipes of any size
+const WRITE_CHUNK: usize = 4096;
+
+// how often /proc is checked while waiting for a child to exit
+const EXIT_POLL_STEP: Duration = Duration::from_millis(50);
+
+// sleep-until-killed wakes up this often to check its timeout
+const SLEEP_STEP: Duration = Duration::from_millis(100);
+
+// path of this binary, argv[0] only contains the file name
+pub fn binary_path() -> String {
+    if let Ok(path) = env::var(BIN_ENV) {
+        return path;
+    }
+    let name = env::args().next().unwrap_or_default();
+    if let Ok(paths) = env::var("PATH") {
+        for dir in paths.split(':').filter(|d| !d.is_empty()) {
+            let path = format!("{}/{}", dir, name);
+            if Path::new(&path).is_file() {
+                return path;
+            }
+        }
+    }
+    String::from(DEFAULT_BIN)
+}
+
+// spawns this binary in the given helper role
+pub fn spawn(
+    role: &str,
+    args: &[&str],
+    env: &HashMap<String, String>,
+    background: bool,
+    redirects: &[wasi_ext_lib::Redirect]
+) -> Result<(wasi_ext_lib::ExitCode, wasi_ext_lib::Pid), wasi_ext_lib::ExitCode> {
+    let mut argv = vec![CHILD_ARG, role];
+    argv.extend_from_slice(args);
+    wasi_ext_lib::spawn(&binary_path(), &argv, env, background, redirects)
+}
+
+// exit status of a foreground child, spawn reports non-zero statuses as errors
+pub fn exit_status(
+    result: &Result<(wasi_ext_lib::ExitCode, wasi_ext_lib::Pid), wasi_ext_lib::ExitCode>
+) -> wasi_ext_lib::ExitCode {
+    match result {
+        Ok((code, _)) => *code,
+        Err(code) => *code,
+    }
+}
+
+// There is no wait syscall, a background child is gone once /proc/<pid> disappears.
+// Returns false if it is still there after timeout.
+pub fn wait_for_exit(pid: wasi_ext_lib::Pid, timeout: Duration) -> bool {
+    let path = format!("proc/{}", pid);
+    let start = Instant::now();
+    loop {
+        if let Err(wasi::ERRNO_NOENT) = unsafe { wasi::path_filestat_get(constants::PWD_DESC, 0, &path) } {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(EXIT_POLL_STEP);
+    }
+}
+
+// usage of every helper role, printed when a child is spawned with an unknown role
+const ROLES: &[&str] = &[
+    "exit-with-code CODE - exit immediately with CODE",
+    "echo-stdin - copy stdin to stdout until EOF",
+    "dump-args - print argv, one argument per line",
+    "dump-env KEY... - print KEY=VALUE lines, unset variables are skipped",
+    "dump-fds - print \"FD FILETYPE FLAGS RIGHTS_BASE\" for every open descriptor",
+    "write-N-bytes N - write N bytes of pattern() to stdout",
+    "sleep-until-killed [SECONDS] - print \"ready\" and sleep, exit 1 after SECONDS",
+    "kill-self - send SIGKILL to itself, exit 1 if still alive",
+    "interrupt PID MILLISECONDS - send SIGINT to PID after MILLISECONDS",
+    "read-until-interrupted MILLISECONDS - read stdin until interrupt sends SIGINT, exit 1 on EOF",
+];
+
+// args - arguments following CHILD_ARG, returns the exit code of the helper
+pub fn run(args: &[String]) -> i32 {
+    let (role, args) = match args.split_first() {
+        Some((role, args)) => (role.as_str(), args),
+        None => {
+            eprintln!("{}: missing helper role\n{}", CHILD_ARG, usage());
+            return 2;
+        }
+    };
+    let result = match role {
+        "exit-with-code" => match parse_arg::<i32>(args.first(), "CODE") {
+            Ok(code) => return code,
+            Err(e) => Err(e),
+        },
+        "echo-stdin" => echo_stdin(),
+        "dump-args" => dump_args(),
+        "dump-env" => dump_env(args),
+        "dump-fds" => dump_fds(),
+        "write-N-bytes" => parse_arg::<usize>(args.first(), "N").and_then(write_n_bytes),
+        "sleep-until-killed" => match args.first() {
+            Some(_) => parse_arg::<u64>(args.first(), "SECONDS").and_then(|s| sleep_until_killed(Some(s))),
+            None => sleep_until_killed(None),
+        },
+        "kill-self" => kill_self(),
+        "interrupt" => parse_arg::<wasi_ext_lib::Pid>(args.first(), "PID")
+            .and_then(|pid| parse_arg::<u64>(args.get(1), "MILLISECONDS").map(|ms| (pid, ms)))
+            .and_then(|(pid, ms)| interrupt(pid, ms)),
+        "read-until-interrupted" => parse_arg::<u64>(args.first(), "MILLISECONDS").and_then(read_until_interrupted),
+        _ => {
+            eprintln!("{}: unknown helper role: {}\n{}", CHILD_ARG, role, usage());
+            return 2;
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{} {}: {}", CHILD_ARG, role, e);
+            1
+        }
+    }
+}
+
+fn usage() -> String {
+    let roles: Vec<String> = ROLES.iter().map(|u| format!("    {}", u)).collect();
+    format!("Helper roles:\n{}", roles.join("\n"))
+}
+
+fn parse_arg<T: FromStr>(arg: Option<&String>, name: &str) -> Result<T, String> {
+    match arg.map(|a| a.parse::<T>()) {
+        Some(Ok(v)) => Ok(v),
+        Some(Err(_)) => Err(format!("invalid {}: {}", name, arg.unwrap())),
+        None => Err(format!("missing {}", name)),
+    }
+}
+
+// contents written by write-N-bytes, byte i of the output is pattern(n)[i]
+pub fn pattern(n: usize) -> Vec<u8> {
+    (0..n).map(|i| b'a' + (i % 26) as u8).collect()
+}
+
+fn write_stdout(output: &[u8]) -> Result<(), String> {
+    let mut stdout = io::stdout();
+    match stdout.write_all(output).and_then(|_| stdout.flush()) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(format!("Couldn't write to stdout: {}", e)),
+    }
+}
+
+// prints the whole command line, one argument per line
+fn dump_args() -> Result<(), String> {
+    let output: String = env::args().map(|a| format!("{}\n", a)).collect();
+    write_stdout(output.as_bytes())
+}
+
+// prints KEY=VALUE lines for given keys, unset variables are skipped
+fn dump_env(keys: &[String]) -> Result<(), String> {
+    let output: String = keys.iter()
+        .filter_map(|k| env::var(k).ok().map(|v| format!("{}={}\n", k, v)))
+        .collect();
+    write_stdout(output.as_bytes())
+}
+
+// prints "FD FILETYPE FLAGS RIGHTS_BASE" lines for every open descriptor
+fn dump_fds() -> Result<(), String> {
+    let mut output = String::new();
+    for fd in 0..=MAX_FD {
+        if let Ok(stat) = unsafe { wasi::fd_fdstat_get(fd) } {
+            output.push_str(&fd_line(fd, &stat));
+        }
+    }
+    write_stdout(output.as_bytes())
+}
+
+// line describing a descriptor in dump-fds output
+pub fn fd_line(fd: wasi::Fd, stat: &wasi::Fdstat) -> String {
+    format!("{} {} {} {:#x}\n", fd, stat.fs_filetype.raw(), stat.fs_flags, stat.fs_rights_base)
+}
+
+fn echo_stdin() -> Result<(), String> {
+    let mut input = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut input) {
+        return Err(format!("Couldn't read stdin: {}", e));
+    }
+    write_stdout(&input)
+}
+
+fn write_n_bytes(n: usize) -> Result<(), String> {
+    for chunk in pattern(n).chunks(WRITE_CHUNK) {
+        write_stdout(chunk)?;
+    }
+    Ok(())
+}
+
+// The parent can wait for the "ready" line before sending a signal. Without a timeout
+// the helper never exits on its own.
+fn sleep_until_killed(timeout: Option<u64>) -> Result<(), String> {
+    write_stdout(b"ready\n")?;
+    let start = Instant::now();
+    loop {
+        thread::sleep(SLEEP_STEP);
+        if let Some(secs) = timeout {
+            if start.elapsed() >= Duration::from_secs(secs) {
+                return Err(format!("not killed within {} seconds", secs));
+            }
+        }
+    }
+}
+
+fn kill_self() -> Result<(), String> {
+    let pid = wasi_ext_lib::getpid().map_err(|e| format!("getpid failed ({})", e))?;
+    match wasi_ext_lib::kill(pid, wasi::SIGNAL_KILL) {
+        Ok(()) => Err(String::from("still alive after SIGKILL")),
+        Err(e) => Err(format!("kill failed ({})", e)),
+    }
+}
+
+fn interrupt(pid: wasi_ext_lib::Pid, millis: u64) -> Result<(), String> {
+    thread::sleep(Duration::from_millis(millis));
+    wasi_ext_lib::kill(pid, wasi::SIGNAL_INT).map_err(|e| format!("kill failed ({})", e))
+}
+
+// SIGINT is sent by a background helper, the parent can't learn the pid of a foreground child
+fn read_until_interrupted(millis: u64) -> Result<(), String> {
+    let pid = wasi_ext_lib::getpid().map_err(|e| format!("getpid failed ({})", e))?;
+    let (pid, millis) = (pid.to_string(), millis.to_string());
+    let redirects = [wasi_ext_lib::Redirect::Write((1, String::from("/dev/null")))];
+    if let Err(e) = spawn("interrupt", &[&pid, &millis], &HashMap::new(), true, &redirects) {
+        return Err(format!("Couldn't spawn interrupt helper ({})", e));
+    }
+    let mut input = Vec::new();
+    match io::stdin().read_to_end(&mut input) {
+        Ok(_) => Err(String::from("stdin ended before SIGINT")),
+        Err(e) => Err(format!("Couldn't read stdin: {}", e)),
+    }
+}
@@ -0,0 +1,163 @@
+use constants;
+use report::Format;
+
+pub const USAGE: &str = "\
+usage: syscalls_test [--list] [--exclude PATTERN]... [--shard INDEX/COUNT]
+                     [--format FORMAT] [--output PATH] [--record PATH] [--diff PATH]
+                     [--record-values] [PATTERN]...
+
+  PATTERN              run only tests matching the pattern ('*' and '?' wildcards are supported)
+  -x, --exclude PATTERN
+                       skip tests matching the pattern
+  -l, --list           print names of selected tests and exit
+  --shard INDEX/COUNT  split selected tests into COUNT shards and run the INDEX-th one (1-based)
+  -f, --format FORMAT  report format: text (default), tap, junit or json (newline-delimited)
+  -o, --output PATH    write the report to PATH instead of stdout (required for junit)
+  --record PATH        write outcomes of all syscalls made by tests to PATH
+  --diff PATH          judge tests by comparing syscall outcomes with a trace recorded by
+                       a reference runtime (see --record) instead of built-in expectations
+  --record-values      also record and compare values returned by syscalls, inode numbers and
+                       timestamps are masked (has to match the way the reference was recorded)
+  -h, --help           print this message and exit
+
+Without arguments (or with the canonical `wasi syscalls` arguments) all tests are run.";
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub list: bool,
+    pub help: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub shard: Option<(usize, usize)>,
+    pub format: Format,
+    pub output: Option<String>,
+    pub record: Option<String>,
+    pub diff: Option<String>,
+    pub record_values: bool,
+    // args_get and args_sizes_get expect the command line from constants::ARGV
+    pub canonical_argv: bool,
+}
+
+impl Options {
+    pub fn selects(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, name)))
+            && !self.exclude.iter().any(|p| glob_match(p, name))
+    }
+
+    // apply filters and sharding, preserving the order of tests
+    pub fn select<'a, T>(&self, tests: &'a [(&'a str, T)]) -> Vec<&'a (&'a str, T)> {
+        let selected = tests.iter().filter(|(name, _)| self.selects(name));
+        match self.shard {
+            Some((index, count)) => selected
+                .enumerate()
+                .filter(|(i, _)| i % count == index - 1)
+                .map(|(_, t)| t)
+                .collect(),
+            None => selected.collect(),
+        }
+    }
+}
+
+fn is_canonical_argv(args: &[String]) -> bool {
+    args.len() == constants::ARGV.len() - 1
+        && args.iter().zip(&constants::ARGV[1..]).all(|(a, c)| a == c.trim_end_matches('\0'))
+}
+
+fn parse_shard(val: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid shard specification: {} (expected INDEX/COUNT)", val);
+    let (index, count) = match val.find('/') {
+        Some(i) => (&val[..i], &val[i + 1..]),
+        None => return Err(invalid()),
+    };
+    match (index.parse::<usize>(), count.parse::<usize>()) {
+        (Ok(i), Ok(c)) if i >= 1 && i <= c => Ok((i, c)),
+        _ => Err(invalid()),
+    }
+}
+
+// args - command line arguments without the program name
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options::default();
+    if args.is_empty() || is_canonical_argv(args) {
+        opts.canonical_argv = true;
+        return Ok(opts);
+    }
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-l" | "--list" => opts.list = true,
+            "-h" | "--help" => opts.help = true,
+            "-x" | "--exclude" => match iter.next() {
+                Some(p) => opts.exclude.push(p.clone()),
+                None => return Err(format!("Option {} requires an argument", arg)),
+            },
+            "--shard" => match iter.next() {
+                Some(s) => opts.shard = Some(parse_shard(s)?),
+                None => return Err(format!("Option {} requires an argument", arg)),
+            },
+            "-f" | "--format" => match iter.next() {
+                Some(f) => opts.format = Format::parse(f)?,
+                None => return Err(format!("Option {} requires an argument", arg)),
+            },
+            "-o" | "--output" => match iter.next() {
+                Some(path) => opts.output = Some(path.clone()),
+                None => return Err(format!("Option {} requires an argument", arg)),
+            },
+            "--record-values" => opts.record_values = true,
+            "--record" => match iter.next() {
+                Some(path) => opts.record = Some(path.clone()),
+                None => return Err(format!("Option {} requires an argument", arg)),
+            },
+            "--diff" => match iter.next() {
+                Some(path) => opts.diff = Some(path.clone()),
+                None => return Err(format!("Option {} requires an argument", arg)),
+            },
+            _ if arg.starts_with("--format=") => {
+                opts.format = Format::parse(&arg["--format=".len()..])?;
+            }
+            _ if arg.starts_with("--output=") => {
+                opts.output = Some(String::from(&arg["--output=".len()..]));
+            }
+            _ if arg.starts_with("--record=") => {
+                opts.record = Some(String::from(&arg["--record=".len()..]));
+            }
+            _ if arg.starts_with("--diff=") => {
+                opts.diff = Some(String::from(&arg["--diff=".len()..]));
+            }
+            _ if arg.starts_with("--exclude=") => {
+                opts.exclude.push(String::from(&arg["--exclude=".len()..]));
+            }
+            _ if arg.starts_with("--shard=") => {
+                opts.shard = Some(parse_shard(&arg["--shard=".len()..])?);
+            }
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            _ => opts.include.push(arg.clone()),
+        }
+    }
+    Ok(opts)
+}
+
+// shell-like wildcard matching: '*' matches any sequence, '?' matches a single character
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    // position of the last '*' in pattern and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ni = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
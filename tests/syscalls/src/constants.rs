@@ -4,9 +4,7 @@ pub const RIGHTS_STDOUT: u64 = 0x82000d1;
 pub const RIGHTS_STDERR: u64 = 0x82000d1;
 
 pub const SAMPLE_TEXT_FILENAME: &str = "text";
-pub const SAMPLE_TEXT_FILENAME_ABS: &str = "/text";
 pub const SAMPLE_DIR_FILENAME: &str = "dir";
-pub const SAMPLE_DIR_FILENAME_ABS: &str = "/dir";
 pub const SAMPLE_LINK_FILENAME: &str = "link";
 pub const SAMPLE_DIR_LINK_FILENAME: &str = "dir_link";
 
@@ -15,6 +13,9 @@ pub const SAMPLE_TEXT_LEN: usize = SAMPLE_TEXT.len();
 
 pub const PWD_DESC: wasi::Fd = 3;
 
+// kernel extension of fdflags, WASI_EXT_FDFLAG_CLOEXEC in src/constants.ts
+pub const FDFLAGS_CLOEXEC: wasi::Fdflags = 0x0040;
+
 pub const ARGV: [&str; 3] = ["syscalls_test\0", "wasi\0", "syscalls\0"];
 
 pub const SAMPLE_DIRENTRY_NAME: &str = "ent";
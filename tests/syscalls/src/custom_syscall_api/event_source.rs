@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use assertions::Checks;
+use child;
+use constants;
+use fixture::Fixture;
+use utils::{clock_subscription, fd_subscription, make_fifo, poll, read_file};
+
+// the reading child is interrupted this long after it starts
+const INTERRUPT_DELAY: &str = "500";
+
+// status of processes interrupted by SIGINT, EXIT_INTERRUPTED in src/constants.ts
+const EXIT_INTERRUPTED: wasi_ext_lib::ExitCode = 130;
+
+const MASKS: &[(&str, wasi_ext_lib::WasiEvents)] = &[
+    ("none", 0),
+    ("WINCH", wasi_ext_lib::WASI_EVENT_WINCH),
+    ("SIGINT", wasi_ext_lib::WASI_EVENT_SIGINT),
+    ("WINCH|SIGINT", wasi_ext_lib::WASI_EVENT_WINCH | wasi_ext_lib::WASI_EVENT_SIGINT),
+];
+
+// reads return pending events as a single u32
+const EVENTS_LEN: usize = 4;
+
+// how long an event source is polled to make sure it doesn't become readable
+const POLL_TIMEOUT: wasi::Timestamp = 100_000_000;
+
+const OUTPUT_FILENAME: &str = "fds";
+
+unsafe fn read_events(fd: wasi::Fd, len: usize) -> Result<Vec<u8>, wasi::Errno> {
+    let mut buf = vec![0u8; len];
+    let n = wasi::fd_read(fd, &[wasi::Iovec { buf: buf.as_mut_ptr(), buf_len: buf.len() }])?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+fn no_events() -> Vec<u8> {
+    0u32.to_le_bytes().to_vec()
+}
+
+fn event_source(t: &mut Checks, name: &str, mask: wasi_ext_lib::WasiEvents) -> Option<wasi::Fd> {
+    t.success(&call!("event_source_fd", name), wasi_ext_lib::event_source_fd(mask)).map(|fd| fd as wasi::Fd)
+}
+
+unsafe fn test_masks(t: &mut Checks) {
+    for &(name, mask) in MASKS {
+        let fd = match event_source(t, name, mask) {
+            Some(fd) => fd,
+            None => continue,
+        };
+        if let Some(stat) = t.success(&call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd)) {
+            t.equal(&call!("fd_fdstat_get", fd), "filetype", wasi::FILETYPE_CHARACTER_DEVICE, stat.fs_filetype);
+            t.check(
+                &call!("fd_fdstat_get", fd), stat.fs_flags & constants::FDFLAGS_CLOEXEC != 0,
+                &format!("event source without CLOEXEC flag (got {:#x})", stat.fs_flags));
+        }
+
+        // nothing happened since the descriptor was created
+        t.output(
+            &call!("poll_oneoff", "fd_read", fd, "clock", POLL_TIMEOUT),
+            poll(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, fd), clock_subscription(2, POLL_TIMEOUT)])
+                .map(|events| events.iter().map(|e| e.userdata).collect::<Vec<_>>()),
+            vec![2]);
+        t.output(&call!("fd_read", fd, EVENTS_LEN), read_events(fd, EVENTS_LEN), no_events());
+        t.error(
+            &call!("fd_read", fd, EVENTS_LEN - 2), read_events(fd, EVENTS_LEN - 2),
+            wasi::ERRNO_NOBUFS, "reading events into too small buffer succeeded");
+
+        // only sources that receive SIGINT can replace its default action
+        let call = call!("attach_sigint", fd);
+        if mask & wasi_ext_lib::WASI_EVENT_SIGINT != 0 {
+            t.success(&call, wasi_ext_lib::attach_sigint(fd as wasi_ext_lib::RawFd));
+        } else {
+            t.error(
+                &call, wasi_ext_lib::attach_sigint(fd as wasi_ext_lib::RawFd),
+                wasi::ERRNO_INVAL.raw() as i32, "attaching SIGINT to source without SIGINT in mask succeeded");
+        }
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    }
+}
+
+unsafe fn test_attach_errors(t: &mut Checks) {
+    t.error(
+        &call!("attach_sigint", constants::PWD_DESC), wasi_ext_lib::attach_sigint(constants::PWD_DESC as i32),
+        wasi::ERRNO_INVAL.raw() as i32, "attaching SIGINT to a directory succeeded");
+
+    if let Some(fd) = event_source(t, "SIGINT", wasi_ext_lib::WASI_EVENT_SIGINT) {
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        t.error(
+            &call!("attach_sigint", fd), wasi_ext_lib::attach_sigint(fd as wasi_ext_lib::RawFd),
+            wasi::ERRNO_BADF.raw() as i32, "attaching SIGINT to a closed descriptor succeeded");
+    }
+}
+
+unsafe fn test_cloexec(t: &mut Checks, fixture: &Fixture) {
+    let fd = match event_source(t, "SIGINT", wasi_ext_lib::WASI_EVENT_SIGINT) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // child shouldn't inherit the event source, attached or not
+    let redirects = [wasi_ext_lib::Redirect::Write((1, fixture.abs_path(OUTPUT_FILENAME)))];
+    for &attached in [false, true].iter() {
+        if attached && t.success(
+            &call!("attach_sigint", fd), wasi_ext_lib::attach_sigint(fd as wasi_ext_lib::RawFd)).is_none() {
+            break;
+        }
+        let call = call!("spawn", "dump-fds");
+        let result = child::spawn("dump-fds", &[], &HashMap::new(), false, &redirects);
+        if !t.equal(&call, "exit status", 0, child::exit_status(&result)) {
+            continue;
+        }
+        if let Some(output) = t.success(&call!("read_file", OUTPUT_FILENAME), read_file(fixture.fd, OUTPUT_FILENAME)) {
+            let fds = String::from_utf8_lossy(&output);
+            t.check(
+                &call, !fds.lines().any(|l| l.starts_with(&format!("{} ", fd))),
+                &format!("event source {} present in child (got {:?})", fd, fds));
+        }
+    }
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+unsafe fn test_blocking_read(t: &mut Checks, fixture: &Fixture) {
+    // stdin of the child is a FIFO kept open for writing, reads block until SIGINT arrives
+    let path = match make_fifo(t, fixture) {
+        Some(path) => path,
+        None => return,
+    };
+    let write_fd = match t.success(
+        &call!("path_open", constants::PWD_DESC, 0, path, 0, wasi::RIGHTS_FD_WRITE, 0, 0),
+        wasi::path_open(constants::PWD_DESC, 0, &path, 0, wasi::RIGHTS_FD_WRITE, 0, 0)) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // without an attached event source SIGINT terminates the child
+    let redirects = [wasi_ext_lib::Redirect::Read((0, fixture.preopen_path(&path)))];
+    let result = child::spawn("read-until-interrupted", &[INTERRUPT_DELAY], &HashMap::new(), false, &redirects);
+    t.equal(
+        &call!("spawn", "read-until-interrupted", INTERRUPT_DELAY), "exit status",
+        EXIT_INTERRUPTED, child::exit_status(&result));
+    t.success(&call!("fd_close", write_fd), wasi::fd_close(write_fd));
+}
+
+unsafe fn test_foreground_spawn(t: &mut Checks) {
+    let fd = match event_source(t, "SIGINT", wasi_ext_lib::WASI_EVENT_SIGINT) {
+        Some(fd) => fd,
+        None => return,
+    };
+    if t.success(&call!("attach_sigint", fd), wasi_ext_lib::attach_sigint(fd as wasi_ext_lib::RawFd)).is_none() {
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        return;
+    }
+
+    // attached event source receives SIGINT instead of the process being terminated,
+    // pending events keep it readable
+    let pid = match t.success(&call!("getpid"), wasi_ext_lib::getpid()) {
+        Some(pid) => pid,
+        None => {
+            t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+            return;
+        }
+    };
+    t.success(&call!("kill", "self", wasi::SIGNAL_INT.raw()), wasi_ext_lib::kill(pid, wasi::SIGNAL_INT));
+    for _ in 0..2 {
+        let call = call!("poll_oneoff", "fd_read", fd);
+        if let Some(events) = t.success(&call, poll(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, fd)])) {
+            t.check(
+                &call,
+                events.len() == 1 && events[0].userdata == 1 && events[0].error == wasi::ERRNO_SUCCESS
+                    && events[0].type_ == wasi::EVENTTYPE_FD_READ,
+                &format!("event source with pending SIGINT is not ready (got {:?})", events));
+        }
+    }
+
+    // pending SIGINT stops the next foreground spawn before the child is run
+    let call = call!("spawn", "exit-with-code", "0");
+    let result = child::spawn("exit-with-code", &["0"], &HashMap::new(), false, &[]);
+    t.equal(&call, "exit status", EXIT_INTERRUPTED, child::exit_status(&result));
+
+    // spawn consumes the event
+    t.output(&call!("fd_read", fd, EVENTS_LEN), read_events(fd, EVENTS_LEN), no_events());
+    let result = child::spawn("exit-with-code", &["0"], &HashMap::new(), false, &[]);
+    t.equal(&call, "exit status", 0, child::exit_status(&result));
+
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+pub fn test_event_source(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        test_masks(&mut t);
+        test_attach_errors(&mut t);
+        test_cloexec(&mut t, fixture);
+        test_blocking_read(&mut t, fixture);
+        test_foreground_spawn(&mut t);
+    }
+    t.finish()
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use assertions::Checks;
+use child;
+use constants;
+use fixture::Fixture;
+use utils::{clock_subscription, fd_subscription, make_fifo, open_end, poll, read, read_n, write, FIFO_DEV};
+
+// ends are told apart by rights, a descriptor with FD_WRITE is always a writer
+const READ_RIGHTS: wasi::Rights = wasi::RIGHTS_FD_READ | wasi::RIGHTS_POLL_FD_READWRITE
+    | wasi::RIGHTS_FD_SEEK | wasi::RIGHTS_FD_TELL;
+const WRITE_RIGHTS: wasi::Rights = wasi::RIGHTS_FD_WRITE | wasi::RIGHTS_POLL_FD_READWRITE
+    | wasi::RIGHTS_FD_SEEK | wasi::RIGHTS_FD_TELL;
+
+// sizes of consecutive writes, reads use a buffer of different size to cross chunk boundaries
+const CHUNKS: &[usize] = &[1, 4095, 4096, 4097, 10000];
+const READ_BUF_LEN: usize = 1000;
+const CHILD_BYTES: usize = 20000;
+
+// how long an empty FIFO is polled to make sure it doesn't become readable
+const POLL_TIMEOUT: wasi::Timestamp = 100_000_000;
+
+// userdata of events that occurred
+unsafe fn poll_userdata(subscriptions: &[wasi::Subscription]) -> Result<Vec<wasi::Userdata>, wasi::Errno> {
+    poll(subscriptions).map(|events| events.iter().map(|e| e.userdata).collect())
+}
+
+unsafe fn test_stream(t: &mut Checks, path: &str) {
+    let read_fd = match open_end(t, path, READ_RIGHTS) {
+        Some(fd) => fd,
+        None => return,
+    };
+    let write_fd = match open_end(t, path, WRITE_RIGHTS) {
+        Some(fd) => fd,
+        None => {
+            t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+            return;
+        }
+    };
+
+    // empty FIFO isn't readable but can always be written to
+    t.output(
+        &call!("poll_oneoff", "fd_read", read_fd, "clock", POLL_TIMEOUT),
+        poll_userdata(&[
+            fd_subscription(1, wasi::EVENTTYPE_FD_READ, read_fd),
+            clock_subscription(2, POLL_TIMEOUT),
+        ]),
+        vec![2]);
+    t.output(
+        &call!("poll_oneoff", "fd_write", write_fd, "clock", POLL_TIMEOUT),
+        poll_userdata(&[
+            fd_subscription(1, wasi::EVENTTYPE_FD_WRITE, write_fd),
+            clock_subscription(2, POLL_TIMEOUT),
+        ]),
+        vec![1]);
+
+    let total: usize = CHUNKS.iter().sum();
+    let data = child::pattern(total);
+    let mut offset = 0;
+    for &len in CHUNKS {
+        t.output(&call!("fd_write", write_fd, len), write(write_fd, &data[offset..offset + len]), len);
+        offset += len;
+    }
+
+    // pending data makes the read end ready
+    let call = call!("poll_oneoff", "fd_read", read_fd);
+    if let Some(events) = t.success(&call, poll(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, read_fd)])) {
+        t.check(
+            &call,
+            events.len() == 1 && events[0].userdata == 1 && events[0].error == wasi::ERRNO_SUCCESS
+                && events[0].fd_readwrite.nbytes > 0,
+            &format!("read end with pending data is not ready (got {:?})", events));
+    }
+
+    t.check(
+        &call!("fd_read", read_fd, total), read_n(read_fd, total, READ_BUF_LEN) == Ok(data),
+        "data read from the FIFO differs from data written");
+
+    // closing the only writer signals EOF
+    t.success(&call!("fd_close", write_fd), wasi::fd_close(write_fd));
+    t.output(&call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN), Vec::new());
+    t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+}
+
+unsafe fn test_writers(t: &mut Checks, path: &str) {
+    let read_fd = match open_end(t, path, READ_RIGHTS) {
+        Some(fd) => fd,
+        None => return,
+    };
+    let writers = (open_end(t, path, WRITE_RIGHTS), open_end(t, path, WRITE_RIGHTS));
+    if let (Some(first), Some(second)) = writers {
+        // EOF is sent only after the last writer closes
+        t.output(&call!("fd_write", first, "a"), write(first, b"a"), 1);
+        t.success(&call!("fd_close", first), wasi::fd_close(first));
+        t.output(&call!("fd_read", read_fd, 1), read_n(read_fd, 1, READ_BUF_LEN), b"a".to_vec());
+        t.output(&call!("fd_write", second, "b"), write(second, b"b"), 1);
+        t.output(&call!("fd_read", read_fd, 1), read_n(read_fd, 1, READ_BUF_LEN), b"b".to_vec());
+        t.success(&call!("fd_close", second), wasi::fd_close(second));
+        t.output(&call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN), Vec::new());
+    } else {
+        for &fd in [writers.0, writers.1].iter().flatten() {
+            t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        }
+    }
+    t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+}
+
+unsafe fn test_seek(t: &mut Checks, path: &str) {
+    // both ends are not seekable
+    for &rights in [READ_RIGHTS, WRITE_RIGHTS].iter() {
+        let fd = match open_end(t, path, rights) {
+            Some(fd) => fd,
+            None => continue,
+        };
+        t.error(
+            &call!("fd_seek", fd, 0, wasi::WHENCE_SET.raw()), wasi::fd_seek(fd, 0, wasi::WHENCE_SET),
+            wasi::ERRNO_SPIPE, "fd_seek on a FIFO succeeded");
+        t.error(&call!("fd_tell", fd), wasi::fd_tell(fd), wasi::ERRNO_SPIPE, "fd_tell on a FIFO succeeded");
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    }
+}
+
+unsafe fn test_child_writer(t: &mut Checks, fixture: &Fixture, path: &str) {
+    let read_fd = match open_end(t, path, READ_RIGHTS) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // descriptors of the child are closed when it exits, which should send EOF
+    let redirects = [wasi_ext_lib::Redirect::Write((1, fixture.preopen_path(path)))];
+    let n = CHILD_BYTES.to_string();
+    let call = call!("spawn", "write-N-bytes", n);
+    let result = child::spawn("write-N-bytes", &[&n], &HashMap::new(), false, &redirects);
+    if t.equal(&call, "exit status", 0, child::exit_status(&result)) {
+        t.check(
+            &call!("fd_read", read_fd, CHILD_BYTES), read_n(read_fd, CHILD_BYTES, READ_BUF_LEN) == Ok(child::pattern(CHILD_BYTES)),
+            "data read from the FIFO differs from data written by the child");
+        t.output(&call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN), Vec::new());
+    }
+    t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+}
+
+pub fn test_fifo(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+
+    let path = match make_fifo(&mut t, fixture) {
+        Some(path) => path,
+        None => return t.finish(),
+    };
+    let abs_path = fixture.preopen_path(&path);
+    t.error(
+        &call!("mknod", abs_path, FIFO_DEV), wasi_ext_lib::mknod(&abs_path, FIFO_DEV),
+        wasi::ERRNO_EXIST.raw() as i32, "creating an existing FIFO succeeded");
+
+    unsafe {
+        test_stream(&mut t, &path);
+        test_writers(&mut t, &path);
+        test_seek(&mut t, &path);
+        test_child_writer(&mut t, fixture, &path);
+        t.success(
+            &call!("path_unlink_file", constants::PWD_DESC, path),
+            wasi::path_unlink_file(constants::PWD_DESC, &path));
+    }
+    t.finish()
+}
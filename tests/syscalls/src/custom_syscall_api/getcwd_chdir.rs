@@ -1,5 +1,7 @@
+use assertions::Checks;
 use constants;
 use std::fs;
+use fixture::Fixture;
 
 struct Test {
     base_cwd: String,
@@ -11,114 +13,78 @@ struct Test {
 
 impl Test {
     fn tear_down(&self) -> Result<(), String> {
+        // created files are removed together with the scratch directory
         if let Err(e) = wasi_ext_lib::chdir(&self.base_cwd) {
             return Err(format!("Couldn't tear down test environment: Couldn't change working directory (error code: {})", e));
         }
-        if let Err(e) = unsafe { wasi::path_remove_directory(constants::PWD_DESC, &self.long_dir) } {
-            return Err(format!("Couldn't tear down test environment: Couldn't remove {} (error: {:?})", &self.long_dir, e));
-        }
-        if let Err(e) = unsafe { wasi::path_unlink_file(constants::PWD_DESC, &self.long_file) } {
-            return Err(format!("Couldn't tear down test environment: Couldn't remove {} (error: {:?})", &self.long_file, e));
-        }
         Ok(())
     }
-    fn run_tests(&self) -> Result<(), String> {
-        chdir_success(&self.base_cwd)?;
-        getcwd_success(Some(&self.base_cwd))?;
+    fn run_tests(&self, t: &mut Checks) {
+        chdir_success(t, &self.base_cwd);
+        getcwd_success(t, &self.base_cwd);
 
         // change directory
         let tmp = fs::canonicalize(&self.dir_path).unwrap();
         let realpath = tmp.to_str().unwrap();
-        chdir_success(&self.dir_path)?;
-        getcwd_success(Some(&realpath))?;
-        chdir_success(&self.base_cwd)?;
+        chdir_success(t, &self.dir_path);
+        getcwd_success(t, &realpath);
+        chdir_success(t, &self.base_cwd);
 
         // attempt to chdir to a text file
-        chdir_error(&self.file_path, wasi::ERRNO_NOTDIR.raw().into(), "attempt to chdir to a text file succeeded")?;
-        getcwd_success(Some(&self.base_cwd))?;
+        chdir_error(t, &self.file_path, wasi::ERRNO_NOTDIR.raw().into(), "attempt to chdir to a text file succeeded");
+        getcwd_success(t, &self.base_cwd);
 
         // check against very long directory name
         let tmp = fs::canonicalize(&self.long_dir).unwrap();
         let realpath = tmp.to_str().unwrap();
-        chdir_success(&self.long_dir)?;
-        getcwd_success(Some(&realpath))?;
-        chdir_success(&self.base_cwd)?;
+        chdir_success(t, &self.long_dir);
+        getcwd_success(t, &realpath);
+        chdir_success(t, &self.base_cwd);
 
         // test against very long file name
-        chdir_error(&self.long_file, wasi::ERRNO_NOTDIR.raw().into(), "attempt to chdir to a text file succeeded")?;
-        getcwd_success(Some(&self.base_cwd))?;
-        chdir_success(&self.base_cwd)?;
-        Ok(())
+        chdir_error(t, &self.long_file, wasi::ERRNO_NOTDIR.raw().into(), "attempt to chdir to a text file succeeded");
+        getcwd_success(t, &self.base_cwd);
+        chdir_success(t, &self.base_cwd);
     }
-    fn try_setup() -> Result<Self, String> {
+    fn try_setup(fixture: &Fixture) -> Result<Self, String> {
         let long_dir = "a".repeat(1025);
-        if let Err(e) = unsafe { wasi::path_create_directory(constants::PWD_DESC, &long_dir) } {
+        if let Err(e) = unsafe { wasi::path_create_directory(fixture.fd, &long_dir) } {
             return Err(format!("Could not setup test environment: {:?}", e));
         }
         let long_file = "b".repeat(2049);
-        if let Err(e) = unsafe { wasi::path_open(
-            constants::PWD_DESC, 0, &long_file, wasi::OFLAGS_CREAT,
+        match unsafe { wasi::path_open(
+            fixture.fd, 0, &long_file, wasi::OFLAGS_CREAT,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) } {
-            return Err(format!("Could not setup test environment: {:?}", e));
+            Ok(fd) => { _ = unsafe { wasi::fd_close(fd) }; }
+            Err(e) => return Err(format!("Could not setup test environment: {:?}", e)),
         }
+        // the working directory is the preopened directory, paths are relative to it
         Ok(Test {
             base_cwd: String::from("/"),
-            dir_path: String::from(constants::SAMPLE_DIR_FILENAME),
-            file_path: String::from(constants::SAMPLE_TEXT_FILENAME),
-            long_dir,
-            long_file
+            dir_path: fixture.path(constants::SAMPLE_DIR_FILENAME),
+            file_path: fixture.path(constants::SAMPLE_TEXT_FILENAME),
+            long_dir: fixture.path(&long_dir),
+            long_file: fixture.path(&long_file)
         })
     }
 }
 
-fn chdir_success(path: &str) -> Result<(), String> {
-    match wasi_ext_lib::chdir(path) {
-        Ok(()) => Ok(()),
-        Err(e) => {
-            Err(format!(
-                "In chdir({}): syscall failed unexpectedly (error code: {})",
-                path, e))
-        }
-    }
+fn chdir_success(t: &mut Checks, path: &str) {
+    t.success(&call!("chdir", path), wasi_ext_lib::chdir(path));
 }
 
-fn getcwd_success(expected: Option<&str>) -> Result<(), String> {
-    match wasi_ext_lib::getcwd() {
-        Ok(path) => {
-            if expected.is_none() || &path == expected.unwrap() {
-                Ok(())
-            } else {
-                Err(format!(
-                    "In getcwd(): unexpected output (expected {}, got {})",
-                    expected.unwrap(), path))
-            }
-        }
-        Err(e) => {
-            Err(format!(
-                "In getcwd(): syscall failed unexpectedly (error code: {})",
-                e))
-        }
-    }
+fn getcwd_success(t: &mut Checks, expected: &str) {
+    t.output(&call!("getcwd"), wasi_ext_lib::getcwd(), String::from(expected));
 }
 
-fn chdir_error(path: &str, errno: i32, msg: &str) -> Result<(), String> {
-    match wasi_ext_lib::chdir(path) {
-        Ok(()) => Err(format!("In chdir(\"{}\"): {}", path, msg)),
-        Err(e) => {
-            if e == errno {
-                Ok(())
-            } else {
-                Err(format!(
-                    "In chdir({}): unexpected error code (expected {}, got {})",
-                    path, errno, e))
-            }
-        }
-    }
+fn chdir_error(t: &mut Checks, path: &str, errno: i32, msg: &str) {
+    t.error(&call!("chdir", path), wasi_ext_lib::chdir(path), errno, msg);
 }
 
-pub fn test_getcwd_chdir() -> Result<(), String> {
-    let test = Test::try_setup()?;
-    let result = test.run_tests();
+pub fn test_getcwd_chdir(fixture: &Fixture) -> Result<(), String> {
+    let test = Test::try_setup(fixture)?;
+    let mut t = Checks::new();
+    test.run_tests(&mut t);
     if let Err(e) = test.tear_down() { eprintln!("{}", e); }
-    result
+    t.finish()
 }
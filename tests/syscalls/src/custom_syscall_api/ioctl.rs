@@ -0,0 +1,164 @@
+use std::mem;
+
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+
+// Request numbers follow the layout decoded by the kernel (see src/utils.ts):
+// bits 30-31 direction, 16-29 argument size, 8-15 driver, 0-7 function.
+// The kernel passes only the function to the device, requests of DEVICE_DRIVER
+// are handled by the kernel itself for every device.
+const IOC_NONE: u64 = 0;
+const IOC_WRITE: u64 = 1;
+pub const IOC_READ: u64 = 2;
+
+const TERMINAL_DRIVER: u64 = 0x54;
+const DEVICE_DRIVER: u64 = 0x44;
+
+pub const fn request(rw: u64, size: usize, driver: u64, func: u64) -> u64 {
+    (rw << 30) | ((size as u64) << 16) | (driver << 8) | func
+}
+
+// the kernel exchanges only the flags, control characters are not supported
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Termios {
+    iflag: u32,
+    oflag: u32,
+    cflag: u32,
+    lflag: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Winsize {
+    rows: u16,
+    cols: u16,
+    xpixel: u16,
+    ypixel: u16,
+}
+
+const TCGETS: u64 = request(IOC_READ, mem::size_of::<Termios>(), TERMINAL_DRIVER, 0x01);
+const TCSETS: u64 = request(IOC_WRITE, mem::size_of::<Termios>(), TERMINAL_DRIVER, 0x02);
+const TCSETSW: u64 = request(IOC_WRITE, mem::size_of::<Termios>(), TERMINAL_DRIVER, 0x03);
+const TIOCGWINSZ: u64 = request(IOC_READ, mem::size_of::<Winsize>(), TERMINAL_DRIVER, 0x13);
+
+// device number of a character device, jswasi reports the containing filesystem in filestat.dev
+pub const DEVGETRDEV: u64 = request(IOC_READ, mem::size_of::<wasi::Device>(), DEVICE_DRIVER, 0x01);
+
+// terminal requests the kernel knows but doesn't implement
+const TCFLSH: u64 = request(IOC_NONE, 0, TERMINAL_DRIVER, 0x0b);
+const FIOQSIZE: u64 = request(IOC_NONE, 0, TERMINAL_DRIVER, 0x60);
+
+const ICANON: u32 = 0o0000002;
+const ECHO: u32 = 0o0000010;
+
+fn tcgets(fd: i32) -> Result<Termios, i32> {
+    let mut termios = Termios::default();
+    wasi_ext_lib::ioctl(fd, TCGETS, Some(&mut termios)).map(|_| termios)
+}
+
+fn tcsets(fd: i32, request: u64, mut termios: Termios) -> Result<(), i32> {
+    wasi_ext_lib::ioctl(fd, request, Some(&mut termios))
+}
+
+fn test_termios(t: &mut Checks) {
+    let original = match t.success(&call!("ioctl", 0, "TCGETS"), tcgets(0)) {
+        Some(termios) => termios,
+        None => return,
+    };
+
+    // setting unchanged attributes should round-trip
+    t.success(&call!("ioctl", 0, "TCSETS", original), tcsets(0, TCSETS, original));
+    t.output(&call!("ioctl", 0, "TCGETS"), tcgets(0), original);
+
+    // toggling ICANON and ECHO shouldn't affect other flags
+    let toggled = Termios { lflag: original.lflag ^ (ICANON | ECHO), ..original };
+    if t.success(&call!("ioctl", 0, "TCSETS", toggled), tcsets(0, TCSETS, toggled)).is_some() {
+        t.output(&call!("ioctl", 0, "TCGETS"), tcgets(0), toggled);
+    }
+    if t.success(&call!("ioctl", 0, "TCSETSW", original), tcsets(0, TCSETSW, original)).is_some() {
+        t.output(&call!("ioctl", 0, "TCGETS"), tcgets(0), original);
+    }
+
+    // kernel requires the whole struct to be passed
+    let mut short = [0u8; 8];
+    t.error(
+        &call!("ioctl", 0, "TCGETS", "8 byte buffer"),
+        wasi_ext_lib::ioctl(0, request(IOC_READ, short.len(), TERMINAL_DRIVER, 0x01), Some(&mut short)),
+        wasi::ERRNO_NOBUFS.raw() as i32, "TCGETS with too small buffer succeeded");
+
+    // leave the terminal as it was even if previous checks failed
+    if tcgets(0) != Ok(original) {
+        t.success(&call!("ioctl", 0, "TCSETS", original), tcsets(0, TCSETS, original));
+    }
+}
+
+fn test_winsize(t: &mut Checks) {
+    let mut winsize = Winsize::default();
+    let call = call!("ioctl", 0, "TIOCGWINSZ");
+    if t.success(&call, wasi_ext_lib::ioctl(0, TIOCGWINSZ, Some(&mut winsize))).is_some() {
+        t.check(&call, winsize.rows > 0 && winsize.cols > 0, &format!("invalid window size: {:?}", winsize));
+    }
+}
+
+fn test_unsupported(t: &mut Checks) {
+    // known terminal requests without implementation
+    for &(name, req) in [("TCFLSH", TCFLSH), ("FIOQSIZE", FIOQSIZE)].iter() {
+        t.error(
+            &call!("ioctl", 0, name), wasi_ext_lib::ioctl::<u8>(0, req, None),
+            wasi::ERRNO_NOTSUP.raw() as i32, "unsupported request succeeded");
+    }
+
+    // functions outside of the terminal ranges
+    for &func in [0x00, 0x61, 0xff].iter() {
+        let req = request(IOC_NONE, 0, TERMINAL_DRIVER, func);
+        t.error(
+            &call!("ioctl", 0, format!("{:#x}", req)), wasi_ext_lib::ioctl::<u8>(0, req, None),
+            wasi::ERRNO_INVAL.raw() as i32, "invalid request succeeded");
+    }
+}
+
+unsafe fn test_non_terminal(t: &mut Checks, fixture: &Fixture) {
+    // directory
+    t.error(
+        &call!("ioctl", fixture.fd, "TCGETS"), tcgets(fixture.fd as i32),
+        wasi::ERRNO_NOTTY.raw() as i32, "TCGETS on a directory succeeded");
+
+    // regular file
+    let call = call!(
+        "path_open", fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME,
+        0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0);
+    let text_fd = match t.success(&call, wasi::path_open(
+        fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME,
+        0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(fd) => fd,
+        None => return,
+    };
+    t.error(
+        &call!("ioctl", text_fd, "TCGETS"), tcgets(text_fd as i32),
+        wasi::ERRNO_NOTTY.raw() as i32, "TCGETS on a regular file succeeded");
+    t.error(
+        &call!("ioctl", text_fd, "TIOCGWINSZ"),
+        wasi_ext_lib::ioctl(text_fd as i32, TIOCGWINSZ, Some(&mut Winsize::default())),
+        wasi::ERRNO_NOTTY.raw() as i32, "TIOCGWINSZ on a regular file succeeded");
+    t.error(
+        &call!("ioctl", text_fd, "DEVGETRDEV"),
+        wasi_ext_lib::ioctl(text_fd as i32, DEVGETRDEV, Some(&mut (0 as wasi::Device))),
+        wasi::ERRNO_NOTTY.raw() as i32, "DEVGETRDEV on a regular file succeeded");
+    t.success(&call!("fd_close", text_fd), wasi::fd_close(text_fd));
+
+    // closed descriptor
+    t.error(
+        &call!("ioctl", text_fd, "TCGETS"), tcgets(text_fd as i32),
+        wasi::ERRNO_BADF.raw() as i32, "TCGETS on a closed descriptor succeeded");
+}
+
+pub fn test_ioctl(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    test_termios(&mut t);
+    test_winsize(&mut t);
+    test_unsupported(&mut t);
+    unsafe { test_non_terminal(&mut t, fixture) };
+    t.finish()
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use assertions::Checks;
+use child;
+use constants;
+use fixture::Fixture;
+
+// the sleeping child exits on its own if it isn't killed
+const CHILD_LIFETIME: &str = "30";
+const EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// a shell reports processes killed by signal N with status 128 + N
+const KILLED_STATUS: wasi_ext_lib::ExitCode = 128 + wasi::SIGNAL_KILL.raw() as i32;
+
+// pid that is never assigned during a test run
+const UNUSED_PID: wasi_ext_lib::Pid = i32::MAX;
+
+fn proc_exists(pid: wasi_ext_lib::Pid) -> bool {
+    unsafe { wasi::path_filestat_get(constants::PWD_DESC, 0, &format!("proc/{}", pid)) }.is_ok()
+}
+
+fn spawn_sleeper(t: &mut Checks) -> Option<wasi_ext_lib::Pid> {
+    let redirects = [wasi_ext_lib::Redirect::Write((1, String::from("/dev/null")))];
+    t.success(
+        &call!("spawn", "sleep-until-killed", CHILD_LIFETIME, "background"),
+        child::spawn("sleep-until-killed", &[CHILD_LIFETIME], &HashMap::new(), true, &redirects),
+    ).map(|(_, pid)| pid)
+}
+
+fn test_kill_background(t: &mut Checks) {
+    let pid = match spawn_sleeper(t) {
+        Some(pid) => pid,
+        None => return,
+    };
+    t.check(&call!("path_filestat_get", format!("proc/{}", pid)), proc_exists(pid), "child missing in /proc");
+
+    // only SIGKILL and SIGINT are supported, other signals don't affect the process
+    for &signal in [wasi::SIGNAL_NONE, wasi::SIGNAL_HUP, wasi::SIGNAL_TERM].iter() {
+        t.error(
+            &call!("kill", pid, signal.raw()), wasi_ext_lib::kill(pid, signal),
+            wasi::ERRNO_INVAL.raw() as i32, "sending unsupported signal succeeded");
+    }
+    t.check(
+        &call!("path_filestat_get", format!("proc/{}", pid)), proc_exists(pid),
+        "child exited after unsupported signal");
+
+    let call = call!("kill", pid, wasi::SIGNAL_KILL.raw());
+    if t.success(&call, wasi_ext_lib::kill(pid, wasi::SIGNAL_KILL)).is_none() {
+        return;
+    }
+    if !t.check(&call, child::wait_for_exit(pid, EXIT_TIMEOUT), &format!("/proc/{} still exists", pid)) {
+        return;
+    }
+
+    // killed process doesn't exist anymore
+    t.error(
+        &call, wasi_ext_lib::kill(pid, wasi::SIGNAL_KILL),
+        wasi::ERRNO_SRCH.raw() as i32, "killing exited process succeeded");
+}
+
+fn test_interrupt_background(t: &mut Checks) {
+    let pid = match spawn_sleeper(t) {
+        Some(pid) => pid,
+        None => return,
+    };
+
+    // a process without an attached event source is terminated by SIGINT
+    let call = call!("kill", pid, wasi::SIGNAL_INT.raw());
+    if t.success(&call, wasi_ext_lib::kill(pid, wasi::SIGNAL_INT)).is_none() {
+        _ = wasi_ext_lib::kill(pid, wasi::SIGNAL_KILL);
+        return;
+    }
+    t.check(&call, child::wait_for_exit(pid, EXIT_TIMEOUT), &format!("/proc/{} still exists", pid));
+}
+
+fn test_exit_status(t: &mut Checks) {
+    // a killed foreground child reports its status to the waiting parent
+    let result = child::spawn("kill-self", &[], &HashMap::new(), false, &[]);
+    t.equal(&call!("spawn", "kill-self"), "exit status", KILLED_STATUS, child::exit_status(&result));
+}
+
+fn test_errors(t: &mut Checks) {
+    t.error(
+        &call!("kill", UNUSED_PID, wasi::SIGNAL_KILL.raw()), wasi_ext_lib::kill(UNUSED_PID, wasi::SIGNAL_KILL),
+        wasi::ERRNO_SRCH.raw() as i32, "killing nonexistent process succeeded");
+
+    // signal is validated before the pid
+    t.error(
+        &call!("kill", UNUSED_PID, wasi::SIGNAL_TERM.raw()), wasi_ext_lib::kill(UNUSED_PID, wasi::SIGNAL_TERM),
+        wasi::ERRNO_INVAL.raw() as i32, "sending unsupported signal to nonexistent process succeeded");
+}
+
+pub fn test_kill(_: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    test_kill_background(&mut t);
+    test_interrupt_background(&mut t);
+    test_exit_status(&mut t);
+    test_errors(&mut t);
+    t.finish()
+}
@@ -0,0 +1,179 @@
+use assertions::Checks;
+use constants;
+use custom_syscall_api::ioctl::DEVGETRDEV;
+use fixture::Fixture;
+use utils::write;
+
+// minor numbers of memory devices, their major number MAJ_MEMORY is 0 so the device
+// number passed to mknod and reported by DEVGETRDEV equals the minor number
+const DEVICES: &[(&str, i32)] = &[("null", 0), ("zero", 1), ("urandom", 2)];
+
+const RIGHTS: wasi::Rights = wasi::RIGHTS_FD_READ | wasi::RIGHTS_FD_WRITE | wasi::RIGHTS_FD_SEEK
+    | wasi::RIGHTS_FD_TELL | wasi::RIGHTS_FD_FILESTAT_GET;
+
+// reads are scattered over buffers of these sizes, the largest ones exceed the 64KiB
+// limit of a single getRandomValues call
+const IOVEC_LENS: &[usize] = &[1, 4095, 4096, 65536, 100000];
+
+const WRITE_DATA: &[u8] = b"discarded";
+const SEEK_OFFSET: wasi::Filedelta = 100;
+
+// random bytes carry almost 8 bits of entropy each, shorter reads can't be judged
+const RANDOM_LEN: usize = 1 << 17;
+const MIN_ENTROPY: f64 = 7.9;
+
+unsafe fn open(t: &mut Checks, path: &str) -> Option<wasi::Fd> {
+    t.success(
+        &call!("path_open", constants::PWD_DESC, 0, path, 0, RIGHTS, 0, 0),
+        wasi::path_open(constants::PWD_DESC, 0, path, 0, RIGHTS, 0, 0))
+}
+
+unsafe fn readv(fd: wasi::Fd, lens: &[usize]) -> Result<Vec<u8>, wasi::Errno> {
+    let mut bufs: Vec<Vec<u8>> = lens.iter().map(|&len| vec![0xffu8; len]).collect();
+    let iovs: Vec<wasi::Iovec> = bufs.iter_mut().map(|b| wasi::Iovec { buf: b.as_mut_ptr(), buf_len: b.len() }).collect();
+    let n = wasi::fd_read(fd, &iovs)?;
+    let mut data: Vec<u8> = bufs.concat();
+    data.truncate(n);
+    Ok(data)
+}
+
+unsafe fn pread(fd: wasi::Fd, len: usize, offset: wasi::Filesize) -> Result<Vec<u8>, wasi::Errno> {
+    let mut buf = vec![0xffu8; len];
+    let n = wasi::fd_pread(fd, &[wasi::Iovec { buf: buf.as_mut_ptr(), buf_len: buf.len() }], offset)?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+fn zeroed(data: &[u8]) -> (usize, bool) {
+    (data.len(), data.iter().all(|&b| b == 0))
+}
+
+// Shannon entropy in bits per byte
+fn entropy(data: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in data {
+        counts[b as usize] += 1;
+    }
+    counts.iter().filter(|&&c| c > 0).map(|&c| {
+        let p = c as f64 / data.len() as f64;
+        -p * p.log2()
+    }).sum()
+}
+
+unsafe fn rdev(fd: wasi::Fd) -> Result<wasi::Device, i32> {
+    let mut rdev: wasi::Device = 0;
+    wasi_ext_lib::ioctl(fd as i32, DEVGETRDEV, Some(&mut rdev)).map(|_| rdev)
+}
+
+// dev is the device number of the filesystem containing the node, like for any other file
+unsafe fn expect_stat(t: &mut Checks, path: &str, fd: wasi::Fd, fs_dev: Option<wasi::Device>) {
+    if let Some(stat) = t.success(&call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd)) {
+        t.equal(&call!("fd_fdstat_get", fd), "filetype", wasi::FILETYPE_CHARACTER_DEVICE, stat.fs_filetype);
+    }
+    let stats = [
+        (call!("fd_filestat_get", fd), wasi::fd_filestat_get(fd)),
+        (call!("path_filestat_get", constants::PWD_DESC, 0, path), wasi::path_filestat_get(constants::PWD_DESC, 0, path)),
+    ];
+    for (call, result) in stats.iter() {
+        if let Some(stat) = t.success(call, *result) {
+            t.equal(call, "filetype", wasi::FILETYPE_CHARACTER_DEVICE, stat.filetype);
+            if let Some(fs_dev) = fs_dev {
+                t.equal(call, "dev", fs_dev, stat.dev);
+            }
+            t.equal(call, "size", 0, stat.size);
+        }
+    }
+}
+
+// device files have no position, every seek ends at offset 0
+unsafe fn expect_no_position(t: &mut Checks, fd: wasi::Fd) {
+    for &whence in [wasi::WHENCE_SET, wasi::WHENCE_CUR, wasi::WHENCE_END].iter() {
+        t.output(
+            &call!("fd_seek", fd, SEEK_OFFSET, whence.raw()), wasi::fd_seek(fd, SEEK_OFFSET, whence), 0);
+    }
+    t.output(&call!("fd_tell", fd), wasi::fd_tell(fd), 0);
+}
+
+unsafe fn expect_reads(t: &mut Checks, fd: wasi::Fd, name: &str) {
+    let total: usize = IOVEC_LENS.iter().sum();
+    match name {
+        // EOF is returned no matter what was written
+        "null" => {
+            t.output(&call!("fd_read", fd, IOVEC_LENS), readv(fd, IOVEC_LENS), Vec::new());
+            t.output(&call!("fd_pread", fd, total, 0), pread(fd, total, 0), Vec::new());
+        }
+        // (length, whether all bytes are zero) is compared, the data itself is too long to record
+        "zero" => {
+            t.output(
+                &call!("fd_read", fd, IOVEC_LENS), readv(fd, IOVEC_LENS).map(|d| zeroed(&d)), (total, true));
+            t.output(&call!("fd_pread", fd, total, 0), pread(fd, total, 0).map(|d| zeroed(&d)), (total, true));
+        }
+        // random data differs between runs, only properties of it are recorded
+        "urandom" => {
+            let call = call!("fd_read", fd, IOVEC_LENS);
+            let result = readv(fd, IOVEC_LENS);
+            t.success(&call, result.as_ref().map(|d| d.len()).map_err(|&e| e));
+            if let Ok(data) = result {
+                t.equal(&call, "length", total, data.len());
+                let bits = entropy(&data);
+                t.check(&call, bits >= MIN_ENTROPY, &format!("low entropy of random data ({:.3} bits per byte)", bits));
+            }
+
+            let call = call!("fd_pread", fd, RANDOM_LEN, 0);
+            let first = pread(fd, RANDOM_LEN, 0);
+            let second = pread(fd, RANDOM_LEN, 0);
+            if let (Ok(first), Ok(second)) = (&first, &second) {
+                t.equal(&call, "length", RANDOM_LEN, first.len());
+                t.check(&call, first != second, "reading the same offset twice returned the same data");
+            } else {
+                t.success(&call, first.and(second).map(|_| ()));
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+unsafe fn test_device(t: &mut Checks, path: &str, name: &str, dev: i32, fs_dev: Option<wasi::Device>) {
+    let fd = match open(t, path) {
+        Some(fd) => fd,
+        None => return,
+    };
+    expect_stat(t, path, fd, fs_dev);
+    t.output(&call!("ioctl", fd, "DEVGETRDEV"), rdev(fd), dev as wasi::Device);
+    expect_no_position(t, fd);
+
+    // all memory devices discard written data
+    t.output(&call!("fd_write", fd, WRITE_DATA), write(fd, WRITE_DATA), WRITE_DATA.len());
+    t.output(
+        &call!("fd_pwrite", fd, WRITE_DATA, SEEK_OFFSET),
+        wasi::fd_pwrite(fd, &[wasi::Ciovec { buf: WRITE_DATA.as_ptr(), buf_len: WRITE_DATA.len() }], SEEK_OFFSET as u64),
+        WRITE_DATA.len());
+
+    expect_reads(t, fd, name);
+    expect_no_position(t, fd);
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+pub fn test_mem_devices(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        let fs_dev = t.success(
+            &call!("path_filestat_get", constants::PWD_DESC, 0, "dev"),
+            wasi::path_filestat_get(constants::PWD_DESC, 0, "dev")).map(|stat| stat.dev);
+        for &(name, dev) in DEVICES {
+            test_device(&mut t, &format!("dev/{}", name), name, dev, fs_dev);
+
+            // nodes created at other paths are served by the same driver
+            let path = fixture.dev_path(name);
+            let abs_path = fixture.preopen_path(&path);
+            if t.success(&call!("mknod", abs_path, dev), wasi_ext_lib::mknod(&abs_path, dev)).is_none() {
+                continue;
+            }
+            test_device(&mut t, &path, name, dev, fs_dev);
+            t.success(
+                &call!("path_unlink_file", constants::PWD_DESC, path),
+                wasi::path_unlink_file(constants::PWD_DESC, &path));
+        }
+    }
+    t.finish()
+}
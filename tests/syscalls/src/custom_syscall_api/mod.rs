@@ -1,3 +1,12 @@
 pub mod isatty;
 pub mod getcwd_chdir;
 pub mod set_env;
+pub mod spawn;
+pub mod ioctl;
+pub mod mount;
+pub mod fifo;
+pub mod mem_devices;
+pub mod proc;
+pub mod kill;
+pub mod event_source;
+pub mod uname;
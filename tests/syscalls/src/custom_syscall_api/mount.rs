@@ -0,0 +1,194 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use utils::{ext_syscall, read_file, write_file, MOUNT_POINT};
+
+const FILENAME: &str = "file";
+const CONTENTS: &[u8] = b"written through the mounted filesystem\n";
+
+// the kernel has no /proc/mounts, mount points are listed in mountinfo of every process
+const MOUNTINFO: &str = "proc/self/mountinfo";
+const VFS_NAME: &str = "VirtualFilesystem";
+
+// filesystems mounted by the kernel at boot, unmounting them would break the rest of the run
+const SYSTEM_MOUNTS: &[(&str, &str)] = &[("/dev", "DeviceFilesystem"), ("/proc", "ProcFilesystem")];
+
+// mount with explicit descriptors, wasi_ext_lib::mount always passes absolute paths
+unsafe fn mount_at(
+    source_fd: i32,
+    source: &str,
+    target_fd: i32,
+    target: &str,
+    fs_type: &str,
+    data: &str
+) -> Result<(), wasi::Errno> {
+    let args = format!(
+        "{{\"source_fd\":{},\"source\":{},\"source_len\":{},\"target_fd\":{},\"target\":{},\"target_len\":{},\
+         \"filesystemtype\":{},\"filesystemtype_len\":{},\"mountflags\":0,\"data\":{},\"data_len\":{}}}",
+        source_fd, source.as_ptr() as usize, source.len(), target_fd, target.as_ptr() as usize, target.len(),
+        fs_type.as_ptr() as usize, fs_type.len(), data.as_ptr() as usize, data.len());
+    ext_syscall("mount", &args, &mut []).map(|_| ())
+}
+
+// returns true if mountinfo lists a filesystem of type fs_name at path
+fn is_mounted_fs(t: &mut Checks, path: &str, fs_name: &str) -> Option<bool> {
+    let mountinfo = t.success(&call!("read_file", MOUNTINFO), unsafe { read_file(constants::PWD_DESC, MOUNTINFO) })?;
+    Some(String::from_utf8_lossy(&mountinfo).lines().any(|l| l == format!("{} {}", path, fs_name)))
+}
+
+fn is_mounted(t: &mut Checks, path: &str) -> Option<bool> {
+    is_mounted_fs(t, path, VFS_NAME)
+}
+
+unsafe fn test_mount_umount(t: &mut Checks, fixture: &Fixture) {
+    let target = fixture.abs_path(MOUNT_POINT);
+    let file = format!("{}/{}", MOUNT_POINT, FILENAME);
+    let call = call!("mount", "", target, "vfs", 0, "");
+    if t.success(&call, wasi_ext_lib::mount("", &target, "vfs", 0, "")).is_none() {
+        return;
+    }
+    if let Some(mounted) = is_mounted(t, &target) {
+        t.check(&call, mounted, &format!("{} doesn't list the mount point", MOUNTINFO));
+    }
+
+    // the root of the fresh filesystem is empty, mounting over it should still fail
+    t.error(
+        &call, wasi_ext_lib::mount("", &target, "vfs", 0, ""),
+        wasi::ERRNO_BUSY.raw() as i32, "mounting over a mount point succeeded");
+
+    // files should be accessible through the scratch directory
+    if t.success(&call!("write_file", file), write_file(fixture.fd, &file, CONTENTS)).is_some() {
+        t.output(&call!("read_file", file), read_file(fixture.fd, &file), CONTENTS.to_vec());
+    }
+
+    // parent of a mount point can't be unmounted
+    let parent = fixture.preopen_path(&fixture.name);
+    t.error(
+        &call!("umount", parent), wasi_ext_lib::umount(&parent),
+        wasi::ERRNO_BUSY.raw() as i32, "unmounting a directory containing a mount point succeeded");
+
+    // after umount the empty directory of the underlying filesystem should be visible again
+    if t.success(&call!("umount", target), wasi_ext_lib::umount(&target)).is_none() {
+        return;
+    }
+    if let Some(mounted) = is_mounted(t, &target) {
+        t.check(&call!("umount", target), !mounted, &format!("{} still lists the mount point", MOUNTINFO));
+    }
+    t.error(
+        &call!("path_filestat_get", fixture.fd, 0, file), wasi::path_filestat_get(fixture.fd, 0, &file),
+        wasi::ERRNO_NOENT, "file of the unmounted filesystem is still visible");
+    if let Some(stat) = t.success(
+        &call!("path_filestat_get", fixture.fd, 0, MOUNT_POINT),
+        wasi::path_filestat_get(fixture.fd, 0, MOUNT_POINT)) {
+        t.equal(&call!("path_filestat_get", fixture.fd, 0, MOUNT_POINT), "filetype", wasi::FILETYPE_DIRECTORY, stat.filetype);
+    }
+
+    // nothing is mounted anymore
+    t.error(
+        &call!("umount", target), wasi_ext_lib::umount(&target),
+        wasi::ERRNO_NOENT.raw() as i32, "unmounting twice succeeded");
+}
+
+unsafe fn test_relative_target(t: &mut Checks, fixture: &Fixture) {
+    // target path is resolved relative to target_fd
+    let call = call!("mount", -1, "", fixture.fd, MOUNT_POINT, "vfs", "");
+    if t.success(&call, mount_at(-1, "", fixture.fd as i32, MOUNT_POINT, "vfs", "")).is_none() {
+        return;
+    }
+    let target = fixture.abs_path(MOUNT_POINT);
+    if let Some(mounted) = is_mounted(t, &target) {
+        t.check(&call, mounted, &format!("{} doesn't list the mount point", MOUNTINFO));
+    }
+    t.success(&call!("umount", target), wasi_ext_lib::umount(&target));
+}
+
+// Only mounting over the proc and device filesystems is checked. Neither can be mounted
+// again: proc is not a known filesystem type and a device filesystem mounted by the syscall
+// has no driver manager to open devices with.
+unsafe fn test_system_mounts(t: &mut Checks) {
+    for &(path, fs_name) in SYSTEM_MOUNTS {
+        if let Some(mounted) = is_mounted_fs(t, path, fs_name) {
+            t.check(&call!("read_file", MOUNTINFO), mounted, &format!("{} doesn't list {}", MOUNTINFO, path));
+        }
+        t.error(
+            &call!("mount", "", path, "vfs", 0, ""), wasi_ext_lib::mount("", path, "vfs", 0, ""),
+            wasi::ERRNO_BUSY.raw() as i32, "mounting over a system filesystem succeeded");
+        if let Some(mounted) = is_mounted_fs(t, path, fs_name) {
+            t.check(&call!("mount", "", path), mounted, &format!("{} was replaced by a failed mount", path));
+        }
+    }
+}
+
+unsafe fn test_errors(t: &mut Checks, fixture: &Fixture) {
+    let target = fixture.abs_path(MOUNT_POINT);
+
+    // options are comma separated key=value pairs
+    for &data in ["novalue", "key=value=value", "key=value,novalue"].iter() {
+        t.error(
+            &call!("mount", "", target, "vfs", 0, data), wasi_ext_lib::mount("", &target, "vfs", 0, data),
+            wasi::ERRNO_INVAL.raw() as i32, "mount with malformed options succeeded");
+    }
+
+    for &fs_type in ["nosuchfs", "proc"].iter() {
+        t.error(
+            &call!("mount", "", target, fs_type, 0, ""), wasi_ext_lib::mount("", &target, fs_type, 0, ""),
+            wasi::ERRNO_INVAL.raw() as i32, "mount of unknown filesystem type succeeded");
+    }
+
+    // closed descriptors
+    let closed_fd = match t.success(
+        &call!("path_open", fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME),
+        wasi::path_open(
+            fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME, 0,
+            constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(fd) => fd,
+        None => return,
+    };
+    t.success(&call!("fd_close", closed_fd), wasi::fd_close(closed_fd));
+    t.error(
+        &call!("mount", -1, "", closed_fd, MOUNT_POINT, "vfs", ""),
+        mount_at(-1, "", closed_fd as i32, MOUNT_POINT, "vfs", ""),
+        wasi::ERRNO_BADF, "mount with closed target descriptor succeeded");
+    t.error(
+        &call!("mount", closed_fd, constants::SAMPLE_DIR_FILENAME, fixture.fd, MOUNT_POINT, "vfs", ""),
+        mount_at(closed_fd as i32, constants::SAMPLE_DIR_FILENAME, fixture.fd as i32, MOUNT_POINT, "vfs", ""),
+        wasi::ERRNO_BADF, "mount with closed source descriptor succeeded");
+
+    // invalid targets
+    let nonexistent = fixture.abs_path("nonexistent");
+    t.error(
+        &call!("mount", "", nonexistent, "vfs", 0, ""), wasi_ext_lib::mount("", &nonexistent, "vfs", 0, ""),
+        wasi::ERRNO_NOENT.raw() as i32, "mount on nonexistent directory succeeded");
+    let dir = fixture.abs_path(constants::SAMPLE_DIR_FILENAME);
+    t.error(
+        &call!("mount", "", dir, "vfs", 0, ""), wasi_ext_lib::mount("", &dir, "vfs", 0, ""),
+        wasi::ERRNO_NOTEMPTY.raw() as i32, "mount on non-empty directory succeeded");
+    let text = fixture.abs_path(constants::SAMPLE_TEXT_FILENAME);
+    t.error(
+        &call!("mount", "", text, "vfs", 0, ""), wasi_ext_lib::mount("", &text, "vfs", 0, ""),
+        wasi::ERRNO_NOTDIR.raw() as i32, "mount on regular file succeeded");
+
+    // failed mounts shouldn't be registered
+    if let Some(mounted) = is_mounted(t, &target) {
+        t.check(&call!("mount", "", target), !mounted, "failed mount is listed in mountinfo");
+        if mounted {
+            _ = wasi_ext_lib::umount(&target);
+        }
+    }
+}
+
+pub fn test_mount(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    if t.success(
+        &call!("path_create_directory", fixture.fd, MOUNT_POINT),
+        unsafe { wasi::path_create_directory(fixture.fd, MOUNT_POINT) }).is_none() {
+        return t.finish();
+    }
+    unsafe {
+        test_mount_umount(&mut t, fixture);
+        test_relative_target(&mut t, fixture);
+        test_system_mounts(&mut t);
+        test_errors(&mut t, fixture);
+    }
+    t.finish()
+}
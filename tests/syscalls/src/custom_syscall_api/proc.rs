@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use assertions::Checks;
+use child;
+use constants;
+use fixture::Fixture;
+use syscalls::fd_readdir::wasi_ls;
+use utils::{read_file, readlink};
+
+const ENV_KEY: &str = "PROC_TEST_VAR";
+
+// the background child exits on its own after this many seconds
+const CHILD_LIFETIME: &str = "1";
+const EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const READDIR_BUF_LEN: usize = 4096;
+
+// paths are relative to the preopened root directory
+fn read_proc(t: &mut Checks, path: &str) -> Option<String> {
+    t.success(
+        &call!("read_file", path),
+        unsafe { read_file(constants::PWD_DESC, path) }.map(|c| String::from_utf8_lossy(&c).into_owned()))
+}
+
+fn test_self(t: &mut Checks) -> Option<wasi_ext_lib::Pid> {
+    let pid = t.success(&call!("getpid"), wasi_ext_lib::getpid())?;
+    t.output(
+        &call!("path_readlink", constants::PWD_DESC, "proc/self"),
+        unsafe { readlink(constants::PWD_DESC, "proc/self") }, pid.to_string());
+    Some(pid)
+}
+
+fn test_status(t: &mut Checks, pid: wasi_ext_lib::Pid) {
+    let call = call!("read_file", "proc/self/status");
+    let status = match read_proc(t, "proc/self/status") {
+        Some(s) => s,
+        None => return,
+    };
+
+    // status consists of "Key:\tvalue" lines, name is the file name of the binary
+    let fields: HashMap<&str, &str> = status.lines().filter_map(|l| l.split_once(":\t")).collect();
+    let argv0 = env::args().next().unwrap_or_default();
+    let name = argv0.rsplit('/').next().unwrap();
+    t.equal(&call, "Name", Some(name), fields.get("Name").cloned());
+    t.equal(&call, "Pid", Some(pid.to_string()), fields.get("Pid").map(|p| p.to_string()));
+    t.equal(&call, "Tgid", Some(pid.to_string()), fields.get("Tgid").map(|p| p.to_string()));
+
+    // /proc/self is the same directory as /proc/<pid>
+    let path = format!("proc/{}/status", pid);
+    if let Some(by_pid) = read_proc(t, &path) {
+        t.equal(&call!("read_file", path), "contents", &status, &by_pid);
+    }
+}
+
+// NUL separated KEY=VALUE entries
+fn environ_value(t: &mut Checks) -> Option<Option<String>> {
+    let environ = read_proc(t, "proc/self/environ")?;
+    let prefix = format!("{}=", ENV_KEY);
+    Some(environ.split('\0').find(|e| e.starts_with(&prefix)).map(|e| String::from(&e[prefix.len()..])))
+}
+
+fn test_environ(t: &mut Checks) {
+    for &value in [Some("value"), Some("changed"), Some(""), None].iter() {
+        if t.success(&call!("set_env", ENV_KEY, value), wasi_ext_lib::set_env(ENV_KEY, value)).is_some() {
+            if let Some(got) = environ_value(t) {
+                t.equal(&call!("read_file", "proc/self/environ"), ENV_KEY, value.map(String::from), got);
+            }
+        }
+    }
+}
+
+fn test_cwd(t: &mut Checks, fixture: &Fixture) {
+    let original = match t.success(&call!("getcwd"), wasi_ext_lib::getcwd()) {
+        Some(cwd) => cwd,
+        None => return,
+    };
+    let dirs = [fixture.preopen_path(&fixture.name), fixture.abs_path(constants::SAMPLE_DIR_FILENAME)];
+    for dir in dirs.iter() {
+        if t.success(&call!("chdir", dir), wasi_ext_lib::chdir(dir)).is_some() {
+            t.output(
+                &call!("path_readlink", constants::PWD_DESC, "proc/self/cwd"),
+                unsafe { readlink(constants::PWD_DESC, "proc/self/cwd") }, dir.clone());
+        }
+    }
+    t.success(&call!("chdir", original), wasi_ext_lib::chdir(&original));
+}
+
+// pids listed in /proc, other entries are skipped
+fn list_pids(t: &mut Checks) -> Option<Vec<wasi_ext_lib::Pid>> {
+    let call = call!("path_open", constants::PWD_DESC, 0, "proc", wasi::OFLAGS_DIRECTORY);
+    let fd = t.success(&call, unsafe { wasi::path_open(
+        constants::PWD_DESC, 0, "proc", wasi::OFLAGS_DIRECTORY,
+        constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) })?;
+    let result = unsafe { wasi_ls(fd, READDIR_BUF_LEN, 0, true) };
+    t.success(&call!("fd_close", fd), unsafe { wasi::fd_close(fd) });
+    match result {
+        Ok((dirents, _)) => {
+            let mut pids: Vec<wasi_ext_lib::Pid> = dirents.keys().filter_map(|n| n.parse().ok()).collect();
+            pids.sort();
+            Some(pids)
+        }
+        Err(e) => {
+            t.check(&call!("fd_readdir", fd), false, &e);
+            None
+        }
+    }
+}
+
+fn test_readdir(t: &mut Checks, pid: wasi_ext_lib::Pid) {
+    let redirects = [wasi_ext_lib::Redirect::Write((1, String::from("/dev/null")))];
+    let call = call!("spawn", "sleep-until-killed", CHILD_LIFETIME, "background");
+    let child_pid = match t.success(
+        &call, child::spawn("sleep-until-killed", &[CHILD_LIFETIME], &HashMap::new(), true, &redirects)) {
+        Some((_, child_pid)) => child_pid,
+        None => return,
+    };
+
+    // both processes are alive, every listed process should have its directory
+    if let Some(pids) = list_pids(t) {
+        t.check(&call!("fd_readdir", "proc"), pids.contains(&pid), &format!("pid {} not listed in {:?}", pid, pids));
+        t.check(
+            &call!("fd_readdir", "proc"), pids.contains(&child_pid),
+            &format!("child pid {} not listed in {:?}", child_pid, pids));
+        for p in pids.iter() {
+            let path = format!("proc/{}/status", p);
+            t.success(&call!("read_file", path), unsafe { read_file(constants::PWD_DESC, &path) });
+        }
+    }
+
+    // exited child should disappear from the listing
+    if t.check(
+        &call!("wait_for_exit", child_pid), child::wait_for_exit(child_pid, EXIT_TIMEOUT),
+        &format!("child {} still running after {:?}", child_pid, EXIT_TIMEOUT)) {
+        if let Some(pids) = list_pids(t) {
+            t.check(
+                &call!("fd_readdir", "proc"), !pids.contains(&child_pid),
+                &format!("exited child {} still listed in {:?}", child_pid, pids));
+        }
+    }
+}
+
+pub fn test_proc(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    if let Some(pid) = test_self(&mut t) {
+        test_status(&mut t, pid);
+        test_readdir(&mut t, pid);
+    }
+    test_environ(&mut t);
+    test_cwd(&mut t, fixture);
+    t.finish()
+}
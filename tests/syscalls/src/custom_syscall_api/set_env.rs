@@ -1,45 +1,21 @@
 use std::env;
 
-fn expect_success(key: &str, val: Option<&str>) -> Result<(), String> {
-    if let Err(e) = wasi_ext_lib::set_env(key, val) {
-        Err(format!(
-            "In set_env({}, {:?}): syscall failed unexpectedly ({:?})",
-            key, val, e))
-    } else { Ok(()) }
-}
+use assertions::Checks;
+use fixture::Fixture;
 
-fn check_env(key: &str, val: Option<&str>) -> Result<(), String> {
-    let actual = env::var(key);
-    match &actual {
-        Ok(var) => {
-            if val.is_some() && val.unwrap() == var {
-                Ok(())
-            } else {
-                Err(format!(
-                    "Unexpected variable value (expected {:?}, got {:?})",
-                    val, actual))
-            }
-        }
-        Err(env::VarError::NotPresent) => {
-            if let None = val { Ok(()) }
-            else { Err(format!(
-                "Unexpected variable value (expected {:?}, got {:?})",
-                val, None::<&str>))
-            }
-        }
+fn get_env(key: &str) -> Result<Option<String>, String> {
+    match env::var(key) {
+        Ok(val) => Ok(Some(val)),
+        Err(env::VarError::NotPresent) => Ok(None),
         Err(e) => Err(format!("Unexpected error ({:?})", e))
     }
 }
 
-pub fn test_set_env() -> Result<(), String> {
-    expect_success("KEY", Some("VAL"))?;
-    check_env("KEY", Some("VAL"))?;
-
-    expect_success("KEY", None)?;
-    check_env("KEY", None)?;
-
-    expect_success("KEY", Some(""))?;
-    check_env("KEY", Some(""))?;
-
-    Ok(())
+pub fn test_set_env(_: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    for &val in [Some("VAL"), None, Some("")].iter() {
+        t.success(&call!("set_env", "KEY", val), wasi_ext_lib::set_env("KEY", val));
+        t.equal(&call!("env::var", "KEY"), "variable value", Ok(val.map(String::from)), get_env("KEY"));
+    }
+    t.finish()
 }
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::env;
+
+use assertions::Checks;
+use child;
+use constants;
+use fixture::Fixture;
+use utils::{read_file, write_file};
+
+// status reported by the kernel when redirects can't be applied
+const EXIT_FAILURE: wasi_ext_lib::ExitCode = 1;
+
+const INPUT: &str = "spawn input\n";
+const INPUT_FILENAME: &str = "input";
+const OUTPUT_FILENAME: &str = "output";
+
+fn read_output(fixture: &Fixture, name: &str) -> Result<String, wasi::Errno> {
+    unsafe { read_file(fixture.fd, name) }.map(|o| String::from_utf8_lossy(&o).into_owned())
+}
+
+// spawns a foreground child, returns true if it exited with the expected status
+fn expect_status(
+    t: &mut Checks,
+    role: &str,
+    args: &[&str],
+    env: &HashMap<String, String>,
+    redirects: &[wasi_ext_lib::Redirect],
+    expected: wasi_ext_lib::ExitCode
+) -> bool {
+    let result = child::spawn(role, args, env, false, redirects);
+    t.equal(&call!("spawn", role, args), "exit status", expected, child::exit_status(&result))
+}
+
+fn test_exit_codes(t: &mut Checks) {
+    for &code in [0, 1, 42, 255].iter() {
+        expect_status(t, "exit-with-code", &[&code.to_string()], &HashMap::new(), &[], code);
+    }
+}
+
+fn test_argv(t: &mut Checks, fixture: &Fixture) {
+    // the kernel replaces argv[0] with the file name of the binary
+    let bin = child::binary_path();
+    let name = bin.rsplit('/').next().unwrap();
+    let args = ["a", "b c", ""];
+    let redirects = [wasi_ext_lib::Redirect::Write((1, fixture.abs_path(OUTPUT_FILENAME)))];
+    if expect_status(t, "dump-args", &args, &HashMap::new(), &redirects, 0) {
+        let expected = format!("{}\n{}\ndump-args\na\nb c\n\n", name, child::CHILD_ARG);
+        t.output(&call!("read_file", OUTPUT_FILENAME), read_output(fixture, OUTPUT_FILENAME), expected);
+    }
+}
+
+fn test_env(t: &mut Checks, fixture: &Fixture) {
+    // variables passed to spawn extend the environment of the parent
+    t.success(&call!("set_env", "SPAWN_PARENT", "parent"), wasi_ext_lib::set_env("SPAWN_PARENT", Some("parent")));
+    t.success(&call!("set_env", "SPAWN_OVERRIDE", "parent"), wasi_ext_lib::set_env("SPAWN_OVERRIDE", Some("parent")));
+    let mut env = HashMap::new();
+    env.insert(String::from("SPAWN_CHILD"), String::from("child"));
+    env.insert(String::from("SPAWN_OVERRIDE"), String::from("child"));
+    let redirects = [wasi_ext_lib::Redirect::Write((1, fixture.abs_path(OUTPUT_FILENAME)))];
+    if expect_status(t, "dump-env", &["SPAWN_PARENT", "SPAWN_CHILD", "SPAWN_OVERRIDE"], &env, &redirects, 0) {
+        t.output(
+            &call!("read_file", OUTPUT_FILENAME), read_output(fixture, OUTPUT_FILENAME),
+            String::from("SPAWN_PARENT=parent\nSPAWN_CHILD=child\nSPAWN_OVERRIDE=child\n"));
+    }
+
+    // environment of the parent should not change
+    t.equal(&call!("env::var", "SPAWN_OVERRIDE"), "variable value", Ok(String::from("parent")), env::var("SPAWN_OVERRIDE"));
+    t.equal(&call!("env::var", "SPAWN_CHILD"), "variable value", false, env::var("SPAWN_CHILD").is_ok());
+    t.success(&call!("set_env", "SPAWN_PARENT", None::<&str>), wasi_ext_lib::set_env("SPAWN_PARENT", None));
+    t.success(&call!("set_env", "SPAWN_OVERRIDE", None::<&str>), wasi_ext_lib::set_env("SPAWN_OVERRIDE", None));
+}
+
+// runs echo-stdin with given redirects and checks what ends up in the output file
+fn check_echo(t: &mut Checks, fixture: &Fixture, redirects: &[wasi_ext_lib::Redirect], expected: &str) {
+    if expect_status(t, "echo-stdin", &[], &HashMap::new(), redirects, 0) {
+        t.output(&call!("read_file", OUTPUT_FILENAME), read_output(fixture, OUTPUT_FILENAME), String::from(expected));
+    }
+}
+
+unsafe fn test_file_redirects(t: &mut Checks, fixture: &Fixture) {
+    let input = fixture.abs_path(INPUT_FILENAME);
+    let output = fixture.abs_path(OUTPUT_FILENAME);
+    if t.success(&call!("write_file", INPUT_FILENAME), write_file(fixture.fd, INPUT_FILENAME, INPUT.as_bytes())).is_none() {
+        return;
+    }
+
+    // reading stdin from a file and writing stdout to a new file should work
+    check_echo(t, fixture, &[
+        wasi_ext_lib::Redirect::Read((0, input.clone())),
+        wasi_ext_lib::Redirect::Write((1, output.clone())),
+    ], INPUT);
+
+    // write redirect should truncate existing file
+    t.success(&call!("write_file", OUTPUT_FILENAME), write_file(fixture.fd, OUTPUT_FILENAME, b"previous longer contents\n"));
+    check_echo(t, fixture, &[
+        wasi_ext_lib::Redirect::Read((0, input.clone())),
+        wasi_ext_lib::Redirect::Write((1, output.clone())),
+    ], INPUT);
+
+    // append redirect should keep existing contents
+    t.success(&call!("write_file", OUTPUT_FILENAME), write_file(fixture.fd, OUTPUT_FILENAME, b"first\n"));
+    check_echo(t, fixture, &[
+        wasi_ext_lib::Redirect::Read((0, input.clone())),
+        wasi_ext_lib::Redirect::Append((1, output.clone())),
+    ], &format!("first\n{}", INPUT));
+
+    // file opened for reading and writing should be readable
+    check_echo(t, fixture, &[
+        wasi_ext_lib::Redirect::ReadWrite((0, input.clone())),
+        wasi_ext_lib::Redirect::Write((1, output.clone())),
+    ], INPUT);
+
+    // large outputs should be written completely
+    let redirects = [wasi_ext_lib::Redirect::Write((1, output.clone()))];
+    if expect_status(t, "write-N-bytes", &["100000"], &HashMap::new(), &redirects, 0) {
+        let call = call!("read_file", OUTPUT_FILENAME);
+        t.output(&call, read_file(fixture.fd, OUTPUT_FILENAME).map(|o| o.len()), 100000);
+        t.check(&call, read_file(fixture.fd, OUTPUT_FILENAME) == Ok(child::pattern(100000)), "output differs from the written pattern");
+    }
+
+    // duplicated descriptor should point to the same file
+    check_echo(t, fixture, &[
+        wasi_ext_lib::Redirect::Read((0, input.clone())),
+        wasi_ext_lib::Redirect::Write((5, output.clone())),
+        wasi_ext_lib::Redirect::Duplicate { fd_src: 5, fd_dst: 1 },
+    ], INPUT);
+}
+
+unsafe fn test_pipe_redirects(t: &mut Checks, fixture: &Fixture) {
+    // stdin and stdout of the child can be any descriptors of the parent
+    let in_fd = match t.success(
+        &call!("path_open", fixture.fd, INPUT_FILENAME),
+        wasi::path_open(fixture.fd, 0, INPUT_FILENAME, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(fd) => fd,
+        None => return,
+    };
+    if let Some(out_fd) = t.success(
+        &call!("path_open", fixture.fd, OUTPUT_FILENAME),
+        wasi::path_open(
+            fixture.fd, 0, OUTPUT_FILENAME, wasi::OFLAGS_CREAT | wasi::OFLAGS_TRUNC,
+            constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        check_echo(t, fixture, &[wasi_ext_lib::Redirect::PipeIn(in_fd), wasi_ext_lib::Redirect::PipeOut(out_fd)], INPUT);
+        t.success(&call!("fd_close", out_fd), wasi::fd_close(out_fd));
+    }
+    t.success(&call!("fd_close", in_fd), wasi::fd_close(in_fd));
+}
+
+unsafe fn test_fd_table(t: &mut Checks, fixture: &Fixture) {
+    let fd = match t.success(
+        &call!("path_open", fixture.fd, INPUT_FILENAME),
+        wasi::path_open(
+            fixture.fd, 0, INPUT_FILENAME, 0,
+            constants::RIGHTS_ALL ^ wasi::RIGHTS_FD_WRITE, constants::RIGHTS_ALL, 0)) {
+        Some(fd) => fd,
+        None => return,
+    };
+    let line = match t.success(&call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd)) {
+        Some(stat) => child::fd_line(fd, &stat),
+        None => return,
+    };
+    let redirects = [wasi_ext_lib::Redirect::Write((1, fixture.abs_path(OUTPUT_FILENAME)))];
+
+    // child should get a copy of the descriptor table of the parent
+    if expect_status(t, "dump-fds", &[], &HashMap::new(), &redirects, 0) {
+        if let Some(fds) = t.success(&call!("read_file", OUTPUT_FILENAME), read_output(fixture, OUTPUT_FILENAME)) {
+            t.check(&call!("dump-fds"), fds.lines().any(|l| format!("{}\n", l) == line),
+                &format!("descriptor missing in child (expected {:?}, got {:?})", line, fds));
+            t.check(&call!("dump-fds"), fds.lines().any(|l| l.starts_with(&format!("1 {} ", wasi::FILETYPE_REGULAR_FILE.raw()))),
+                &format!("stdout of child is not redirected to a file (got {:?})", fds));
+        }
+    }
+
+    // closing the descriptor in the child should not affect the parent
+    let redirects = [
+        wasi_ext_lib::Redirect::Write((1, fixture.abs_path(OUTPUT_FILENAME))),
+        wasi_ext_lib::Redirect::Close(fd),
+    ];
+    if expect_status(t, "dump-fds", &[], &HashMap::new(), &redirects, 0) {
+        if let Some(fds) = t.success(&call!("read_file", OUTPUT_FILENAME), read_output(fixture, OUTPUT_FILENAME)) {
+            t.check(&call!("dump-fds"), !fds.lines().any(|l| l.starts_with(&format!("{} ", fd))),
+                &format!("closed descriptor {} present in child (got {:?})", fd, fds));
+        }
+    }
+    t.success(&call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd));
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+
+    // redirects using closed descriptors should fail before the child is run
+    expect_status(t, "exit-with-code", &["0"], &HashMap::new(), &[wasi_ext_lib::Redirect::Close(fd)], EXIT_FAILURE);
+    expect_status(
+        t, "exit-with-code", &["0"], &HashMap::new(),
+        &[wasi_ext_lib::Redirect::Duplicate { fd_src: fd, fd_dst: 1 }], EXIT_FAILURE);
+    expect_status(t, "exit-with-code", &["0"], &HashMap::new(), &[wasi_ext_lib::Redirect::PipeIn(fd)], EXIT_FAILURE);
+}
+
+fn test_errors(t: &mut Checks, fixture: &Fixture) {
+    // redirecting stdin from nonexistent file should fail
+    expect_status(
+        t, "exit-with-code", &["0"], &HashMap::new(),
+        &[wasi_ext_lib::Redirect::Read((0, fixture.abs_path("nonexistent")))], EXIT_FAILURE);
+
+    // spawning nonexistent binary should fail
+    let path = fixture.abs_path("nonexistent");
+    let result = wasi_ext_lib::spawn(&path, &[], &HashMap::new(), false, &[]);
+    t.check(&call!("spawn", path), child::exit_status(&result) != 0, "spawning nonexistent binary succeeded");
+}
+
+fn test_background(t: &mut Checks) {
+    // spawn should return right away with pid of the child
+    let call = call!("spawn", "exit-with-code", ["7"], "background");
+    if let Some((code, pid)) = t.success(&call, child::spawn("exit-with-code", &["7"], &HashMap::new(), true, &[])) {
+        t.equal(&call, "exit status", 0, code);
+        t.check(&call, pid > 0, &format!("invalid child pid: {}", pid));
+    }
+}
+
+pub fn test_spawn(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    test_exit_codes(&mut t);
+    test_argv(&mut t, fixture);
+    test_env(&mut t, fixture);
+    unsafe {
+        test_file_redirects(&mut t, fixture);
+        test_pipe_redirects(&mut t, fixture);
+        test_fd_table(&mut t, fixture);
+    }
+    test_errors(&mut t, fixture);
+    test_background(&mut t);
+    t.finish()
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use assertions::Checks;
+use fixture::Fixture;
+use utils::ext_syscall;
+
+// UnameNameType in src/types.ts, names come from window.location and navigator.userAgent
+const NAME_TYPES: &[(&str, i64)] = &[
+    ("href", 0),
+    ("protocol", 1),
+    ("host", 2),
+    ("port", 3),
+    ("pathname", 4),
+    ("search", 5),
+    ("hash", 6),
+    ("origin", 7),
+    ("user_agent", 8),
+];
+
+const INVALID_NAME_TYPES: &[i64] = &[9, 100, -1];
+
+// large enough for any of the names
+const NAME_BUF_LEN: usize = 4096;
+
+// bytes after the buffer passed to the kernel, they should never change
+const GUARD_LEN: usize = 16;
+const GUARD_BYTE: u8 = 0xaa;
+
+// buf is filled with the name followed by a NUL byte, the returned size doesn't include it
+unsafe fn uname(name_type: i64, buf: &mut [u8]) -> Result<usize, wasi::Errno> {
+    let args = format!("{{\"buf_len\":{},\"name_type\":{}}}", buf.len(), name_type);
+    ext_syscall("uname", &args, buf)
+}
+
+unsafe fn get_name(t: &mut Checks, name: &str, name_type: i64) -> Option<String> {
+    let mut buf = vec![0u8; NAME_BUF_LEN];
+    let len = t.success(&call!("uname", name, NAME_BUF_LEN), uname(name_type, &mut buf))?;
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+unsafe fn test_names(t: &mut Checks) -> HashMap<&'static str, String> {
+    let mut names = HashMap::new();
+    for &(name, name_type) in NAME_TYPES {
+        if let Some(value) = get_name(t, name, name_type) {
+            names.insert(name, value);
+        }
+    }
+    names
+}
+
+fn test_consistency(t: &mut Checks, names: &HashMap<&str, String>) {
+    let get = |name: &str| names.get(name).map(|s| s.as_str());
+    let call = call!("uname", "origin");
+    if let (Some(origin), Some(protocol), Some(host)) = (get("origin"), get("protocol"), get("host")) {
+        t.equal(&call, "origin", format!("{}//{}", protocol, host), String::from(origin));
+    }
+    if let (Some(href), Some(origin), Some(pathname), Some(search), Some(hash)) =
+        (get("href"), get("origin"), get("pathname"), get("search"), get("hash")) {
+        t.equal(
+            &call!("uname", "href"), "href", format!("{}{}{}{}", origin, pathname, search, hash), String::from(href));
+    }
+    if let (Some(host), Some(port)) = (get("host"), get("port")) {
+        t.check(
+            &call!("uname", "port"), port.is_empty() || host.ends_with(&format!(":{}", port)),
+            &format!("port {:?} doesn't match host {:?}", port, host));
+    }
+
+    // fixed prefixes and suffixes of location fields
+    for &(name, ok) in [
+        ("protocol", get("protocol").map(|p| p.ends_with(':'))),
+        ("pathname", get("pathname").map(|p| p.starts_with('/'))),
+        ("search", get("search").map(|s| s.is_empty() || s.starts_with('?'))),
+        ("hash", get("hash").map(|h| h.is_empty() || h.starts_with('#'))),
+        ("user_agent", get("user_agent").map(|u| !u.is_empty())),
+    ].iter() {
+        if let Some(ok) = ok {
+            t.check(&call!("uname", name), ok, &format!("malformed {}: {:?}", name, get(name).unwrap()));
+        }
+    }
+}
+
+// Every buffer too small for the name and its NUL terminator should fail with ENOBUFS,
+// larger buffers should hold the name. Nothing may be written past the buffer.
+unsafe fn test_buffer_sizes(t: &mut Checks, names: &HashMap<&str, String>) {
+    for &(name, name_type) in NAME_TYPES {
+        let value = match names.get(name) {
+            Some(value) => value.as_bytes(),
+            None => continue,
+        };
+        let mut failures = Vec::new();
+        for len in 0..=value.len() + 1 {
+            let mut buf = vec![GUARD_BYTE; len + GUARD_LEN];
+            let result = uname(name_type, &mut buf[..len]);
+            let fits = len > value.len();
+            match result {
+                Ok(n) if fits => {
+                    if n != value.len() || &buf[..n] != value || buf[n] != 0 {
+                        failures.push(format!("{}: got {:?}", len, String::from_utf8_lossy(&buf[..len])));
+                    }
+                }
+                Err(wasi::ERRNO_NOBUFS) if !fits => {}
+                _ => failures.push(format!("{}: got {:?}", len, result)),
+            }
+            if buf[len..].iter().any(|&b| b != GUARD_BYTE) {
+                failures.push(format!("{}: bytes written past the buffer", len));
+            }
+        }
+        t.check(
+            &call!("uname", name, format!("0..={}", value.len() + 1)), failures.is_empty(),
+            &format!("unexpected results for buffer sizes: {}", failures.join(", ")));
+    }
+}
+
+unsafe fn test_invalid(t: &mut Checks) {
+    for &name_type in INVALID_NAME_TYPES {
+        let mut buf = vec![0u8; NAME_BUF_LEN];
+        t.error(
+            &call!("uname", name_type, NAME_BUF_LEN), uname(name_type, &mut buf),
+            wasi::ERRNO_INVAL, "uname with invalid name type succeeded");
+    }
+}
+
+pub fn test_uname(_: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        let names = test_names(&mut t);
+        test_consistency(&mut t, &names);
+        test_buffer_sizes(&mut t, &names);
+        test_invalid(&mut t);
+    }
+    t.finish()
+}
@@ -0,0 +1,319 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use constants;
+use syscalls::fd_readdir::wasi_ls;
+
+// every scratch directory is created in the preopened directory under this prefix
+pub const SCRATCH_PREFIX: &str = "syscalls_test.";
+
+// tests create names up to 2049 characters long, a dirent has to fit in the buffer
+const READDIR_BUF_LEN: usize = 8192;
+
+// declarative description of scratch directory contents
+pub enum Node {
+    File(&'static str, &'static [u8]),
+    Dir(&'static str, &'static [Node]),
+    // (name, target)
+    Symlink(&'static str, &'static str),
+}
+
+pub const EMPTY: &[Node] = &[];
+
+// files most tests work on, names are defined in constants
+pub const SAMPLE: &[Node] = &[
+    Node::File(constants::SAMPLE_TEXT_FILENAME, constants::SAMPLE_TEXT),
+    Node::Dir(constants::SAMPLE_DIR_FILENAME, &[
+        Node::File("ent0", &[]),
+        Node::File("ent1", &[]),
+        Node::File("ent2", &[]),
+        Node::File("ent3", &[]),
+        Node::File("ent4", &[]),
+        Node::File("ent5", &[]),
+        Node::File("ent6", &[]),
+        Node::File("ent7", &[]),
+        Node::File("ent8", &[]),
+        Node::File("ent9", &[]),
+    ]),
+    Node::Symlink(constants::SAMPLE_LINK_FILENAME, constants::SAMPLE_TEXT_FILENAME),
+    Node::Symlink(constants::SAMPLE_DIR_LINK_FILENAME, constants::SAMPLE_DIR_FILENAME),
+];
+
+// scratch directory that still has to be removed
+struct Scratch {
+    name: String,
+    // set once the directory is opened
+    fd: Option<wasi::Fd>,
+    // nodes created outside the directory, paths are relative to constants::PWD_DESC
+    nodes: Vec<String>,
+}
+
+thread_local! {
+    static LIVE: RefCell<Vec<Scratch>> = RefCell::new(Vec::new());
+}
+
+// Fresh directory a single test works in. It is removed together with its contents
+// once the test finishes, whatever the outcome.
+pub struct Fixture {
+    // descriptor of the scratch directory, opened with all rights
+    pub fd: wasi::Fd,
+    // name of the scratch directory in constants::PWD_DESC
+    pub name: String,
+    // name constants::PWD_DESC was preopened under, absolute paths start with it
+    preopen: String,
+}
+
+impl Fixture {
+    pub fn new(test_name: &str, nodes: &[Node]) -> Result<Self, String> {
+        let preopen = unsafe { preopen_name()? };
+        let name = unsafe { create_unique(test_name)? };
+        LIVE.with(|l| l.borrow_mut().push(Scratch { name: name.clone(), fd: None, nodes: Vec::new() }));
+        let fd = match unsafe { wasi::path_open(
+            constants::PWD_DESC, 0, &name, wasi::OFLAGS_DIRECTORY,
+            constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) } {
+            Ok(d) => d,
+            Err(e) => {
+                forget(&name);
+                _ = unsafe { remove_tree(constants::PWD_DESC, &name) };
+                return Err(format!("Could not setup test environment: Couldn't open {} ({:?})", name, e));
+            }
+        };
+        with_scratch(&name, |s| s.fd = Some(fd));
+        let fixture = Fixture { fd, name, preopen };
+        if let Err(e) = unsafe { populate(fixture.fd, nodes) } {
+            return Err(format!("Could not setup test environment: {}", e));
+        }
+        Ok(fixture)
+    }
+
+    // path relative to constants::PWD_DESC and to the initial working directory
+    pub fn path(&self, name: &str) -> String {
+        format!("{}/{}", self.name, name)
+    }
+
+    // Path that doesn't depend on the working directory, for calls that take no directory
+    // descriptor. path is relative to constants::PWD_DESC.
+    pub fn preopen_path(&self, path: &str) -> String {
+        if self.preopen.ends_with('/') {
+            format!("{}{}", self.preopen, path)
+        } else {
+            format!("{}/{}", self.preopen, path)
+        }
+    }
+
+    // preopen_path of name in the scratch directory
+    pub fn abs_path(&self, name: &str) -> String {
+        self.preopen_path(&self.path(name))
+    }
+
+    // Path of a FIFO or device node named after the scratch directory, only the device
+    // filesystem supports them. The node is unlinked together with the directory.
+    pub fn dev_path(&self, suffix: &str) -> String {
+        let path = format!("dev/{}.{}", self.name, suffix);
+        with_scratch(&self.name, |s| s.nodes.push(path.clone()));
+        path
+    }
+
+    // like drop, but reports errors
+    pub fn remove(self) -> Result<(), String> {
+        self.cleanup()
+    }
+
+    fn cleanup(&self) -> Result<(), String> {
+        match forget(&self.name) {
+            Some(scratch) => unsafe { teardown(scratch) },
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        if let Err(e) = self.cleanup() {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+fn with_scratch<F: FnOnce(&mut Scratch)>(name: &str, f: F) {
+    LIVE.with(|l| {
+        if let Some(scratch) = l.borrow_mut().iter_mut().find(|s| s.name == name) {
+            f(scratch);
+        }
+    })
+}
+
+// returns None if the directory was already removed
+fn forget(name: &str) -> Option<Scratch> {
+    LIVE.with(|l| {
+        let mut live = l.borrow_mut();
+        let i = live.iter().position(|s| s.name == name)?;
+        Some(live.remove(i))
+    })
+}
+
+unsafe fn preopen_name() -> Result<String, String> {
+    let prestat = match wasi::fd_prestat_get(constants::PWD_DESC) {
+        Ok(p) => p,
+        Err(e) => return Err(format!("Could not setup test environment: fd_prestat_get failed ({:?})", e)),
+    };
+    let mut name = vec![0u8; prestat.u.dir.pr_name_len];
+    if let Err(e) = wasi::fd_prestat_dir_name(constants::PWD_DESC, name.as_mut_ptr(), name.len()) {
+        return Err(format!("Could not setup test environment: fd_prestat_dir_name failed ({:?})", e));
+    }
+    // some runtimes count a terminating NUL in the length
+    match String::from_utf8(name) {
+        Ok(name) => Ok(String::from(name.trim_end_matches('\0'))),
+        Err(e) => Err(format!("Could not setup test environment: preopen name isn't UTF-8 ({})", e)),
+    }
+}
+
+unsafe fn create_unique(test_name: &str) -> Result<String, String> {
+    let mut last_err = wasi::ERRNO_EXIST;
+    for _ in 0..8 {
+        let mut suffix = [0u8; 4];
+        if let Err(e) = wasi::random_get(suffix.as_mut_ptr(), suffix.len()) {
+            return Err(format!("Could not setup test environment: random_get failed ({:?})", e));
+        }
+        let name = format!("{}{}.{:08x}", SCRATCH_PREFIX, test_name, u32::from_le_bytes(suffix));
+        match wasi::path_create_directory(constants::PWD_DESC, &name) {
+            Ok(()) => return Ok(name),
+            Err(e) if e == wasi::ERRNO_EXIST => last_err = e,
+            Err(e) => return Err(format!("Could not setup test environment: Couldn't create {} ({:?})", name, e)),
+        }
+    }
+    Err(format!("Could not setup test environment: Couldn't create a unique directory ({:?})", last_err))
+}
+
+unsafe fn populate(fd: wasi::Fd, nodes: &[Node]) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::File(name, contents) => {
+                let file_fd = match wasi::path_open(
+                    fd, 0, name, wasi::OFLAGS_CREAT | wasi::OFLAGS_EXCL,
+                    constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+                    Ok(d) => d,
+                    Err(e) => return Err(format!("Couldn't create {} ({:?})", name, e)),
+                };
+                let result = if contents.is_empty() {
+                    Ok(0)
+                } else {
+                    wasi::fd_write(file_fd, &[wasi::Ciovec { buf: contents.as_ptr(), buf_len: contents.len() }])
+                };
+                if let Err(e) = wasi::fd_close(file_fd) {
+                    return Err(format!("Couldn't close {} ({:?})", name, e));
+                }
+                match result {
+                    Ok(n) if n == contents.len() => {}
+                    Ok(n) => return Err(format!("Couldn't write {} (wrote {} of {} bytes)", name, n, contents.len())),
+                    Err(e) => return Err(format!("Couldn't write {} ({:?})", name, e)),
+                }
+            }
+            Node::Dir(name, children) => {
+                if let Err(e) = wasi::path_create_directory(fd, name) {
+                    return Err(format!("Couldn't create {} ({:?})", name, e));
+                }
+                let dir_fd = match wasi::path_open(
+                    fd, 0, name, wasi::OFLAGS_DIRECTORY,
+                    constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+                    Ok(d) => d,
+                    Err(e) => return Err(format!("Couldn't open {} ({:?})", name, e)),
+                };
+                let result = populate(dir_fd, children);
+                if let Err(e) = wasi::fd_close(dir_fd) {
+                    return Err(format!("Couldn't close {} ({:?})", name, e));
+                }
+                result?;
+            }
+            Node::Symlink(name, target) => {
+                if let Err(e) = wasi::path_symlink(target, fd, name) {
+                    return Err(format!("Couldn't create symlink {} -> {} ({:?})", name, target, e));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// The descriptor is closed before the directory is removed, also after a panic, otherwise
+// it would keep referring to the removed directory until the process exits. Nodes the test
+// unlinked itself are skipped.
+unsafe fn teardown(scratch: Scratch) -> Result<(), String> {
+    if let Some(fd) = scratch.fd {
+        if let Err(e) = wasi::fd_close(fd) {
+            return Err(format!("Couldn't tear down test environment: Couldn't close {} ({:?})", fd, e));
+        }
+    }
+    for node in &scratch.nodes {
+        match wasi::path_unlink_file(constants::PWD_DESC, node) {
+            Ok(()) | Err(wasi::ERRNO_NOENT) => {}
+            Err(e) => return Err(format!("Couldn't tear down test environment: Couldn't unlink {} ({:?})", node, e)),
+        }
+    }
+    match remove_tree(constants::PWD_DESC, &scratch.name) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(format!("Couldn't tear down test environment: {}", e)),
+    }
+}
+
+// removes a directory with all its contents, symlinks are not followed
+unsafe fn remove_tree(parent: wasi::Fd, name: &str) -> Result<(), String> {
+    let fd = match wasi::path_open(
+        parent, 0, name, wasi::OFLAGS_DIRECTORY,
+        constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+        Ok(d) => d,
+        Err(e) => return Err(format!("Couldn't open {} ({:?})", name, e)),
+    };
+    let result = remove_contents(fd);
+    if let Err(e) = wasi::fd_close(fd) {
+        return Err(format!("Couldn't close {} ({:?})", name, e));
+    }
+    result?;
+    if let Err(e) = wasi::path_remove_directory(parent, name) {
+        return Err(format!("Couldn't remove {} ({:?})", name, e));
+    }
+    Ok(())
+}
+
+unsafe fn remove_contents(fd: wasi::Fd) -> Result<(), String> {
+    let (dirents, _) = wasi_ls(fd, READDIR_BUF_LEN, 0, true)?;
+    for (name, dirent) in dirents.iter() {
+        if name == "." || name == ".." {
+            continue;
+        }
+        if dirent.d_type == wasi::FILETYPE_DIRECTORY.raw() {
+            remove_tree(fd, name)?;
+        } else if let Err(e) = wasi::path_unlink_file(fd, name) {
+            return Err(format!("Couldn't remove {} ({:?})", name, e));
+        }
+    }
+    Ok(())
+}
+
+// Removes scratch directories of the test that panicked. This also works when the binary
+// is built with panic=abort (the default for wasm32-wasi), in which case drop is never called.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let live: Vec<Scratch> = LIVE.with(|l| l.borrow_mut().drain(..).collect());
+        for scratch in live {
+            if let Err(e) = unsafe { teardown(scratch) } {
+                eprintln!("{}", e);
+            }
+        }
+    }));
+}
+
+// runs a test in a fresh scratch directory and removes the directory afterwards
+pub fn run(test_name: &str, nodes: &[Node], test: fn(&Fixture) -> Result<(), String>) -> Result<(), String> {
+    let fixture = Fixture::new(test_name, nodes)?;
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| test(&fixture))) {
+        Ok(r) => r,
+        Err(_) => Err(String::from("test panicked")),
+    };
+    match (result, fixture.remove()) {
+        (Err(e), Err(cleanup)) => Err(format!("{}; {}", e, cleanup)),
+        (result, cleanup) => result.and(cleanup),
+    }
+}
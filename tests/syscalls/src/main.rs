@@ -1,94 +1,178 @@
+#[macro_use]
+mod assertions;
 mod syscalls;
 mod constants;
 mod utils;
 mod custom_syscall_api;
+mod cli;
+mod report;
+mod fixture;
+mod trace;
+mod child;
+
+use std::env;
+use std::time::Instant;
+
+use fixture::{Fixture, Node};
+use report::{Status, Summary, TestResult};
 
 use syscalls::*;
 use custom_syscall_api::*;
 
+struct Test {
+    // contents of the scratch directory the test is run in
+    fixture: &'static [Node],
+    run: fn(&Fixture) -> Result<(), String>,
+}
+
+// tests that verify the command line and can only pass when it matches constants::ARGV
+const ARGV_TESTS: [&str; 2] = ["args_sizes_get", "args_get"];
+
 fn main() -> Result<(), String>{
-    let tests: Vec<(&str, fn() -> Result<(), String>)> = vec![
-        ("environ_sizes_get", environ_sizes_get::test_environ_sizes_get as fn() -> Result<(), String>),
-        ("args_sizes_get", args_sizes_get::test_args_sizes_get as fn() -> Result<(), String>),
-        ("fd_prestat_get", fd_prestat_get::test_fd_prestat_get as fn() -> Result<(), String>),
-        ("fd_fdstat_get", fd_fdstat_get::test_fd_fdstat_get as fn() -> Result<(), String>),
-        ("fd_filestat_get", fd_filestat_get::test_fd_filestat_get as fn() -> Result<(), String>),
-        ("fd_read", fd_read::test_fd_read as fn() -> Result<(), String>),
-        ("fd_write", fd_write::test_fd_write as fn() -> Result<(), String>),
-        ("fd_prestat_dir_name", fd_prestat_dir_name::test_fd_prestat_dir_name as fn() -> Result<(), String>),
-        ("environ_get", environ_get::test_environ_get as fn() -> Result<(), String>),
-        ("args_get", args_get::test_args_get as fn() -> Result<(), String>),
-        ("fd_close", fd_close::test_fd_close as fn() -> Result<(), String>),
-        ("path_open", path_open::test_path_open as fn() -> Result<(), String>),
-        ("fd_seek", fd_seek::test_fd_seek as fn() -> Result<(), String>),
-        ("fd_tell", fd_tell::test_fd_tell as fn() -> Result<(), String>),
-        ("fd_readdir", fd_readdir::test_fd_readdir as fn() -> Result<(), String>),
-        ("path_filestat_get", path_filestat_get::test_path_filestat_get as fn() -> Result<(), String>),
-        ("random_get", random_get::test_random_get as fn() -> Result<(), String>),
-        ("clock_time_get", clock_time_get::test_clock_time_get as fn() -> Result<(), String>),
-        ("path_readlink", path_readlink::test_path_readlink as fn() -> Result<(), String>),
-        ("path_symlink", path_symlink::test_path_symlink as fn() -> Result<(), String>),
-        ("fd_filestat_set_times", fd_filestat_set_times::test_fd_filestat_set_times as fn() -> Result<(), String>),
-        ("path_filestat_set_times", path_filestat_set_times::test_path_filestat_set_times as fn() -> Result<(), String>),
-        ("poll_oneoff", poll_oneoff::test_poll_oneoff as fn() -> Result<(), String>),
-        ("isatty", isatty::test_isatty as fn() -> Result<(), String>),
-        ("getcwd_chdir", getcwd_chdir::test_getcwd_chdir as fn() -> Result<(), String>),
-        ("set_env", set_env::test_set_env as fn() -> Result<(), String>),
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(|a| a.as_str()) == Some(child::CHILD_ARG) {
+        std::process::exit(child::run(&args[1..]));
+    }
+
+    let tests: Vec<(&str, Test)> = vec![
+        ("environ_sizes_get", Test { fixture: fixture::EMPTY, run: environ_sizes_get::test_environ_sizes_get }),
+        ("args_sizes_get", Test { fixture: fixture::EMPTY, run: args_sizes_get::test_args_sizes_get }),
+        ("fd_prestat_get", Test { fixture: fixture::SAMPLE, run: fd_prestat_get::test_fd_prestat_get }),
+        ("fd_fdstat_get", Test { fixture: fixture::SAMPLE, run: fd_fdstat_get::test_fd_fdstat_get }),
+        ("fd_filestat_get", Test { fixture: fixture::SAMPLE, run: fd_filestat_get::test_fd_filestat_get }),
+        ("fd_read", Test { fixture: fixture::SAMPLE, run: fd_read::test_fd_read }),
+        ("fd_write", Test { fixture: fixture::SAMPLE, run: fd_write::test_fd_write }),
+        ("fd_prestat_dir_name", Test { fixture: fixture::EMPTY, run: fd_prestat_dir_name::test_fd_prestat_dir_name }),
+        ("environ_get", Test { fixture: fixture::EMPTY, run: environ_get::test_environ_get }),
+        ("args_get", Test { fixture: fixture::EMPTY, run: args_get::test_args_get }),
+        ("fd_close", Test { fixture: fixture::SAMPLE, run: fd_close::test_fd_close }),
+        ("path_open", Test { fixture: fixture::SAMPLE, run: path_open::test_path_open }),
+        ("fd_seek", Test { fixture: fixture::SAMPLE, run: fd_seek::test_fd_seek }),
+        ("fd_tell", Test { fixture: fixture::SAMPLE, run: fd_tell::test_fd_tell }),
+        ("fd_readdir", Test { fixture: fixture::SAMPLE, run: fd_readdir::test_fd_readdir }),
+        ("path_filestat_get", Test { fixture: fixture::SAMPLE, run: path_filestat_get::test_path_filestat_get }),
+        ("random_get", Test { fixture: fixture::EMPTY, run: random_get::test_random_get }),
+        ("clock_res_get", Test { fixture: fixture::EMPTY, run: clock_res_get::test_clock_res_get }),
+        ("clock_time_get", Test { fixture: fixture::EMPTY, run: clock_time_get::test_clock_time_get }),
+        ("path_readlink", Test { fixture: fixture::SAMPLE, run: path_readlink::test_path_readlink }),
+        ("path_symlink", Test { fixture: fixture::SAMPLE, run: path_symlink::test_path_symlink }),
+        ("fd_filestat_set_times", Test { fixture: fixture::SAMPLE, run: fd_filestat_set_times::test_fd_filestat_set_times }),
+        ("path_filestat_set_times", Test { fixture: fixture::SAMPLE, run: path_filestat_set_times::test_path_filestat_set_times }),
+        ("poll_oneoff", Test { fixture: fixture::SAMPLE, run: poll_oneoff::test_poll_oneoff }),
+        ("path_rename", Test { fixture: fixture::EMPTY, run: path_rename::test_path_rename }),
+        ("path_link", Test { fixture: fixture::EMPTY, run: path_link::test_path_link }),
+        ("directory_lifecycle", Test { fixture: fixture::EMPTY, run: directory_lifecycle::test_directory_lifecycle }),
+        ("fd_renumber", Test { fixture: fixture::SAMPLE, run: fd_renumber::test_fd_renumber }),
+        ("fd_fdstat_set_flags", Test { fixture: fixture::EMPTY, run: fd_fdstat_set_flags::test_fd_fdstat_set_flags }),
+        ("fd_pread_pwrite", Test { fixture: fixture::EMPTY, run: fd_pread_pwrite::test_fd_pread_pwrite }),
+        ("fd_filestat_set_size", Test { fixture: fixture::EMPTY, run: fd_filestat_set_size::test_fd_filestat_set_size }),
+        ("rights", Test { fixture: fixture::EMPTY, run: rights::test_rights }),
+        ("isatty", Test { fixture: fixture::SAMPLE, run: isatty::test_isatty }),
+        ("getcwd_chdir", Test { fixture: fixture::SAMPLE, run: getcwd_chdir::test_getcwd_chdir }),
+        ("set_env", Test { fixture: fixture::EMPTY, run: set_env::test_set_env }),
+        ("spawn", Test { fixture: fixture::EMPTY, run: spawn::test_spawn }),
+        ("ioctl", Test { fixture: fixture::SAMPLE, run: ioctl::test_ioctl }),
+        ("mount", Test { fixture: fixture::SAMPLE, run: mount::test_mount }),
+        ("fifo", Test { fixture: fixture::EMPTY, run: fifo::test_fifo }),
+        ("mem_devices", Test { fixture: fixture::EMPTY, run: mem_devices::test_mem_devices }),
+        ("proc", Test { fixture: fixture::SAMPLE, run: proc::test_proc }),
+        ("kill", Test { fixture: fixture::EMPTY, run: kill::test_kill }),
+        ("event_source", Test { fixture: fixture::EMPTY, run: event_source::test_event_source }),
+        ("uname", Test { fixture: fixture::EMPTY, run: uname::test_uname }),
     ];
 
-    unsafe {
-        let tmp_fd = wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
-            wasi::OFLAGS_CREAT, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0).unwrap();
-        wasi::fd_write(
-            tmp_fd,
-            &[wasi::Ciovec{
-                buf: constants::SAMPLE_TEXT.as_ptr(),
-                buf_len: constants::SAMPLE_TEXT_LEN
-            }]).unwrap();
-        wasi::fd_close(tmp_fd).unwrap();
-        wasi::path_create_directory(constants::PWD_DESC, constants::SAMPLE_DIR_FILENAME).unwrap();
-        wasi::path_symlink(
-            constants::SAMPLE_TEXT_FILENAME,
-            constants::PWD_DESC,
-            constants::SAMPLE_LINK_FILENAME).unwrap();
-        wasi::path_symlink(
-            constants::SAMPLE_DIR_FILENAME,
-            constants::PWD_DESC,
-            constants::SAMPLE_DIR_LINK_FILENAME).unwrap();
-        for i in 0..10 {
-            wasi::fd_close(wasi::path_open(
-                constants::PWD_DESC, 0,
-                &format!("{}/ent{}", constants::SAMPLE_DIR_FILENAME, i),
-                wasi::OFLAGS_CREAT, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0).unwrap()).unwrap();
+    let opts = match cli::parse(&args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n{}", e, cli::USAGE);
+            return Err(e);
         }
+    };
+    if opts.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
     }
-    let mut fails: u32 = 0;
-    for (name, test) in &tests {
-        let result = test();
-        if let Err(_) = result { fails += 1; }
-        println!("[TEST] {}: {:?}", name, result);
+    let selected = opts.select(&tests);
+    if opts.list {
+        for (name, _) in &selected {
+            println!("{}", name);
+        }
+        return Ok(());
     }
+    if selected.is_empty() {
+        return Err(String::from("No tests match given filters"));
+    }
+    let mut reporter = report::new_reporter(opts.format, opts.output.as_ref().map(|s| s.as_str()))?;
+
+    let mut recorder = match &opts.record {
+        Some(path) => Some(trace::Recorder::create(path)?),
+        None => None,
+    };
+    let reference = match &opts.diff {
+        Some(path) => Some(trace::Reference::load(path)?),
+        None => None,
+    };
 
-    unsafe {
-        // teardown test environment
-        wasi::path_unlink_file(constants::PWD_DESC, constants::SAMPLE_TEXT_FILENAME).unwrap();
-        wasi::path_unlink_file(constants::PWD_DESC, constants::SAMPLE_LINK_FILENAME).unwrap();
-        wasi::path_unlink_file(constants::PWD_DESC, constants::SAMPLE_DIR_LINK_FILENAME).unwrap();
-        let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
-            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
-            Ok(d) => d,
-            Err(e) => { return Err(format!("Couldn't tear down test environment: {}", e)) }
+    fixture::install_panic_hook();
+    let mut summary = Summary::default();
+    let run_start = Instant::now();
+    if let Err(e) = reporter.begin(selected.len()) {
+        return Err(format!("Couldn't write test report: {}", e));
+    }
+    for (name, test) in &selected {
+        // drop checks left over by setup code
+        assertions::take_checks();
+        let start = Instant::now();
+        let mut status = if !opts.canonical_argv && ARGV_TESTS.contains(name) {
+            Status::Skipped(format!("command line differs from {:?}", constants::ARGV))
+        } else {
+            match fixture::run(name, test.fixture, test.run) {
+                Ok(()) => Status::Passed,
+                Err(e) => Status::Failed(e),
+            }
         };
-        let (dirents, _) = fd_readdir::wasi_ls(desc, 256, 0, true)?;
-        for name in dirents.keys() {
-            wasi::path_unlink_file(desc, &name).unwrap();
+        let duration = start.elapsed();
+        let mut checks = assertions::take_checks();
+        let skipped = match status { Status::Skipped(_) => true, _ => false };
+        if !skipped && (recorder.is_some() || reference.is_some()) {
+            let entries = trace::entries(name, &checks, opts.record_values);
+            if let Some(r) = &mut recorder {
+                r.write(&entries)?;
+            }
+            // in differential mode the reference trace replaces built-in expectations
+            if reference.is_some() && entries.is_empty() {
+                status = Status::Skipped(String::from("not comparable, the test doesn't record its calls"));
+            } else if let Some(r) = &reference {
+                status = match r.diff(name, &entries) {
+                    None => Status::Skipped(String::from("test missing from the reference trace")),
+                    Some(diff) => {
+                        let failures: Vec<&String> = diff.iter().filter_map(|c| c.message.as_ref()).collect();
+                        let status = match assertions::join_failures(&failures) {
+                            Ok(()) => Status::Passed,
+                            Err(e) => Status::Failed(e),
+                        };
+                        checks = diff;
+                        status
+                    }
+                };
+            }
+        }
+        match status {
+            Status::Passed => summary.passed += 1,
+            Status::Failed(_) => summary.failed += 1,
+            Status::Skipped(_) => summary.skipped += 1,
         }
-        wasi::path_remove_directory(constants::PWD_DESC, constants::SAMPLE_DIR_FILENAME).unwrap();
+        let result = TestResult { name: String::from(*name), duration, status, checks };
+        if let Err(e) = reporter.result(&result) {
+            return Err(format!("Couldn't write test report: {}", e));
+        }
+    }
+    summary.duration = run_start.elapsed();
+
+    if let Err(e) = reporter.finish(&summary) {
+        return Err(format!("Couldn't write test report: {}", e));
     }
-    println!("[SUMMARY]: {} tests succeeded, {} tests failed",  tests.len() as u32 - fails, fails);
-    if fails == 0 {
+    if summary.failed == 0 {
         Ok(())
     } else {
         Err(String::from("Tests failed"))
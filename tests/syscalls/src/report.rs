@@ -0,0 +1,334 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use assertions::Check;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Tap,
+    Junit,
+    Json,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "text" => Ok(Format::Text),
+            "tap" => Ok(Format::Tap),
+            "junit" => Ok(Format::Junit),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown report format: {} (expected text, tap, junit or json)", name)),
+        }
+    }
+}
+
+impl Default for Format {
+    fn default() -> Self { Format::Text }
+}
+
+#[derive(Debug)]
+pub enum Status {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub duration: Duration,
+    pub status: Status,
+    // sub-results of tests using assertions::Checks, empty for other tests
+    pub checks: Vec<Check>,
+}
+
+impl TestResult {
+    pub fn failed_checks(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+
+    // checks that weren't made, the message gives the reason
+    pub fn skipped_checks(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| c.passed && c.message.is_some())
+    }
+
+    // first failing call, for tests that don't record checks it is extracted
+    // from error messages of the form "In fd_read(3): ..."
+    pub fn failed_assertion(&self) -> Option<&str> {
+        if let Some(c) = self.failed_checks().next() {
+            return Some(&c.call);
+        }
+        match &self.status {
+            Status::Failed(e) if e.starts_with("In ") => {
+                e.find("): ").map(|i| &e[3..i + 1])
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub passed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    pub duration: Duration,
+}
+
+pub trait Reporter {
+    fn begin(&mut self, n_tests: usize) -> io::Result<()>;
+    fn result(&mut self, result: &TestResult) -> io::Result<()>;
+    fn finish(&mut self, summary: &Summary) -> io::Result<()>;
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1000.0 + d.subsec_nanos() as f64 / 1_000_000.0
+}
+
+pub fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 allows no C0 control characters other than these, not even escaped
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn json_opt(s: Option<&str>) -> String {
+    match s {
+        Some(s) => format!("\"{}\"", escape_json(s)),
+        None => String::from("null"),
+    }
+}
+
+// output format used before reporters were introduced
+pub struct TextReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Reporter for TextReporter<W> {
+    fn begin(&mut self, _n_tests: usize) -> io::Result<()> { Ok(()) }
+
+    fn result(&mut self, result: &TestResult) -> io::Result<()> {
+        match &result.status {
+            Status::Passed => {
+                writeln!(self.out, "[TEST] {}: {:?}", result.name, Ok::<(), String>(()))?;
+                for c in result.skipped_checks() {
+                    writeln!(self.out, "[SKIP] {}: {}", result.name, c.message.as_ref().unwrap())?;
+                }
+                Ok(())
+            }
+            Status::Failed(e) => writeln!(self.out, "[TEST] {}: {:?}", result.name, Err::<(), &String>(e)),
+            Status::Skipped(reason) => writeln!(self.out, "[SKIP] {}: {}", result.name, reason),
+        }
+    }
+
+    fn finish(&mut self, summary: &Summary) -> io::Result<()> {
+        if summary.skipped == 0 {
+            writeln!(
+                self.out, "[SUMMARY]: {} tests succeeded, {} tests failed",
+                summary.passed, summary.failed)
+        } else {
+            writeln!(
+                self.out, "[SUMMARY]: {} tests succeeded, {} tests failed, {} tests skipped",
+                summary.passed, summary.failed, summary.skipped)
+        }
+    }
+}
+
+pub struct TapReporter<W: Write> {
+    out: W,
+    index: usize,
+}
+
+impl<W: Write> Reporter for TapReporter<W> {
+    fn begin(&mut self, n_tests: usize) -> io::Result<()> {
+        writeln!(self.out, "TAP version 13")?;
+        writeln!(self.out, "1..{}", n_tests)
+    }
+
+    fn result(&mut self, result: &TestResult) -> io::Result<()> {
+        self.index += 1;
+        match &result.status {
+            Status::Passed => {
+                writeln!(
+                    self.out, "ok {} - {} # time={:.3}ms",
+                    self.index, result.name, millis(result.duration))?;
+                for c in result.skipped_checks() {
+                    writeln!(self.out, "# {}", c.message.as_ref().unwrap())?;
+                }
+                Ok(())
+            }
+            Status::Skipped(reason) => writeln!(
+                self.out, "ok {} - {} # SKIP {}", self.index, result.name, reason),
+            Status::Failed(e) => {
+                writeln!(self.out, "not ok {} - {} # time={:.3}ms", self.index, result.name, millis(result.duration))?;
+                writeln!(self.out, "  ---")?;
+                writeln!(self.out, "  message: \"{}\"", escape_json(e))?;
+                if let Some(a) = result.failed_assertion() {
+                    writeln!(self.out, "  assertion: \"{}\"", escape_json(a))?;
+                }
+                if !result.checks.is_empty() {
+                    writeln!(self.out, "  checks: {}", result.checks.len())?;
+                    writeln!(self.out, "  failures:")?;
+                    for c in result.failed_checks() {
+                        writeln!(self.out, "    - \"{}\"", escape_json(c.message.as_ref().unwrap()))?;
+                    }
+                }
+                writeln!(self.out, "  duration_ms: {:.3}", millis(result.duration))?;
+                writeln!(self.out, "  ...")
+            }
+        }
+    }
+
+    fn finish(&mut self, summary: &Summary) -> io::Result<()> {
+        writeln!(
+            self.out, "# passed {}, failed {}, skipped {}",
+            summary.passed, summary.failed, summary.skipped)
+    }
+}
+
+// newline-delimited JSON, one object per test and a final summary object
+pub struct JsonReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Reporter for JsonReporter<W> {
+    fn begin(&mut self, n_tests: usize) -> io::Result<()> {
+        writeln!(self.out, "{{\"type\":\"plan\",\"tests\":{}}}", n_tests)
+    }
+
+    fn result(&mut self, result: &TestResult) -> io::Result<()> {
+        let (status, error) = match &result.status {
+            Status::Passed => ("passed", None),
+            Status::Failed(e) => ("failed", Some(e.as_str())),
+            Status::Skipped(reason) => ("skipped", Some(reason.as_str())),
+        };
+        let checks: Vec<String> = result.checks.iter().map(|c| format!(
+            "{{\"call\":\"{}\",\"observed\":\"{}\",\"passed\":{},\"message\":{}}}",
+            escape_json(&c.call), escape_json(&c.observed), c.passed,
+            json_opt(c.message.as_ref().map(|m| m.as_str())))).collect();
+        writeln!(
+            self.out,
+            "{{\"type\":\"test\",\"name\":\"{}\",\"status\":\"{}\",\"duration_ms\":{:.3},\"error\":{},\"assertion\":{},\"checks\":[{}]}}",
+            escape_json(&result.name), status, millis(result.duration),
+            json_opt(error), json_opt(result.failed_assertion()), checks.join(","))
+    }
+
+    fn finish(&mut self, summary: &Summary) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "{{\"type\":\"summary\",\"passed\":{},\"failed\":{},\"skipped\":{},\"duration_ms\":{:.3}}}",
+            summary.passed, summary.failed, summary.skipped, millis(summary.duration))
+    }
+}
+
+// JUnit XML needs totals in the root element, so results are buffered until the run finishes
+pub struct JunitReporter<W: Write> {
+    out: W,
+    cases: Vec<String>,
+}
+
+impl<W: Write> Reporter for JunitReporter<W> {
+    fn begin(&mut self, _n_tests: usize) -> io::Result<()> { Ok(()) }
+
+    fn result(&mut self, result: &TestResult) -> io::Result<()> {
+        let mut case = format!(
+            "    <testcase classname=\"syscalls_test\" name=\"{}\" time=\"{:.6}\"",
+            escape_xml(&result.name), millis(result.duration) / 1000.0);
+        match &result.status {
+            Status::Passed => {
+                let skipped: Vec<&str> = result.skipped_checks()
+                    .filter_map(|c| c.message.as_ref().map(|m| m.as_str()))
+                    .collect();
+                if skipped.is_empty() {
+                    case.push_str("/>")
+                } else {
+                    case.push_str(&format!(
+                        ">\n      <system-out>{}</system-out>\n    </testcase>", escape_xml(&skipped.join("\n"))))
+                }
+            }
+            Status::Skipped(reason) => case.push_str(&format!(
+                ">\n      <skipped message=\"{}\"/>\n    </testcase>", escape_xml(reason))),
+            Status::Failed(e) => {
+                // list every failed check on its own line, the error message otherwise
+                let failures: Vec<&str> = result.failed_checks()
+                    .filter_map(|c| c.message.as_ref().map(|m| m.as_str()))
+                    .collect();
+                let body = if failures.is_empty() { e.clone() } else { failures.join("\n") };
+                case.push_str(&format!(
+                    ">\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>",
+                    escape_xml(e), escape_xml(result.failed_assertion().unwrap_or("")), escape_xml(&body)))
+            }
+        }
+        self.cases.push(case);
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> io::Result<()> {
+        let total = summary.passed + summary.failed + summary.skipped;
+        let time = millis(summary.duration) / 1000.0;
+        writeln!(self.out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            self.out, "<testsuites tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+            total, summary.failed, summary.skipped, time)?;
+        writeln!(
+            self.out, "  <testsuite name=\"syscalls_test\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+            total, summary.failed, summary.skipped, time)?;
+        for case in &self.cases {
+            writeln!(self.out, "{}", case)?;
+        }
+        writeln!(self.out, "  </testsuite>")?;
+        writeln!(self.out, "</testsuites>")?;
+        self.out.flush()
+    }
+}
+
+// output - path of the report file, stdout is used if None
+pub fn new_reporter(format: Format, output: Option<&str>) -> Result<Box<dyn Reporter>, String> {
+    let out: Box<dyn Write> = match output {
+        Some(path) => match File::create(path) {
+            Ok(f) => Box::new(f),
+            Err(e) => return Err(format!("Couldn't create report file {}: {}", path, e)),
+        },
+        None => {
+            if format == Format::Junit {
+                return Err(String::from("JUnit report requires an output file (--output PATH)"));
+            }
+            Box::new(io::stdout())
+        }
+    };
+    Ok(match format {
+        Format::Text => Box::new(TextReporter { out }),
+        Format::Tap => Box::new(TapReporter { out, index: 0 }),
+        Format::Json => Box::new(JsonReporter { out }),
+        Format::Junit => Box::new(JunitReporter { out, cases: Vec::new() }),
+    })
+}
@@ -1,7 +1,8 @@
 use std::{ str, slice };
 use constants;
+use fixture::Fixture;
 
-pub fn test_args_get() -> Result<(), String> {
+pub fn test_args_get(_: &Fixture) -> Result<(), String> {
     unsafe {
         let (argc, argv_s) = match wasi::args_sizes_get() {
             Ok(a) => a,
@@ -1,6 +1,7 @@
 use constants;
+use fixture::Fixture;
 
-pub fn test_args_sizes_get() -> Result<(), String> {
+pub fn test_args_sizes_get(_: &Fixture) -> Result<(), String> {
     unsafe {
         let expected_size = constants::ARGV.iter().fold(0, |r, arg| r + arg.len());
         match wasi::args_sizes_get() {
@@ -0,0 +1,39 @@
+use assertions::Checks;
+use fixture::Fixture;
+use utils::raw_errno;
+
+pub const CLOCKS: &[(wasi::Clockid, &str)] = &[
+    (wasi::CLOCKID_REALTIME, "REALTIME"),
+    (wasi::CLOCKID_MONOTONIC, "MONOTONIC"),
+    (wasi::CLOCKID_PROCESS_CPUTIME_ID, "PROCESS_CPUTIME_ID"),
+    (wasi::CLOCKID_THREAD_CPUTIME_ID, "THREAD_CPUTIME_ID"),
+];
+pub const INVALID_CLOCK: u32 = 4;
+
+// anything coarser than a second can't be used for measuring time
+const MAX_RESOLUTION: wasi::Timestamp = 1_000_000_000;
+
+pub fn test_clock_res_get(_: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        for &(id, name) in CLOCKS {
+            // resolutions differ between runtimes, only whether the call succeeded is recorded
+            let call = call!("clock_res_get", id.raw());
+            let result = wasi::clock_res_get(id);
+            t.success(&call, result.map(|_| ()));
+            if let Ok(res) = result {
+                t.check(&call, res > 0, &format!("{} clock has zero resolution", name));
+                t.check(
+                    &call, res <= MAX_RESOLUTION,
+                    &format!("{} clock has resolution of {} ns", name, res));
+            }
+        }
+
+        let mut res: wasi::Timestamp = 0;
+        raw_errno(
+            &mut t, &call!("clock_res_get", INVALID_CLOCK),
+            wasi::wasi_snapshot_preview1::clock_res_get(INVALID_CLOCK as i32, &mut res as *mut _ as i32),
+            wasi::ERRNO_INVAL);
+    }
+    t.finish()
+}
@@ -1,18 +1,109 @@
+use assertions::Checks;
+use fixture::Fixture;
+use utils::raw_errno;
+
+use syscalls::clock_res_get::{CLOCKS, INVALID_CLOCK};
+
 const PRECISION: wasi::Timestamp = 128; // this is arbitrary and doesn't affect the test
+const PRECISIONS: &[wasi::Timestamp] = &[0, 1, PRECISION, 1_000_000_000, wasi::Timestamp::MAX];
+
+const SAMPLES: usize = 1000;
+
+// the realtime clock can be set back, only these are guaranteed not to decrease
+const MONOTONIC_CLOCKS: &[wasi::Clockid] = &[
+    wasi::CLOCKID_MONOTONIC,
+    wasi::CLOCKID_PROCESS_CPUTIME_ID,
+    wasi::CLOCKID_THREAD_CPUTIME_ID,
+];
+
+// clocks are compared over this interval, with an error of a few realtime clock ticks
+const INTERVAL: wasi::Timestamp = 50_000_000;
+const TOLERANCE: wasi::Timestamp = 10_000_000;
+
+// readings differ between runs, only whether the call succeeded is recorded
+unsafe fn time(t: &mut Checks, id: wasi::Clockid, precision: wasi::Timestamp) -> Option<wasi::Timestamp> {
+    let result = wasi::clock_time_get(id, precision);
+    t.success(&call!("clock_time_get", id.raw(), precision), result.map(|_| ()))?;
+    result.ok()
+}
+
+// Samples are only recorded as a single check, the first failure ends sampling
+unsafe fn expect_monotonic(t: &mut Checks, id: wasi::Clockid) {
+    let call = call!("clock_time_get", id.raw(), 0);
+    let mut last = 0;
+    for _ in 0..SAMPLES {
+        let now = match wasi::clock_time_get(id, 0) {
+            Ok(time) => time,
+            Err(e) => {
+                t.success(&call, Err::<(), _>(e));
+                return;
+            }
+        };
+        if now < last {
+            t.check(&call, false, &format!("clock went backwards from {} to {}", last, now));
+            return;
+        }
+        last = now;
+    }
+    t.check(&call, true, "");
+}
+
+unsafe fn test_precision(t: &mut Checks) {
+    // precision is only a hint, any value should give a reading
+    for &(id, _) in CLOCKS {
+        for &precision in PRECISIONS {
+            time(t, id, precision);
+        }
+    }
+}
+
+unsafe fn test_consistency(t: &mut Checks) {
+    let (real_start, mono_start) = match (
+        time(t, wasi::CLOCKID_REALTIME, 0), time(t, wasi::CLOCKID_MONOTONIC, 0)) {
+        (Some(real), Some(mono)) => (real, mono),
+        _ => return,
+    };
+
+    // busy wait so the test doesn't depend on poll_oneoff
+    let mut mono_end = mono_start;
+    while mono_end.saturating_sub(mono_start) < INTERVAL {
+        mono_end = match wasi::clock_time_get(wasi::CLOCKID_MONOTONIC, 0) {
+            Ok(time) => time,
+            Err(e) => {
+                t.success(&call!("clock_time_get", wasi::CLOCKID_MONOTONIC.raw(), 0), Err::<(), _>(e));
+                return;
+            }
+        };
+    }
+    let real_end = match time(t, wasi::CLOCKID_REALTIME, 0) {
+        Some(time) => time,
+        None => return,
+    };
+
+    let (mono_delta, real_delta) = (mono_end.saturating_sub(mono_start), real_end.saturating_sub(real_start));
+    let diff = if mono_delta > real_delta { mono_delta - real_delta } else { real_delta - mono_delta };
+    t.check(
+        &call!("clock_time_get", wasi::CLOCKID_REALTIME.raw(), 0), diff <= TOLERANCE,
+        &format!("realtime clock advanced {} ns while monotonic advanced {} ns", real_delta, mono_delta));
+}
 
-unsafe fn expect_success(id: wasi::Clockid, precision: wasi::Timestamp) -> Result<(), String> {
-    if let Err(e) = wasi::clock_time_get(id, precision) {
-        Err(format!("In clock_time_get({:?}, {}): {:?}", id, precision, e))
-    } else { Ok(()) }
+unsafe fn test_invalid(t: &mut Checks) {
+    let mut time: wasi::Timestamp = 0;
+    raw_errno(
+        t, &call!("clock_time_get", INVALID_CLOCK, 0),
+        wasi::wasi_snapshot_preview1::clock_time_get(INVALID_CLOCK as i32, 0, &mut time as *mut _ as i32),
+        wasi::ERRNO_INVAL);
 }
 
-pub fn test_clock_time_get() -> Result<(), String> {
+pub fn test_clock_time_get(_: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
     unsafe {
-        // Valid clockids should work
-        expect_success(wasi::CLOCKID_MONOTONIC, PRECISION)?;
-        expect_success(wasi::CLOCKID_REALTIME, PRECISION)?;
-        expect_success(wasi::CLOCKID_PROCESS_CPUTIME_ID, PRECISION)?;
-        expect_success(wasi::CLOCKID_THREAD_CPUTIME_ID, PRECISION)?;
+        test_precision(&mut t);
+        for &id in MONOTONIC_CLOCKS {
+            expect_monotonic(&mut t, id);
+        }
+        test_consistency(&mut t);
+        test_invalid(&mut t);
     }
-    Ok(())
+    t.finish()
 }
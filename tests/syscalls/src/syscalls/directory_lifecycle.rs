@@ -0,0 +1,175 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use syscalls::fd_readdir::wasi_ls;
+use utils::{
+    create_dir, create_file, expect_filetype, expect_missing, for_each_root, path, MOUNT_POINT, READDIR_BUF_LEN,
+};
+
+unsafe fn remove_dir(t: &mut Checks, fd: wasi::Fd, path: &str) -> bool {
+    t.success(&call!("path_remove_directory", fd, path), wasi::path_remove_directory(fd, path)).is_some()
+}
+
+unsafe fn unlink(t: &mut Checks, fd: wasi::Fd, path: &str) -> bool {
+    t.success(&call!("path_unlink_file", fd, path), wasi::path_unlink_file(fd, path)).is_some()
+}
+
+unsafe fn test_create(t: &mut Checks, fd: wasi::Fd, root: &str) {
+    let dir = path(root, "dir");
+    if create_dir(t, fd, &dir) {
+        expect_filetype(t, fd, &dir, wasi::FILETYPE_DIRECTORY);
+        t.error(
+            &call!("path_create_directory", fd, dir), wasi::path_create_directory(fd, &dir),
+            wasi::ERRNO_EXIST, "creating an existing directory succeeded");
+        remove_dir(t, fd, &dir);
+    }
+    let missing = path(root, "nonexistent/dir");
+    t.error(
+        &call!("path_create_directory", fd, missing), wasi::path_create_directory(fd, &missing),
+        wasi::ERRNO_NOENT, "creating a directory in nonexistent parent succeeded");
+}
+
+unsafe fn test_remove(t: &mut Checks, fd: wasi::Fd, root: &str) {
+    let (dir, file) = (path(root, "dir"), path(root, "dir/file"));
+    if !create_dir(t, fd, &dir) || !create_file(t, fd, &file, constants::SAMPLE_TEXT) {
+        return;
+    }
+
+    // populated directory stays in place
+    t.error(
+        &call!("path_remove_directory", fd, dir), wasi::path_remove_directory(fd, &dir),
+        wasi::ERRNO_NOTEMPTY, "removing a non-empty directory succeeded");
+    expect_filetype(t, fd, &file, wasi::FILETYPE_REGULAR_FILE);
+
+    // entry type has to match the syscall
+    t.error(
+        &call!("path_remove_directory", fd, file), wasi::path_remove_directory(fd, &file),
+        wasi::ERRNO_NOTDIR, "removing a file as a directory succeeded");
+    t.error(
+        &call!("path_unlink_file", fd, dir), wasi::path_unlink_file(fd, &dir),
+        wasi::ERRNO_ISDIR, "unlinking a directory succeeded");
+    expect_filetype(t, fd, &file, wasi::FILETYPE_REGULAR_FILE);
+    expect_filetype(t, fd, &dir, wasi::FILETYPE_DIRECTORY);
+
+    if unlink(t, fd, &file) {
+        expect_missing(t, fd, &file);
+    }
+    if remove_dir(t, fd, &dir) {
+        expect_missing(t, fd, &dir);
+    }
+
+    // removing twice should fail
+    t.error(
+        &call!("path_remove_directory", fd, dir), wasi::path_remove_directory(fd, &dir),
+        wasi::ERRNO_NOENT, "removing a nonexistent directory succeeded");
+    t.error(
+        &call!("path_unlink_file", fd, file), wasi::path_unlink_file(fd, &file),
+        wasi::ERRNO_NOENT, "unlinking a nonexistent file succeeded");
+}
+
+unsafe fn test_trailing_slash(t: &mut Checks, fd: wasi::Fd, root: &str) {
+    // trailing slash is allowed for directories
+    let dir = path(root, "slash_dir");
+    let dir_slash = format!("{}/", dir);
+    if create_dir(t, fd, &dir_slash) {
+        expect_filetype(t, fd, &dir, wasi::FILETYPE_DIRECTORY);
+        t.error(
+            &call!("path_unlink_file", fd, dir_slash), wasi::path_unlink_file(fd, &dir_slash),
+            wasi::ERRNO_ISDIR, "unlinking a directory with trailing slash succeeded");
+        if remove_dir(t, fd, &dir_slash) {
+            expect_missing(t, fd, &dir);
+        }
+    }
+
+    // and means the path has to be a directory
+    let file = path(root, "slash_file");
+    let file_slash = format!("{}/", file);
+    if create_file(t, fd, &file, constants::SAMPLE_TEXT) {
+        t.error(
+            &call!("path_unlink_file", fd, file_slash), wasi::path_unlink_file(fd, &file_slash),
+            wasi::ERRNO_NOTDIR, "unlinking a file with trailing slash succeeded");
+        t.error(
+            &call!("path_remove_directory", fd, file_slash), wasi::path_remove_directory(fd, &file_slash),
+            wasi::ERRNO_NOTDIR, "removing a file with trailing slash succeeded");
+        expect_filetype(t, fd, &file, wasi::FILETYPE_REGULAR_FILE);
+        unlink(t, fd, &file);
+    }
+}
+
+unsafe fn test_cwd(t: &mut Checks, fixture: &Fixture, root: &str) {
+    let original = match t.success(&call!("getcwd"), wasi_ext_lib::getcwd()) {
+        Some(cwd) => cwd,
+        None => return,
+    };
+    let dir = path(root, "cwd");
+    if !create_dir(t, fixture.fd, &dir) {
+        return;
+    }
+
+    // like on linux, the working directory can be removed
+    let abs_dir = fixture.abs_path(&dir);
+    if t.success(&call!("chdir", abs_dir), wasi_ext_lib::chdir(&abs_dir)).is_some() {
+        if remove_dir(t, fixture.fd, &dir) {
+            expect_missing(t, fixture.fd, &dir);
+        }
+        t.success(&call!("chdir", original), wasi_ext_lib::chdir(&original));
+    } else {
+        remove_dir(t, fixture.fd, &dir);
+    }
+}
+
+unsafe fn test_open_descriptor(t: &mut Checks, fd: wasi::Fd, root: &str) {
+    let (dir, file) = (path(root, "open_dir"), path(root, "open_dir/file"));
+    if !create_dir(t, fd, &dir) || !create_file(t, fd, &file, constants::SAMPLE_TEXT) {
+        return;
+    }
+    let call = call!("path_open", fd, 0, dir, wasi::OFLAGS_DIRECTORY);
+    let dir_fd = match t.success(&call, wasi::path_open(
+        fd, 0, &dir, wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(dir_fd) => dir_fd,
+        None => return,
+    };
+
+    // open descriptor doesn't keep the directory in place, it just becomes empty
+    if unlink(t, fd, &file) && remove_dir(t, fd, &dir) {
+        expect_missing(t, fd, &dir);
+        match wasi_ls(dir_fd, READDIR_BUF_LEN, 0, true) {
+            Ok((dirents, _)) => {
+                let names: Vec<&String> = dirents.keys().filter(|n| *n != "." && *n != "..").collect();
+                t.check(
+                    &call!("fd_readdir", dir_fd), names.is_empty(),
+                    &format!("removed directory lists entries {:?}", names));
+            }
+            Err(e) => {
+                t.check(&call!("fd_readdir", dir_fd), false, &e);
+            }
+        }
+    }
+    t.success(&call!("fd_close", dir_fd), wasi::fd_close(dir_fd));
+}
+
+pub fn test_directory_lifecycle(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        for_each_root(&mut t, fixture, |t, root| {
+            test_create(t, fixture.fd, root);
+            test_remove(t, fixture.fd, root);
+            test_trailing_slash(t, fixture.fd, root);
+            test_cwd(t, fixture, root);
+            test_open_descriptor(t, fixture.fd, root);
+
+            // mounted filesystem can't be removed, unlinking it fails like for any directory
+            if root == MOUNT_POINT {
+                t.error(
+                    &call!("path_remove_directory", fixture.fd, MOUNT_POINT),
+                    wasi::path_remove_directory(fixture.fd, MOUNT_POINT),
+                    wasi::ERRNO_BUSY, "removing a mount point succeeded");
+                t.error(
+                    &call!("path_unlink_file", fixture.fd, MOUNT_POINT),
+                    wasi::path_unlink_file(fixture.fd, MOUNT_POINT),
+                    wasi::ERRNO_ISDIR, "unlinking a mount point succeeded");
+            }
+        });
+    }
+    t.finish()
+}
@@ -1,4 +1,6 @@
-pub fn test_environ_get() -> Result<(), String> {
+use fixture::Fixture;
+
+pub fn test_environ_get(_: &Fixture) -> Result<(), String> {
     unsafe {
         let (envc, envv_s) = match wasi::environ_sizes_get() {
             Ok(e) => e,
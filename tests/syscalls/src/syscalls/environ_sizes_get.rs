@@ -1,4 +1,6 @@
-pub fn test_environ_sizes_get() -> Result<(), String> {
+use fixture::Fixture;
+
+pub fn test_environ_sizes_get(_: &Fixture) -> Result<(), String> {
     unsafe{
         // Currently, exporting variables is not supported in wasi
         // so we can only check if the invocation succeeds
@@ -1,9 +1,10 @@
 use constants;
+use fixture::Fixture;
 
-pub fn test_fd_close() -> Result<(), String> {
+pub fn test_fd_close(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         let result = wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
             constants::SAMPLE_DIR_FILENAME, wasi::OFLAGS_DIRECTORY,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0);
         let desc: wasi::Fd;
@@ -19,7 +20,7 @@ pub fn test_fd_close() -> Result<(), String> {
         }
 
         let result = wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
             constants::SAMPLE_TEXT_FILENAME, 0,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0);
         let desc: wasi::Fd;
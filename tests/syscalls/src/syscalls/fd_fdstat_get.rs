@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 unsafe fn expect_success(desc: wasi::Fd, expected: wasi::Fdstat) -> Result<(), String>{
     match wasi::fd_fdstat_get(desc) {
@@ -23,7 +24,7 @@ unsafe fn expect_success(desc: wasi::Fd, expected: wasi::Fdstat) -> Result<(), S
     }
 }
 
-pub fn test_fd_fdstat_get() -> Result<(), String>{
+pub fn test_fd_fdstat_get(fixture: &Fixture) -> Result<(), String>{
     unsafe {
         // check character devices
         expect_success(0, wasi::Fdstat{
@@ -63,7 +64,7 @@ pub fn test_fd_fdstat_get() -> Result<(), String>{
         let rights_inheriting = 321;
         let fdflags = 0;
         let desc = match wasi::path_open(
-            constants::PWD_DESC, dirflags, path,
+            fixture.fd, dirflags, path,
             oflags, rights_base, rights_inheriting, fdflags){
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -84,7 +85,7 @@ pub fn test_fd_fdstat_get() -> Result<(), String>{
         let oflags = 0;
         let fdflags = wasi::FDFLAGS_APPEND | wasi::FDFLAGS_SYNC | wasi::FDFLAGS_DSYNC;
         let desc = match wasi::path_open(
-            constants::PWD_DESC, dirflags, path,
+            fixture.fd, dirflags, path,
             oflags, rights_base, rights_inheriting, fdflags) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -102,7 +103,7 @@ pub fn test_fd_fdstat_get() -> Result<(), String>{
 
         // test expanded symbolic link
         let desc = match wasi::path_open(
-            constants::PWD_DESC, dirflags, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, dirflags, constants::SAMPLE_LINK_FILENAME,
             oflags, rights_base, rights_inheriting, fdflags) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -121,7 +122,7 @@ pub fn test_fd_fdstat_get() -> Result<(), String>{
         // test unexpanded symbolic link
         let dirflags = 0;
         let desc = match wasi::path_open(
-            constants::PWD_DESC, dirflags, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, dirflags, constants::SAMPLE_LINK_FILENAME,
             oflags, rights_base, rights_inheriting, fdflags) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -0,0 +1,190 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use utils::{
+    closed_fd, for_each_root, make_fifo, open_end, path, read, read_file, terminal_stat, write, write_file,
+    FIFO_READ_RIGHTS, FIFO_WRITE_RIGHTS,
+};
+
+const SYNC_FLAGS: &[wasi::Fdflags] = &[
+    wasi::FDFLAGS_DSYNC,
+    wasi::FDFLAGS_RSYNC,
+    wasi::FDFLAGS_SYNC,
+    wasi::FDFLAGS_APPEND | wasi::FDFLAGS_DSYNC | wasi::FDFLAGS_RSYNC | wasi::FDFLAGS_SYNC,
+];
+
+// fd_fdstat_get also reports kernel extensions of fdflags, only standard ones are compared
+const STD_FDFLAGS_MASK: wasi::Fdflags = 0x001f;
+
+const READ_BUF_LEN: usize = 256;
+
+const STDIN: wasi::Fd = 0;
+
+unsafe fn get_flags(fd: wasi::Fd) -> Result<wasi::Fdflags, wasi::Errno> {
+    wasi::fd_fdstat_get(fd).map(|stat| stat.fs_flags & STD_FDFLAGS_MASK)
+}
+
+// sets flags and makes sure fd_fdstat_get sees them
+unsafe fn set_flags(t: &mut Checks, fd: wasi::Fd, flags: wasi::Fdflags) -> bool {
+    if t.success(&call!("fd_fdstat_set_flags", fd, flags), wasi::fd_fdstat_set_flags(fd, flags)).is_none() {
+        return false;
+    }
+    t.output(&call!("fd_fdstat_get", fd), get_flags(fd), flags)
+}
+
+unsafe fn expect_write(t: &mut Checks, fd: wasi::Fd, data: &[u8], offset: usize) {
+    t.output(&call!("fd_write", fd, String::from_utf8_lossy(data)), write(fd, data), data.len());
+    t.output(&call!("fd_tell", fd), wasi::fd_tell(fd), offset as wasi::Filesize);
+}
+
+unsafe fn seek_start(t: &mut Checks, fd: wasi::Fd) -> bool {
+    t.success(
+        &call!("fd_seek", fd, 0, wasi::WHENCE_SET.raw()), wasi::fd_seek(fd, 0, wasi::WHENCE_SET)).is_some()
+}
+
+unsafe fn open(t: &mut Checks, fd: wasi::Fd, path: &str, fdflags: wasi::Fdflags) -> Option<wasi::Fd> {
+    t.success(
+        &call!("path_open", fd, 0, path, 0, fdflags),
+        wasi::path_open(fd, 0, path, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, fdflags))
+}
+
+unsafe fn test_append(t: &mut Checks, dir: wasi::Fd, root: &str) {
+    let file = path(root, "append");
+    if t.success(&call!("write_file", file), write_file(dir, &file, b"0123")).is_none() {
+        return;
+    }
+    let fd = match open(t, dir, &file, 0) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // appended data lands at the end wherever the cursor was moved
+    if set_flags(t, fd, wasi::FDFLAGS_APPEND) && seek_start(t, fd) {
+        expect_write(t, fd, b"ab", 6);
+        if seek_start(t, fd) {
+            expect_write(t, fd, b"cd", 8);
+        }
+    }
+
+    // without the flag writes go to the cursor again
+    if set_flags(t, fd, 0) && seek_start(t, fd) {
+        expect_write(t, fd, b"x", 1);
+    }
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    t.output(&call!("read_file", file), read_file(dir, &file), b"x123abcd".to_vec());
+
+    // flag given to path_open can be cleared too
+    let fd = match open(t, dir, &file, wasi::FDFLAGS_APPEND) {
+        Some(fd) => fd,
+        None => return,
+    };
+    t.output(&call!("fd_fdstat_get", fd), get_flags(fd), wasi::FDFLAGS_APPEND);
+    if seek_start(t, fd) {
+        expect_write(t, fd, b"ef", 10);
+    }
+    if set_flags(t, fd, 0) && seek_start(t, fd) {
+        expect_write(t, fd, b"y", 1);
+    }
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    t.output(&call!("read_file", file), read_file(dir, &file), b"y123abcdef".to_vec());
+}
+
+unsafe fn test_sync(t: &mut Checks, dir: wasi::Fd) {
+    let file = "sync";
+    if t.success(&call!("write_file", file), write_file(dir, file, b"")).is_none() {
+        return;
+    }
+    let fd = match open(t, dir, file, 0) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // synchronization flags don't change where data is written
+    let mut contents = Vec::new();
+    for &flags in SYNC_FLAGS {
+        if set_flags(t, fd, flags) {
+            expect_write(t, fd, b"sync", contents.len() + 4);
+            contents.extend_from_slice(b"sync");
+        }
+    }
+    set_flags(t, fd, 0);
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    t.output(&call!("read_file", file), read_file(dir, file), contents);
+}
+
+unsafe fn test_fifo(t: &mut Checks, fixture: &Fixture) {
+    let path = match make_fifo(t, fixture) {
+        Some(path) => path,
+        None => return,
+    };
+    let ends = (open_end(t, &path, FIFO_READ_RIGHTS), open_end(t, &path, FIFO_WRITE_RIGHTS));
+    if let (Some(read_fd), Some(write_fd)) = ends {
+        // empty FIFO doesn't block but there is nothing to read yet
+        if set_flags(t, read_fd, wasi::FDFLAGS_NONBLOCK) {
+            t.error(
+                &call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN),
+                wasi::ERRNO_AGAIN, "nonblocking read from an empty FIFO succeeded");
+            t.output(&call!("fd_write", write_fd, "data"), write(write_fd, b"data"), 4);
+            t.output(&call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN), b"data".to_vec());
+            t.error(
+                &call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN),
+                wasi::ERRNO_AGAIN, "nonblocking read from an empty FIFO succeeded");
+        }
+
+        // data written before the flag is cleared can be read with blocking reads
+        t.output(&call!("fd_write", write_fd, "more"), write(write_fd, b"more"), 4);
+        if set_flags(t, read_fd, 0) {
+            t.output(&call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN), b"more".to_vec());
+        }
+
+        // once writers are gone, EOF is reported instead of EAGAIN
+        t.success(&call!("fd_close", write_fd), wasi::fd_close(write_fd));
+        if set_flags(t, read_fd, wasi::FDFLAGS_NONBLOCK) {
+            t.output(&call!("fd_read", read_fd, READ_BUF_LEN), read(read_fd, READ_BUF_LEN), Vec::new());
+        }
+        t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+    } else {
+        for &fd in [ends.0, ends.1].iter().flatten() {
+            t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        }
+    }
+}
+
+unsafe fn test_stdin(t: &mut Checks) {
+    // only a terminal is expected to have nothing to read
+    let stat = match terminal_stat(t, STDIN) {
+        Some(stat) => stat,
+        None => return,
+    };
+
+    // the terminal descriptor is shared with the parent, its flags have to be restored
+    let flags = stat.fs_flags & STD_FDFLAGS_MASK;
+    if set_flags(t, STDIN, flags | wasi::FDFLAGS_NONBLOCK) {
+        t.error(
+            &call!("fd_read", STDIN, READ_BUF_LEN), read(STDIN, READ_BUF_LEN),
+            wasi::ERRNO_AGAIN, "nonblocking read from terminal without input succeeded");
+    }
+    set_flags(t, STDIN, flags);
+}
+
+unsafe fn test_bad_fd(t: &mut Checks, fixture: &Fixture) {
+    let fd = match closed_fd(t, fixture.fd, "sync") {
+        Some(fd) => fd,
+        None => return,
+    };
+    t.error(
+        &call!("fd_fdstat_set_flags", fd, wasi::FDFLAGS_APPEND), wasi::fd_fdstat_set_flags(fd, wasi::FDFLAGS_APPEND),
+        wasi::ERRNO_BADF, "setting flags of a closed descriptor succeeded");
+}
+
+pub fn test_fd_fdstat_set_flags(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        for_each_root(&mut t, fixture, |t, root| test_append(t, fixture.fd, root));
+        test_sync(&mut t, fixture.fd);
+        test_fifo(&mut t, fixture);
+        test_stdin(&mut t);
+        test_bad_fd(&mut t, fixture);
+    }
+    t.finish()
+}
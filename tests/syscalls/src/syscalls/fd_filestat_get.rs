@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 unsafe fn expect_success(desc: wasi::Fd, filetype_e: wasi::Filetype, size_e: u64) -> Result<(), String> {
     match wasi::fd_filestat_get(desc) {
@@ -16,7 +17,7 @@ unsafe fn expect_success(desc: wasi::Fd, filetype_e: wasi::Filetype, size_e: u64
     }
 }
 
-pub fn test_fd_filestat_get() -> Result<(), String> {
+pub fn test_fd_filestat_get(fixture: &Fixture) -> Result<(), String> {
     /* filestat struct in fact has more fields than we test here
      * all fields: (dev, ino, filetype, nlink, size, atim, mtim, ctim)
      * we only test filetype and size
@@ -33,7 +34,7 @@ pub fn test_fd_filestat_get() -> Result<(), String> {
 
         // check regular file
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()) }
@@ -46,7 +47,7 @@ pub fn test_fd_filestat_get() -> Result<(), String> {
 
         // check unexpanded symlink
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_LINK_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()) }
@@ -59,7 +60,7 @@ pub fn test_fd_filestat_get() -> Result<(), String> {
 
         // check expended symlink
         let desc = match wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_LINK_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()) }
@@ -0,0 +1,207 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use utils::{
+    closed_fd, for_each_root, make_fifo, open_at_cursor, open_end, path, raw_errno, read_file, terminal_stat,
+    write_file, CURSOR,
+};
+
+// mtim set before resizing, it should be replaced with the time of the change
+const OLD_MTIM: wasi::Timestamp = 123;
+
+const ADVICES: &[wasi::Advice] = &[
+    wasi::ADVICE_NORMAL,
+    wasi::ADVICE_SEQUENTIAL,
+    wasi::ADVICE_RANDOM,
+    wasi::ADVICE_WILLNEED,
+    wasi::ADVICE_DONTNEED,
+    wasi::ADVICE_NOREUSE,
+];
+const INVALID_ADVICE: i32 = 6;
+
+const FIFO_RIGHTS: wasi::Rights = wasi::RIGHTS_FD_READ
+    | wasi::RIGHTS_POLL_FD_READWRITE
+    | wasi::RIGHTS_FD_FILESTAT_SET_SIZE
+    | wasi::RIGHTS_FD_ALLOCATE
+    | wasi::RIGHTS_FD_SYNC
+    | wasi::RIGHTS_FD_DATASYNC;
+
+const STDIN: wasi::Fd = 0;
+const SYNC_RIGHTS: wasi::Rights = wasi::RIGHTS_FD_SYNC | wasi::RIGHTS_FD_DATASYNC;
+
+unsafe fn set_size(t: &mut Checks, fd: wasi::Fd, size: wasi::Filesize) -> bool {
+    t.success(&call!("fd_filestat_set_size", fd, size), wasi::fd_filestat_set_size(fd, size)).is_some()
+}
+
+unsafe fn allocate(t: &mut Checks, fd: wasi::Fd, offset: wasi::Filesize, len: wasi::Filesize) -> bool {
+    t.success(&call!("fd_allocate", fd, offset, len), wasi::fd_allocate(fd, offset, len)).is_some()
+}
+
+unsafe fn sync(t: &mut Checks, fd: wasi::Fd) {
+    t.success(&call!("fd_sync", fd), wasi::fd_sync(fd));
+    t.success(&call!("fd_datasync", fd), wasi::fd_datasync(fd));
+}
+
+// checks size reported by the descriptor, the contents seen through a new one and the cursor
+unsafe fn expect_contents(t: &mut Checks, dir: wasi::Fd, file: &str, fd: wasi::Fd, contents: &[u8]) {
+    let call = call!("fd_filestat_get", fd);
+    if let Some(stat) = t.success(&call, wasi::fd_filestat_get(fd)) {
+        t.equal(&call, "size", contents.len() as wasi::Filesize, stat.size);
+    }
+    t.output(&call!("read_file", file), read_file(dir, file), contents.to_vec());
+    t.output(&call!("fd_tell", fd), wasi::fd_tell(fd), CURSOR);
+}
+
+unsafe fn reset_mtim(t: &mut Checks, fd: wasi::Fd) -> bool {
+    t.success(
+        &call!("fd_filestat_set_times", fd, 0, OLD_MTIM, wasi::FSTFLAGS_MTIM),
+        wasi::fd_filestat_set_times(fd, 0, OLD_MTIM, wasi::FSTFLAGS_MTIM)).is_some()
+}
+
+unsafe fn expect_mtim_changed(t: &mut Checks, fd: wasi::Fd) {
+    let call = call!("fd_filestat_get", fd);
+    if let Some(stat) = t.success(&call, wasi::fd_filestat_get(fd)) {
+        t.check(&call, stat.mtim != OLD_MTIM, "resizing the file didn't update mtim");
+    }
+}
+
+unsafe fn test_file(t: &mut Checks, dir: wasi::Fd, root: &str) {
+    let file = path(root, "file");
+    let fd = match open_at_cursor(t, dir, &file) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // SAMPLE_TEXT is "sample text\n"
+    if reset_mtim(t, fd) && set_size(t, fd, 6) {
+        expect_contents(t, dir, &file, fd, b"sample");
+        expect_mtim_changed(t, fd);
+    }
+
+    // extended part is filled with zeros
+    if reset_mtim(t, fd) && set_size(t, fd, 10) {
+        expect_contents(t, dir, &file, fd, b"sample\0\0\0\0");
+        expect_mtim_changed(t, fd);
+    }
+
+    // fd_allocate grows the file to cover the range but never shrinks it
+    if allocate(t, fd, 8, 4) {
+        expect_contents(t, dir, &file, fd, b"sample\0\0\0\0\0\0");
+    }
+    if allocate(t, fd, 0, 2) {
+        expect_contents(t, dir, &file, fd, b"sample\0\0\0\0\0\0");
+    }
+    if set_size(t, fd, 0) {
+        expect_contents(t, dir, &file, fd, b"");
+    }
+
+    sync(t, fd);
+    for &advice in ADVICES {
+        t.success(
+            &call!("fd_advise", fd, 0, 0, advice.raw()), wasi::fd_advise(fd, 0, 0, advice));
+    }
+    raw_errno(
+        t, &call!("fd_advise", fd, 0, 0, INVALID_ADVICE),
+        wasi::wasi_snapshot_preview1::fd_advise(fd as i32, 0, 0, INVALID_ADVICE), wasi::ERRNO_INVAL);
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+unsafe fn test_read_only(t: &mut Checks, dir: wasi::Fd) {
+    let file = "read_only";
+    if t.success(&call!("write_file", file), write_file(dir, file, constants::SAMPLE_TEXT)).is_none() {
+        return;
+    }
+    let rights = wasi::RIGHTS_FD_READ | wasi::RIGHTS_FD_FILESTAT_GET;
+    let call = call!("path_open", dir, 0, file, 0, rights, 0, 0);
+    let fd = match t.success(&call, wasi::path_open(dir, 0, file, 0, rights, 0, 0)) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // descriptor without the rights can't change the size
+    t.error(
+        &call!("fd_filestat_set_size", fd, 0), wasi::fd_filestat_set_size(fd, 0),
+        wasi::ERRNO_NOTCAPABLE, "truncating through a read-only descriptor succeeded");
+    t.error(
+        &call!("fd_allocate", fd, 0, 100), wasi::fd_allocate(fd, 0, 100),
+        wasi::ERRNO_NOTCAPABLE, "allocating through a read-only descriptor succeeded");
+    t.output(&call!("read_file", file), read_file(dir, file), constants::SAMPLE_TEXT.to_vec());
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+unsafe fn test_directory(t: &mut Checks, fixture: &Fixture) {
+    t.error(
+        &call!("fd_filestat_set_size", fixture.fd, 0), wasi::fd_filestat_set_size(fixture.fd, 0),
+        wasi::ERRNO_ISDIR, "truncating a directory succeeded");
+    t.error(
+        &call!("fd_allocate", fixture.fd, 0, 100), wasi::fd_allocate(fixture.fd, 0, 100),
+        wasi::ERRNO_ISDIR, "allocating a directory succeeded");
+}
+
+// devices have no size and nothing to synchronize
+unsafe fn expect_size_errors(t: &mut Checks, fd: wasi::Fd) {
+    t.error(
+        &call!("fd_filestat_set_size", fd, 0), wasi::fd_filestat_set_size(fd, 0),
+        wasi::ERRNO_INVAL, "truncating a device succeeded");
+    t.error(
+        &call!("fd_allocate", fd, 0, 100), wasi::fd_allocate(fd, 0, 100),
+        wasi::ERRNO_INVAL, "allocating a device succeeded");
+}
+
+unsafe fn expect_sync_errors(t: &mut Checks, fd: wasi::Fd) {
+    t.error(&call!("fd_sync", fd), wasi::fd_sync(fd), wasi::ERRNO_INVAL, "fd_sync on a device succeeded");
+    t.error(&call!("fd_datasync", fd), wasi::fd_datasync(fd), wasi::ERRNO_INVAL, "fd_datasync on a device succeeded");
+}
+
+unsafe fn test_fifo(t: &mut Checks, fixture: &Fixture) {
+    let path = match make_fifo(t, fixture) {
+        Some(path) => path,
+        None => return,
+    };
+    if let Some(fd) = open_end(t, &path, FIFO_RIGHTS) {
+        expect_size_errors(t, fd);
+        expect_sync_errors(t, fd);
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    }
+}
+
+unsafe fn test_terminal(t: &mut Checks) {
+    // a file stdin is redirected to can be synchronized
+    match terminal_stat(t, STDIN) {
+        Some(stat) if stat.fs_rights_base & SYNC_RIGHTS == SYNC_RIGHTS => expect_sync_errors(t, STDIN),
+        _ => {}
+    }
+}
+
+unsafe fn test_bad_fd(t: &mut Checks, fixture: &Fixture) {
+    let fd = match closed_fd(t, fixture.fd, "read_only") {
+        Some(fd) => fd,
+        None => return,
+    };
+    t.error(
+        &call!("fd_filestat_set_size", fd, 0), wasi::fd_filestat_set_size(fd, 0),
+        wasi::ERRNO_BADF, "truncating a closed descriptor succeeded");
+    t.error(
+        &call!("fd_allocate", fd, 0, 1), wasi::fd_allocate(fd, 0, 1),
+        wasi::ERRNO_BADF, "allocating a closed descriptor succeeded");
+    t.error(&call!("fd_sync", fd), wasi::fd_sync(fd), wasi::ERRNO_BADF, "fd_sync on a closed descriptor succeeded");
+    t.error(
+        &call!("fd_datasync", fd), wasi::fd_datasync(fd),
+        wasi::ERRNO_BADF, "fd_datasync on a closed descriptor succeeded");
+    t.error(
+        &call!("fd_advise", fd, 0, 0, wasi::ADVICE_NORMAL.raw()), wasi::fd_advise(fd, 0, 0, wasi::ADVICE_NORMAL),
+        wasi::ERRNO_BADF, "fd_advise on a closed descriptor succeeded");
+}
+
+pub fn test_fd_filestat_set_size(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        for_each_root(&mut t, fixture, |t, root| test_file(t, fixture.fd, root));
+        test_read_only(&mut t, fixture.fd);
+        test_directory(&mut t, fixture);
+        test_fifo(&mut t, fixture);
+        test_terminal(&mut t);
+        test_bad_fd(&mut t, fixture);
+    }
+    t.finish()
+}
@@ -1,5 +1,6 @@
 use constants;
 use utils::fd_check_times;
+use fixture::Fixture;
 
 struct Test {
     root_fd: wasi::Fd,
@@ -85,7 +86,7 @@ impl Test {
         // attempt to set times without permissions should fail
         expect_error(
             self.no_permission_fd, 0u64, 0u64, wasi::FSTFLAGS_ATIM,
-            wasi::ERRNO_ACCES, "attempt to set times with no permissions succeeded")?;
+            wasi::ERRNO_NOTCAPABLE, "attempt to set times with no permissions succeeded")?;
         Ok(())
     }
 }
@@ -129,11 +130,11 @@ unsafe fn expect_error(
     }
 }
 
-pub fn test_fd_filestat_set_times() -> Result<(), String> {
+pub fn test_fd_filestat_set_times(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         let file_path = String::from("fd_set_times_file");
         let dir_path = String::from("fd_set_times_dir");
-        if let Err(e) = wasi::path_create_directory(constants::PWD_DESC, &dir_path) {
+        if let Err(e) = wasi::path_create_directory(fixture.fd, &dir_path) {
             return Err(e.to_string());
         }
 
@@ -141,14 +142,14 @@ pub fn test_fd_filestat_set_times() -> Result<(), String> {
         let mut dir_fds: [wasi::Fd; 2] = [0; 2];
         for i in 0..2 {
             dir_fds[i] = match wasi::path_open(
-                constants::PWD_DESC, 0, &dir_path, wasi::OFLAGS_DIRECTORY,
+                fixture.fd, 0, &dir_path, wasi::OFLAGS_DIRECTORY,
                 constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0
             ) {
                 Ok(desc) => desc,
                 Err(e) => { return Err(e.to_string()); }
             };
             file_fds[i] = match wasi::path_open(
-                constants::PWD_DESC, 0, &file_path, wasi::OFLAGS_CREAT,
+                fixture.fd, 0, &file_path, wasi::OFLAGS_CREAT,
                 constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0
             ) {
                 Ok(desc) => desc,
@@ -157,7 +158,7 @@ pub fn test_fd_filestat_set_times() -> Result<(), String> {
         }
 
         let no_permission_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, &dir_path, wasi::OFLAGS_DIRECTORY,
+            fixture.fd, 0, &dir_path, wasi::OFLAGS_DIRECTORY,
             constants::RIGHTS_ALL ^ wasi::RIGHTS_FD_FILESTAT_SET_TIMES,
             constants::RIGHTS_ALL ^ wasi::RIGHTS_FD_FILESTAT_SET_TIMES, 0
         ) {
@@ -165,7 +166,7 @@ pub fn test_fd_filestat_set_times() -> Result<(), String> {
             Err(e) => { return Err(e.to_string()); }
         };
         let test = Test {
-            root_fd: constants::PWD_DESC,
+            root_fd: fixture.fd,
             dir_fds,
             dir_path,
             file_fds,
@@ -0,0 +1,139 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use utils::{
+    closed_fd, for_each_root, make_fifo, open_at_cursor, open_end, path, read, read_file, terminal_stat,
+    CURSOR, FIFO_READ_RIGHTS, FIFO_WRITE_RIGHTS,
+};
+
+const READ_BUF_LEN: usize = 64;
+
+// reads into buffers of given lengths, returns what each of them got
+unsafe fn pread(fd: wasi::Fd, lens: &[usize], offset: wasi::Filesize) -> Result<Vec<Vec<u8>>, wasi::Errno> {
+    let mut bufs: Vec<Vec<u8>> = lens.iter().map(|&len| vec![0u8; len]).collect();
+    let iovs: Vec<wasi::Iovec> = bufs.iter_mut().map(|b| wasi::Iovec { buf: b.as_mut_ptr(), buf_len: b.len() }).collect();
+    let mut n = wasi::fd_pread(fd, &iovs, offset)?;
+    for buf in bufs.iter_mut() {
+        let len = n.min(buf.len());
+        buf.truncate(len);
+        n -= len;
+    }
+    Ok(bufs)
+}
+
+unsafe fn pwrite(fd: wasi::Fd, data: &[&[u8]], offset: wasi::Filesize) -> Result<usize, wasi::Errno> {
+    let iovs: Vec<wasi::Ciovec> = data.iter().map(|d| wasi::Ciovec { buf: d.as_ptr(), buf_len: d.len() }).collect();
+    wasi::fd_pwrite(fd, &iovs, offset)
+}
+
+fn chunks(chunks: &[&[u8]]) -> Vec<Vec<u8>> {
+    chunks.iter().map(|c| c.to_vec()).collect()
+}
+
+unsafe fn expect_pread(t: &mut Checks, fd: wasi::Fd, lens: &[usize], offset: wasi::Filesize, expected: &[&[u8]]) {
+    t.output(&call!("fd_pread", fd, lens, offset), pread(fd, lens, offset), chunks(expected));
+    t.output(&call!("fd_tell", fd), wasi::fd_tell(fd), CURSOR);
+}
+
+unsafe fn expect_pwrite(t: &mut Checks, fd: wasi::Fd, data: &[&[u8]], offset: wasi::Filesize) {
+    let call = call!("fd_pwrite", fd, data.iter().map(|d| String::from_utf8_lossy(d)).collect::<Vec<_>>(), offset);
+    t.output(&call, pwrite(fd, data, offset), data.iter().map(|d| d.len()).sum());
+    t.output(&call!("fd_tell", fd), wasi::fd_tell(fd), CURSOR);
+}
+
+unsafe fn test_file(t: &mut Checks, dir: wasi::Fd, root: &str) {
+    let file = path(root, "file");
+    let fd = match open_at_cursor(t, dir, &file) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // SAMPLE_TEXT is "sample text\n"
+    expect_pread(t, fd, &[4], 2, &[b"mple"]);
+    expect_pread(t, fd, &[READ_BUF_LEN], 0, &[constants::SAMPLE_TEXT]);
+    expect_pread(t, fd, &[3, 4, READ_BUF_LEN], 1, &[b"amp", b"le t", b"ext\n"]);
+    expect_pread(t, fd, &[2, 0, 2], 7, &[b"te", b"", b"xt"]);
+    expect_pread(t, fd, &[READ_BUF_LEN], constants::SAMPLE_TEXT_LEN as wasi::Filesize, &[b""]);
+    expect_pread(t, fd, &[READ_BUF_LEN], 100, &[b""]);
+
+    // gathered buffers are written one after another
+    expect_pwrite(t, fd, &[b"AB", b"", b"CD"], 0);
+    expect_pread(t, fd, &[READ_BUF_LEN], 0, &[b"ABCDle text\n"]);
+
+    // writing past the end leaves a gap of zeros
+    expect_pwrite(t, fd, &[b"Z"], 15);
+    let expected: &[u8] = b"ABCDle text\n\0\0\0Z";
+    let call = call!("fd_filestat_get", fd);
+    if let Some(stat) = t.success(&call, wasi::fd_filestat_get(fd)) {
+        t.equal(&call, "size", expected.len() as wasi::Filesize, stat.size);
+    }
+    expect_pread(t, fd, &[READ_BUF_LEN], 0, &[expected]);
+
+    // sequential reads continue from the untouched cursor
+    t.output(&call!("fd_read", fd, READ_BUF_LEN), read(fd, READ_BUF_LEN), expected[CURSOR as usize..].to_vec());
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+    t.output(&call!("read_file", file), read_file(dir, &file), expected.to_vec());
+}
+
+unsafe fn expect_spipe(t: &mut Checks, fd: wasi::Fd, write: bool) {
+    if write {
+        t.error(
+            &call!("fd_pwrite", fd, "data", 0), pwrite(fd, &[b"data"], 0),
+            wasi::ERRNO_SPIPE, "fd_pwrite on unseekable descriptor succeeded");
+    } else {
+        t.error(
+            &call!("fd_pread", fd, [READ_BUF_LEN], 0), pread(fd, &[READ_BUF_LEN], 0),
+            wasi::ERRNO_SPIPE, "fd_pread on unseekable descriptor succeeded");
+    }
+}
+
+unsafe fn test_fifo(t: &mut Checks, fixture: &Fixture) {
+    let path = match make_fifo(t, fixture) {
+        Some(path) => path,
+        None => return,
+    };
+    for &(rights, write) in [(FIFO_READ_RIGHTS, false), (FIFO_WRITE_RIGHTS, true)].iter() {
+        if let Some(fd) = open_end(t, &path, rights) {
+            expect_spipe(t, fd, write);
+            t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        }
+    }
+}
+
+unsafe fn test_terminal(t: &mut Checks) {
+    // files standard streams are redirected to are seekable
+    for &(fd, write) in [(0, false), (1, true)].iter() {
+        if terminal_stat(t, fd).is_some() {
+            expect_spipe(t, fd, write);
+        }
+    }
+}
+
+unsafe fn test_errors(t: &mut Checks, fixture: &Fixture) {
+    t.error(
+        &call!("fd_pread", fixture.fd, [READ_BUF_LEN], 0), pread(fixture.fd, &[READ_BUF_LEN], 0),
+        wasi::ERRNO_ISDIR, "fd_pread on a directory succeeded");
+    t.error(
+        &call!("fd_pwrite", fixture.fd, "data", 0), pwrite(fixture.fd, &[b"data"], 0),
+        wasi::ERRNO_ISDIR, "fd_pwrite on a directory succeeded");
+
+    if let Some(fd) = closed_fd(t, fixture.fd, "file") {
+        t.error(
+            &call!("fd_pread", fd, [READ_BUF_LEN], 0), pread(fd, &[READ_BUF_LEN], 0),
+            wasi::ERRNO_BADF, "fd_pread on a closed descriptor succeeded");
+        t.error(
+            &call!("fd_pwrite", fd, "data", 0), pwrite(fd, &[b"data"], 0),
+            wasi::ERRNO_BADF, "fd_pwrite on a closed descriptor succeeded");
+    }
+}
+
+pub fn test_fd_pread_pwrite(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        for_each_root(&mut t, fixture, |t, root| test_file(t, fixture.fd, root));
+        test_fifo(&mut t, fixture);
+        test_terminal(&mut t);
+        test_errors(&mut t, fixture);
+    }
+    t.finish()
+}
@@ -1,6 +1,7 @@
 use constants;
+use fixture::Fixture;
 
-pub fn test_fd_prestat_dir_name() -> Result<(), String> {
+pub fn test_fd_prestat_dir_name(_: &Fixture) -> Result<(), String> {
     unsafe {
         let prestat_desc = match wasi::fd_prestat_get(constants::PWD_DESC) {
             Ok(p) => p,
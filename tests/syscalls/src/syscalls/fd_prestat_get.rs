@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 unsafe fn expect_success(desc: wasi::Fd, name_len_ex: usize, tag_ex: u8) -> Result<(), String> {
     match wasi::fd_prestat_get(desc) {
@@ -34,7 +35,7 @@ unsafe fn expect_error(desc: wasi::Fd, errno: wasi::Errno, msg: &str) -> Result<
     }
 }
 
-pub fn test_fd_prestat_get() -> Result<(), String> {
+pub fn test_fd_prestat_get(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         // check character devices
         expect_error(0, wasi::ERRNO_BADF, "Character device cannot be a preopened directory")?;
@@ -46,7 +47,7 @@ pub fn test_fd_prestat_get() -> Result<(), String> {
 
         // check non-preopened directory
         let new_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_DIR_FILENAME,
             wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(fd) => { fd },
             Err(e) => { return Err(e.to_string()); }
@@ -59,7 +60,7 @@ pub fn test_fd_prestat_get() -> Result<(), String> {
 
         // check non-preopened file
         let new_fd = match wasi::path_open(
-            constants::PWD_DESC, 0,
+            fixture.fd, 0,
             constants::SAMPLE_TEXT_FILENAME, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(fd) => { fd },
             Err(e) => { return Err(e.to_string()); }
@@ -2,6 +2,7 @@ use std::convert::TryInto;
 use std::collections::HashMap;
 use std::mem::size_of;
 use constants;
+use fixture::Fixture;
 
 #[derive(Debug)]
 pub struct Dirent {
@@ -161,7 +162,7 @@ impl Test {
         }
         Ok(())
     }
-    unsafe fn run_tests(&self) -> Result<(), String> {
+    unsafe fn run_tests(&self, root_fd: wasi::Fd) -> Result<(), String> {
         // check if test directory contents match for different buffer sizes
         expect_success(self.dir_fd, 128, 0, true, constants::N_DIRENTRIES as usize, Some(&self.dirents))?;
         expect_success(self.dir_fd, 256, 0, true, constants::N_DIRENTRIES as usize, Some(&self.dirents))?;
@@ -212,7 +213,7 @@ impl Test {
         expect_success(self.dir_elink_fd, 128, 0, true, constants::N_DIRENTRIES as usize, Some(&self.dirents))?;
 
         let dummy_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+            root_fd, 0, constants::SAMPLE_DIR_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => {
                 if let Err(e) = wasi::fd_close(d) { return Err(e.to_string()) }
@@ -230,36 +231,36 @@ impl Test {
     }
 }
 
-pub fn test_fd_readdir() -> Result<(), String> {
+pub fn test_fd_readdir(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         let test = Test {
             dir_fd: match wasi::path_open(
-                constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+                fixture.fd, 0, constants::SAMPLE_DIR_FILENAME,
                 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
                 Ok(d) => d,
                 Err(e) => { return Err(e.to_string()) }
             },
             text_fd: match wasi::path_open(
-                constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
+                fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME,
                 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
                 Ok(d) => d,
                 Err(e) => { return Err(e.to_string()) }
             },
             elink_fd: match wasi::path_open(
-                constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
+                fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
                 constants::SAMPLE_LINK_FILENAME, 0, constants::RIGHTS_ALL,
                 constants::RIGHTS_ALL, 0) {
                 Ok(d) => d,
                 Err(e) => { return Err(e.to_string()) }
             },
             ulink_fd: match wasi::path_open(
-                constants::PWD_DESC, 0, constants::SAMPLE_LINK_FILENAME,
+                fixture.fd, 0, constants::SAMPLE_LINK_FILENAME,
                 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
                 Ok(d) => d,
                 Err(e) => { return Err(e.to_string()) }
             },
             dir_elink_fd: match wasi::path_open(
-                constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
+                fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
                 constants::SAMPLE_DIR_LINK_FILENAME, 0, constants::RIGHTS_ALL,
                 constants::RIGHTS_ALL, 0) {
                 Ok(d) => d,
@@ -277,7 +278,7 @@ pub fn test_fd_readdir() -> Result<(), String> {
                     })
                 }).collect(),
         };
-        let result = test.run_tests();
+        let result = test.run_tests(fixture.fd);
         test.tear_down()?;
         result?;
     }
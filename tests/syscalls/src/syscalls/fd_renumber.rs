@@ -0,0 +1,219 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use utils::{closed_fd, expect_fd_filetype, read};
+
+// fd_renumber behaves like dup2 in jswasi: the source descriptor stays open and both numbers
+// share the same open file, including its offset.
+
+const STDIN: wasi::Fd = 0;
+
+unsafe fn open(t: &mut Checks, fixture: &Fixture, path: &str, oflags: wasi::Oflags) -> Option<wasi::Fd> {
+    t.success(
+        &call!("path_open", fixture.fd, 0, path, oflags),
+        wasi::path_open(fixture.fd, 0, path, oflags, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0))
+}
+
+// opens both paths, or neither if one of them fails
+unsafe fn open_pair(
+    t: &mut Checks, fixture: &Fixture, first: (&str, wasi::Oflags), second: (&str, wasi::Oflags),
+) -> Option<(wasi::Fd, wasi::Fd)> {
+    match (open(t, fixture, first.0, first.1), open(t, fixture, second.0, second.1)) {
+        (Some(first), Some(second)) => Some((first, second)),
+        (first, second) => {
+            for fd in first.iter().chain(second.iter()) {
+                close(t, *fd);
+            }
+            None
+        }
+    }
+}
+
+unsafe fn renumber(t: &mut Checks, fd: wasi::Fd, to: wasi::Fd) -> bool {
+    t.success(&call!("fd_renumber", fd, to), wasi::fd_renumber(fd, to)).is_some()
+}
+
+unsafe fn close(t: &mut Checks, fd: wasi::Fd) {
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+// Prestat has no Debug implementation, only the tag and name length are checked
+unsafe fn prestat(fd: wasi::Fd) -> Result<(u8, usize), wasi::Errno> {
+    wasi::fd_prestat_get(fd).map(|p| (p.tag, p.u.dir.pr_name_len))
+}
+
+unsafe fn expect_closed(t: &mut Checks, fd: wasi::Fd) {
+    t.error(
+        &call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd),
+        wasi::ERRNO_BADF, "descriptor is open");
+}
+
+unsafe fn expect_offset(t: &mut Checks, fd: wasi::Fd, offset: wasi::Filesize) {
+    t.output(&call!("fd_tell", fd), wasi::fd_tell(fd), offset);
+}
+
+// closed descriptor is taken while fd is open so the two numbers differ
+unsafe fn open_with_closed(t: &mut Checks, fixture: &Fixture) -> Option<(wasi::Fd, wasi::Fd)> {
+    let fd = open(t, fixture, constants::SAMPLE_TEXT_FILENAME, 0)?;
+    match closed_fd(t, fixture.fd, constants::SAMPLE_TEXT_FILENAME) {
+        Some(closed) => Some((fd, closed)),
+        None => {
+            close(t, fd);
+            None
+        }
+    }
+}
+
+unsafe fn test_closed_target(t: &mut Checks, fixture: &Fixture) {
+    let (fd, closed) = match open_with_closed(t, fixture) {
+        Some(fds) => fds,
+        None => return,
+    };
+    if renumber(t, fd, closed) {
+        // both numbers refer to one open file and move the same offset
+        t.output(&call!("fd_read", fd, 4), read(fd, 4), constants::SAMPLE_TEXT[..4].to_vec());
+        expect_offset(t, closed, 4);
+        t.output(
+            &call!("fd_read", closed, constants::SAMPLE_TEXT_LEN), read(closed, constants::SAMPLE_TEXT_LEN),
+            constants::SAMPLE_TEXT[4..].to_vec());
+        expect_offset(t, fd, constants::SAMPLE_TEXT_LEN as wasi::Filesize);
+        t.output(
+            &call!("fd_seek", closed, 1, wasi::WHENCE_SET.raw()), wasi::fd_seek(closed, 1, wasi::WHENCE_SET), 1);
+        expect_offset(t, fd, 1);
+
+        // closing one number leaves the other usable
+        close(t, closed);
+        expect_closed(t, closed);
+        expect_offset(t, fd, 1);
+    }
+    close(t, fd);
+}
+
+unsafe fn test_open_target(t: &mut Checks, fixture: &Fixture) {
+    let (file_fd, dir_fd) = match open_pair(
+        t, fixture, (constants::SAMPLE_TEXT_FILENAME, 0), (constants::SAMPLE_DIR_FILENAME, wasi::OFLAGS_DIRECTORY)) {
+        Some(fds) => fds,
+        None => return,
+    };
+
+    // previous descriptor of the target is closed and replaced
+    if renumber(t, file_fd, dir_fd) {
+        expect_fd_filetype(t, dir_fd, wasi::FILETYPE_REGULAR_FILE);
+        t.output(
+            &call!("fd_read", dir_fd, constants::SAMPLE_TEXT_LEN), read(dir_fd, constants::SAMPLE_TEXT_LEN),
+            constants::SAMPLE_TEXT.to_vec());
+    }
+    close(t, dir_fd);
+    close(t, file_fd);
+}
+
+unsafe fn test_self(t: &mut Checks, fixture: &Fixture) {
+    let fd = match open(t, fixture, constants::SAMPLE_TEXT_FILENAME, 0) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // renumbering onto itself is a no-op and mustn't close the descriptor
+    if renumber(t, fd, fd) {
+        expect_fd_filetype(t, fd, wasi::FILETYPE_REGULAR_FILE);
+        t.output(
+            &call!("fd_read", fd, constants::SAMPLE_TEXT_LEN), read(fd, constants::SAMPLE_TEXT_LEN),
+            constants::SAMPLE_TEXT.to_vec());
+    }
+    close(t, fd);
+    t.error(
+        &call!("fd_renumber", fd, fd), wasi::fd_renumber(fd, fd),
+        wasi::ERRNO_BADF, "renumbering closed fd onto itself succeeded");
+}
+
+unsafe fn test_bad_source(t: &mut Checks, fixture: &Fixture) {
+    let (fd, closed) = match open_with_closed(t, fixture) {
+        Some(fds) => fds,
+        None => return,
+    };
+
+    // failed renumber leaves the target as it was, open or closed
+    t.error(
+        &call!("fd_renumber", closed, fd), wasi::fd_renumber(closed, fd),
+        wasi::ERRNO_BADF, "renumbering closed fd onto open fd succeeded");
+    expect_fd_filetype(t, fd, wasi::FILETYPE_REGULAR_FILE);
+    close(t, fd);
+    t.error(
+        &call!("fd_renumber", closed, fd), wasi::fd_renumber(closed, fd),
+        wasi::ERRNO_BADF, "renumbering closed fd onto closed fd succeeded");
+    expect_closed(t, fd);
+}
+
+unsafe fn test_stdio(t: &mut Checks, fixture: &Fixture) {
+    let (saved, fd) = match open_pair(
+        t, fixture, (constants::SAMPLE_DIR_FILENAME, wasi::OFLAGS_DIRECTORY), (constants::SAMPLE_TEXT_FILENAME, 0)) {
+        Some(fds) => fds,
+        None => return,
+    };
+
+    // keep stdin in a spare descriptor so it can be put back
+    if !renumber(t, STDIN, saved) {
+        close(t, saved);
+        close(t, fd);
+        return;
+    }
+    expect_fd_filetype(t, saved, wasi::FILETYPE_CHARACTER_DEVICE);
+    if renumber(t, fd, STDIN) {
+        expect_fd_filetype(t, STDIN, wasi::FILETYPE_REGULAR_FILE);
+        t.output(
+            &call!("fd_read", STDIN, constants::SAMPLE_TEXT_LEN), read(STDIN, constants::SAMPLE_TEXT_LEN),
+            constants::SAMPLE_TEXT.to_vec());
+    }
+    if renumber(t, saved, STDIN) {
+        expect_fd_filetype(t, STDIN, wasi::FILETYPE_CHARACTER_DEVICE);
+    }
+    close(t, saved);
+    close(t, fd);
+}
+
+unsafe fn test_preopen(t: &mut Checks, fixture: &Fixture) {
+    let (fd, dir_fd) = match open_pair(
+        t, fixture, (constants::SAMPLE_TEXT_FILENAME, 0), (constants::SAMPLE_DIR_FILENAME, wasi::OFLAGS_DIRECTORY)) {
+        Some(fds) => fds,
+        None => return,
+    };
+
+    // preopened directory keeps its status under the new number
+    if renumber(t, constants::PWD_DESC, fd) {
+        if let Some((tag, name_len)) = t.success(&call!("fd_prestat_get", fd), prestat(fd)) {
+            t.equal(&call!("fd_prestat_get", fd), "tag", wasi::PREOPENTYPE_DIR.raw(), tag);
+            let mut name = vec![0u8; name_len];
+            t.output(
+                &call!("fd_prestat_dir_name", fd, name.len()),
+                wasi::fd_prestat_dir_name(fd, name.as_mut_ptr(), name.len()).map(|_| name.clone()),
+                b"/".to_vec());
+        }
+    }
+    t.success(&call!("fd_prestat_get", constants::PWD_DESC), prestat(constants::PWD_DESC));
+
+    // and loses it once a regular directory takes its place
+    if renumber(t, dir_fd, fd) {
+        t.error(
+            &call!("fd_prestat_get", fd), prestat(fd),
+            wasi::ERRNO_BADF, "renumbered directory became preopened");
+        let mut name = vec![0u8; 1];
+        t.error(
+            &call!("fd_prestat_dir_name", fd, 1), wasi::fd_prestat_dir_name(fd, name.as_mut_ptr(), 1),
+            wasi::ERRNO_BADF, "renumbered directory became preopened");
+    }
+    close(t, fd);
+    close(t, dir_fd);
+}
+
+pub fn test_fd_renumber(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        test_closed_target(&mut t, fixture);
+        test_open_target(&mut t, fixture);
+        test_self(&mut t, fixture);
+        test_bad_source(&mut t, fixture);
+        test_stdio(&mut t, fixture);
+        test_preopen(&mut t, fixture);
+    }
+    t.finish()
+}
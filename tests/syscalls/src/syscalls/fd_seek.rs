@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 unsafe fn expect_success(
     desc: wasi::Fd,
@@ -42,10 +43,10 @@ unsafe fn expect_error(
     }
 }
 
-pub fn test_fd_seek() -> Result<(), String> {
+pub fn test_fd_seek(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()) },
@@ -53,13 +54,13 @@ pub fn test_fd_seek() -> Result<(), String> {
 
         // character devices should not have fd_seek access
         expect_error(
-            0, 0, wasi::WHENCE_SET, wasi::ERRNO_ACCES,
+            0, 0, wasi::WHENCE_SET, wasi::ERRNO_NOTCAPABLE,
             "character devices should not have seek rights")?;
         expect_error(
-            1, 0, wasi::WHENCE_SET, wasi::ERRNO_ACCES,
+            1, 0, wasi::WHENCE_SET, wasi::ERRNO_NOTCAPABLE,
             "character devices should not have seek rights")?;
         expect_error(
-            2, 0, wasi::WHENCE_SET, wasi::ERRNO_ACCES,
+            2, 0, wasi::WHENCE_SET, wasi::ERRNO_NOTCAPABLE,
             "character devices should not have seek rights")?;
 
         // seeking forward from start should work
@@ -105,12 +106,12 @@ pub fn test_fd_seek() -> Result<(), String> {
 
         // seeking a directory should fail
         expect_error(
-            constants::PWD_DESC, 0, wasi::WHENCE_SET, wasi::ERRNO_BADF,
+            fixture.fd, 0, wasi::WHENCE_SET, wasi::ERRNO_BADF,
             "attempt to seek a directory succeeded")?;
 
         // seeking unexpanded symlink should work
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_LINK_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()) },
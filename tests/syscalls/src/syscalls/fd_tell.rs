@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 unsafe fn expect_success(desc: wasi::Fd, expected: wasi::Filesize, close: bool) -> Result<(), String> {
     // close - close the descriptor in case the test fails
@@ -46,19 +47,19 @@ unsafe fn expect_error(desc: wasi::Fd, errno: wasi::Errno, msg: &str, close: boo
     }
 }
 
-pub fn test_fd_tell() -> Result<(), String> {
+pub fn test_fd_tell(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         // attempt to fd_tell a directory should fail
-        expect_error(constants::PWD_DESC, wasi::ERRNO_BADF, "attempt to fd_tell a directory succeeded", false)?;
+        expect_error(fixture.fd, wasi::ERRNO_BADF, "attempt to fd_tell a directory succeeded", false)?;
 
         // character devices should not have fd_tell rights
-        expect_error(0, wasi::ERRNO_ACCES, "attempt to fd_tell stdin succeeded", false)?;
-        expect_error(1, wasi::ERRNO_ACCES, "attempt to fd_tell stdout succeeded", false)?;
-        expect_error(2, wasi::ERRNO_ACCES, "attempt to fd_tell stderr succeeded", false)?;
+        expect_error(0, wasi::ERRNO_NOTCAPABLE, "attempt to fd_tell stdin succeeded", false)?;
+        expect_error(1, wasi::ERRNO_NOTCAPABLE, "attempt to fd_tell stdout succeeded", false)?;
+        expect_error(2, wasi::ERRNO_NOTCAPABLE, "attempt to fd_tell stderr succeeded", false)?;
 
         // fd_tell should work on regular file
         let desc = match wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_TEXT_FILENAME,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_TEXT_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0){
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -80,20 +81,20 @@ pub fn test_fd_tell() -> Result<(), String> {
 
         // fd tell should fail on a regular file without access
         let desc = match wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_TEXT_FILENAME,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_TEXT_FILENAME,
             0, constants::RIGHTS_ALL ^ wasi::RIGHTS_FD_TELL,
             constants::RIGHTS_ALL ^ wasi::RIGHTS_FD_TELL, 0){
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
         };
-        expect_error(desc, wasi::ERRNO_ACCES, "attempt to fd_tell without permission succeeded", true)?;
+        expect_error(desc, wasi::ERRNO_NOTCAPABLE, "attempt to fd_tell without permission succeeded", true)?;
         if let Err(e) = wasi::fd_close(desc) {
             return Err(e.to_string());
         }
 
         // attempt to fd_tell unexpanded symlink should succeed
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_LINK_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0){
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 const TEMP_FILENAME: &str = "write_file";
 const TEMP_SYMLINK: &str = "write_symlink";
@@ -70,11 +71,11 @@ unsafe fn verify_fd_read(
     }
 }
 
-pub fn test_fd_write() -> Result<(), String> {
+pub fn test_fd_write(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         // attempt to write without write permission should fail
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME, 0,
+            fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME, 0,
             constants::RIGHTS_ALL ^ wasi::RIGHTS_FD_WRITE, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); },
@@ -83,7 +84,7 @@ pub fn test_fd_write() -> Result<(), String> {
         let buf = "something".as_bytes();
 
         let result = expect_error(
-            desc, buf, wasi::ERRNO_ACCES,
+            desc, buf, wasi::ERRNO_NOTCAPABLE,
             "attempt to write without write permission succeeded");
         if let Err(e) = wasi::fd_close(desc){
             return Err(e.to_string());
@@ -97,7 +98,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // writing single buffer to a regular file should succeed
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, TEMP_FILENAME, wasi::OFLAGS_CREAT,
+            fixture.fd, 0, TEMP_FILENAME, wasi::OFLAGS_CREAT,
             constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -107,7 +108,7 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
             return Err(e);
@@ -115,7 +116,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // written buffer should be read correctly
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, TEMP_FILENAME, 0,
+            fixture.fd, 0, TEMP_FILENAME, 0,
             constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -125,20 +126,20 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
             return Err(e);
         }
 
         let buf = "symlink write".as_bytes();
-        if let Err(e) = wasi::path_symlink(TEMP_FILENAME, constants::PWD_DESC, TEMP_SYMLINK) {
+        if let Err(e) = wasi::path_symlink(TEMP_FILENAME, fixture.fd, TEMP_SYMLINK) {
             return Err(e.to_string());
         }
 
         // writing to unexpanded symlink should not succeed
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, TEMP_SYMLINK, 0,
+            fixture.fd, 0, TEMP_SYMLINK, 0,
             constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -150,10 +151,10 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
@@ -161,7 +162,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // writing to expanded symlink should succeed
         let desc = match wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, TEMP_SYMLINK,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, TEMP_SYMLINK,
             wasi::OFLAGS_TRUNC, constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -171,10 +172,10 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
@@ -182,7 +183,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // check if written buffer can be read correctly
         let desc = match wasi::path_open(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, TEMP_FILENAME, 0,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, TEMP_FILENAME, 0,
             constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -192,10 +193,10 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
@@ -203,7 +204,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // attempt to write to a directory should not succeed
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME, wasi::OFLAGS_DIRECTORY,
+            fixture.fd, 0, constants::SAMPLE_DIR_FILENAME, wasi::OFLAGS_DIRECTORY,
             constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -213,22 +214,22 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
         }
 
-        // we check for ERRNO_ACCESS because by default, stdin doesn't have write rights
+        // we check for ERRNO_NOTCAPABLE because by default, stdin doesn't have write rights
         // attempt to write to stdin should fail
-        if let Err(e) = expect_error(0, buf, wasi::ERRNO_ACCES, "attempt to write to stdin succeeded") {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+        if let Err(e) = expect_error(0, buf, wasi::ERRNO_NOTCAPABLE, "attempt to write to stdin succeeded") {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
@@ -236,7 +237,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // writing multiple buffers should succeed
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, TEMP_FILENAME,
+            fixture.fd, 0, TEMP_FILENAME,
             wasi::OFLAGS_TRUNC, constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -253,10 +254,10 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
@@ -264,7 +265,7 @@ pub fn test_fd_write() -> Result<(), String> {
 
         // check if written buffer can be read correctly
         let desc = match wasi::path_open(
-            constants::PWD_DESC, 0, TEMP_FILENAME, 0,
+            fixture.fd, 0, TEMP_FILENAME, 0,
             constants::RIGHTS_ALL, 0, 0) {
             Ok(d) => d,
             Err(e) => { return Err(e.to_string()); }
@@ -274,19 +275,19 @@ pub fn test_fd_write() -> Result<(), String> {
             return Err(e.to_string());
         }
         if let Err(e) = result {
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
                 return Err(e.to_string());
             }
-            if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+            if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
                 return Err(e.to_string());
             }
             return Err(e);
         }
 
-        if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_FILENAME) {
+        if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_FILENAME) {
             return Err(e.to_string());
         }
-        if let Err(e) = wasi::path_unlink_file(constants::PWD_DESC, TEMP_SYMLINK) {
+        if let Err(e) = wasi::path_unlink_file(fixture.fd, TEMP_SYMLINK) {
             return Err(e.to_string());
         }
     }
@@ -15,9 +15,18 @@ pub mod fd_tell;
 pub mod fd_readdir;
 pub mod path_filestat_get;
 pub mod random_get;
+pub mod clock_res_get;
 pub mod clock_time_get;
 pub mod path_readlink;
 pub mod path_symlink;
 pub mod fd_filestat_set_times;
 pub mod path_filestat_set_times;
 pub mod poll_oneoff;
+pub mod path_rename;
+pub mod path_link;
+pub mod directory_lifecycle;
+pub mod fd_renumber;
+pub mod fd_fdstat_set_flags;
+pub mod fd_pread_pwrite;
+pub mod fd_filestat_set_size;
+pub mod rights;
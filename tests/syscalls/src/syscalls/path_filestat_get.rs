@@ -1,4 +1,5 @@
 use constants;
+use fixture::Fixture;
 
 const INVALID_PATH: &str = "invalid";
 
@@ -50,42 +51,42 @@ unsafe fn expect_error(
     }
 }
 
-pub fn test_path_filestat_get() -> Result<(), String> {
+pub fn test_path_filestat_get(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         // check text file
         expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME,
             wasi::FILETYPE_REGULAR_FILE, constants::SAMPLE_TEXT_LEN as u64)?;
 
         // check directory
         expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_DIR_FILENAME,
             wasi::FILETYPE_DIRECTORY, constants::DIR_SIZE as u64)?;
 
         // check expanded symlinks
         expect_success(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_LINK_FILENAME,
             wasi::FILETYPE_REGULAR_FILE, constants::SAMPLE_TEXT_LEN as u64)?;
         expect_success(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_DIR_LINK_FILENAME,
+            fixture.fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_DIR_LINK_FILENAME,
             wasi::FILETYPE_DIRECTORY, constants::DIR_SIZE as u64)?;
 
         // check unexpanded symlinks
         expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_LINK_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_LINK_FILENAME,
             wasi::FILETYPE_SYMBOLIC_LINK, constants::SAMPLE_TEXT_FILENAME.len() as u64)?;
         expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_LINK_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_DIR_LINK_FILENAME,
             wasi::FILETYPE_SYMBOLIC_LINK, constants::SAMPLE_DIR_FILENAME.len() as u64)?;
 
         // path_filestat_get should fail for invalid path
         expect_error(
-            constants::PWD_DESC, 0, INVALID_PATH, wasi::ERRNO_NOENT,
+            fixture.fd, 0, INVALID_PATH, wasi::ERRNO_NOENT,
             "syscall succeeded with invalid path")?;
 
         // path_filestat_get should fail for invalid descriptor
         let dummy_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_DIR_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => {
                 if let Err(e) = wasi::fd_close(d) { return Err(e.to_string()) }
@@ -99,13 +100,13 @@ pub fn test_path_filestat_get() -> Result<(), String> {
 
         // path_filestat_get should fail without rights
         let no_access_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME, 0,
+            fixture.fd, 0, constants::SAMPLE_DIR_FILENAME, 0,
             constants::RIGHTS_ALL ^ wasi::RIGHTS_PATH_FILESTAT_GET, constants::RIGHTS_ALL, 0) {
             Ok(d) => d,
             Err(e) => return Err(e.to_string())
         };
         expect_error(
-            no_access_fd, 0, &*format!("{}0", constants::SAMPLE_DIRENTRY_NAME), wasi::ERRNO_ACCES,
+            no_access_fd, 0, &*format!("{}0", constants::SAMPLE_DIRENTRY_NAME), wasi::ERRNO_NOTCAPABLE,
             "syscall succeeded without required permissions")?;
         if let Err(e) = wasi::fd_close(no_access_fd) { return Err(e.to_string()) }
     }
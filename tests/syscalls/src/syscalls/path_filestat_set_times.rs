@@ -1,5 +1,6 @@
 use constants;
 use utils::{fd_check_times, path_check_times};
+use fixture::Fixture;
 
 struct Test {
     root_fd: wasi::Fd,
@@ -139,32 +140,32 @@ unsafe fn expect_error(
     }
 }
 
-pub fn test_path_filestat_set_times() -> Result<(), String> {
+pub fn test_path_filestat_set_times(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         let file_path = String::from("path_set_times_file");
         let dir_path = String::from("path_set_times_dir");
         let file_symlink_path = String::from("path_set_times_file_link");
         let dir_symlink_path = String::from("path_set_times_dir_link");
         let invalid_path = String::from("path_set_times_invalid");
-        if let Err(e) = wasi::path_create_directory(constants::PWD_DESC, &dir_path) {
+        if let Err(e) = wasi::path_create_directory(fixture.fd, &dir_path) {
             return Err(e.to_string());
         }
-        if let Err(e) = wasi::path_symlink(&file_path, constants::PWD_DESC, &file_symlink_path) {
+        if let Err(e) = wasi::path_symlink(&file_path, fixture.fd, &file_symlink_path) {
             return Err(e.to_string());
         }
-        if let Err(e) = wasi::path_symlink(&dir_path, constants::PWD_DESC, &dir_symlink_path) {
+        if let Err(e) = wasi::path_symlink(&dir_path, fixture.fd, &dir_symlink_path) {
             return Err(e.to_string());
         }
 
         let dir_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, &dir_path, wasi::OFLAGS_DIRECTORY,
+            fixture.fd, 0, &dir_path, wasi::OFLAGS_DIRECTORY,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0
         ) {
             Ok(desc) => desc,
             Err(e) => { return Err(e.to_string()); }
         };
         let file_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, &file_path, wasi::OFLAGS_CREAT,
+            fixture.fd, 0, &file_path, wasi::OFLAGS_CREAT,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0
         ) {
             Ok(desc) => desc,
@@ -172,7 +173,7 @@ pub fn test_path_filestat_set_times() -> Result<(), String> {
         };
 
         let dir_symlink_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, &dir_symlink_path, 0,
+            fixture.fd, 0, &dir_symlink_path, 0,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0
         ) {
             Ok(desc) => desc,
@@ -180,7 +181,7 @@ pub fn test_path_filestat_set_times() -> Result<(), String> {
         };
 
         let file_symlink_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, &file_symlink_path, 0,
+            fixture.fd, 0, &file_symlink_path, 0,
             constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0
         ) {
             Ok(desc) => desc,
@@ -188,7 +189,7 @@ pub fn test_path_filestat_set_times() -> Result<(), String> {
         };
 
         let test = Test {
-            root_fd: constants::PWD_DESC,
+            root_fd: fixture.fd,
             dir_fd,
             dir_path,
             file_fd,
@@ -0,0 +1,150 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use syscalls::fd_readdir::wasi_ls;
+use utils::{
+    create_dir, create_file, expect_contents, mnt, readlink, stat, with_vfs_mount, MOUNT_POINT, READDIR_BUF_LEN,
+};
+
+// Hard links need a filesystem with inodes, a virtual filesystem is mounted in the scratch
+// directory.
+
+const CONTENTS: &[u8] = b"linked file contents\n";
+const OTHER_CONTENTS: &[u8] = b"written through the other name\n";
+
+unsafe fn link(t: &mut Checks, fd: wasi::Fd, flags: wasi::Lookupflags, old: &str, new: &str) -> bool {
+    t.success(&call!("path_link", fd, flags, old, fd, new), wasi::path_link(fd, flags, old, fd, new)).is_some()
+}
+
+unsafe fn expect_error(t: &mut Checks, fd: wasi::Fd, old: &str, new: &str, errno: wasi::Errno, msg: &str) {
+    t.error(&call!("path_link", fd, 0, old, fd, new), wasi::path_link(fd, 0, old, fd, new), errno, msg);
+}
+
+unsafe fn expect_nlink(t: &mut Checks, fd: wasi::Fd, path: &str, nlink: wasi::Linkcount) {
+    if let Some(s) = stat(t, fd, path) {
+        t.equal(&call!("path_filestat_get", fd, 0, path), "nlink", nlink, s.nlink);
+    }
+}
+
+// both names should refer to the same inode in filestat and in directory entries
+unsafe fn expect_same_inode(t: &mut Checks, fd: wasi::Fd, dir: &str, first: &str, second: &str) {
+    let paths = (format!("{}/{}", dir, first), format!("{}/{}", dir, second));
+    let ino = match (stat(t, fd, &paths.0), stat(t, fd, &paths.1)) {
+        (Some(s1), Some(s2)) => {
+            t.equal(&call!("path_filestat_get", fd, 0, paths.1), "ino", s1.ino, s2.ino);
+            s1.ino
+        }
+        _ => return,
+    };
+
+    let call = call!("path_open", fd, 0, dir, wasi::OFLAGS_DIRECTORY);
+    let dir_fd = match t.success(&call, wasi::path_open(
+        fd, 0, dir, wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(dir_fd) => dir_fd,
+        None => return,
+    };
+    match wasi_ls(dir_fd, READDIR_BUF_LEN, 0, true) {
+        Ok((dirents, _)) => {
+            for name in [first, second].iter() {
+                t.equal(&call!("fd_readdir", dir_fd, name), "d_ino", Some(ino), dirents.get(*name).map(|d| d.d_ino));
+            }
+        }
+        Err(e) => {
+            t.check(&call!("fd_readdir", dir_fd), false, &e);
+        }
+    }
+    t.success(&call!("fd_close", dir_fd), wasi::fd_close(dir_fd));
+}
+
+unsafe fn test_link_unlink(t: &mut Checks, fd: wasi::Fd) {
+    let (file, linked, nested) = (mnt("file"), mnt("link"), mnt("dir/link"));
+    if !create_file(t, fd, &file, CONTENTS) {
+        return;
+    }
+    expect_nlink(t, fd, &file, 1);
+
+    // every link increments the count seen through all names
+    if !link(t, fd, 0, &file, &linked) {
+        return;
+    }
+    expect_nlink(t, fd, &file, 2);
+    expect_nlink(t, fd, &linked, 2);
+    expect_same_inode(t, fd, MOUNT_POINT, "file", "link");
+    let created = create_dir(t, fd, &mnt("dir"));
+    if created && link(t, fd, 0, &linked, &nested) {
+        expect_nlink(t, fd, &file, 3);
+        expect_nlink(t, fd, &nested, 3);
+    }
+
+    // names share contents
+    expect_contents(t, fd, &linked, CONTENTS);
+    if create_file(t, fd, &linked, OTHER_CONTENTS) {
+        expect_contents(t, fd, &file, OTHER_CONTENTS);
+    }
+
+    // contents stay reachable until the last name is removed
+    if t.success(&call!("path_unlink_file", fd, file), wasi::path_unlink_file(fd, &file)).is_some() {
+        expect_nlink(t, fd, &linked, if created { 2 } else { 1 });
+        expect_contents(t, fd, &linked, OTHER_CONTENTS);
+    }
+    if created && t.success(&call!("path_unlink_file", fd, nested), wasi::path_unlink_file(fd, &nested)).is_some() {
+        expect_nlink(t, fd, &linked, 1);
+    }
+}
+
+unsafe fn test_symlinks(t: &mut Checks, fd: wasi::Fd) {
+    let (target, symlink) = (mnt("target"), mnt("symlink"));
+    if !create_file(t, fd, &target, CONTENTS)
+        || t.success(&call!("path_symlink", "target", fd, symlink), wasi::path_symlink("target", fd, &symlink)).is_none() {
+        return;
+    }
+
+    // without SYMLINK_FOLLOW the symlink itself is linked
+    let linked = mnt("symlink_link");
+    if link(t, fd, 0, &symlink, &linked) {
+        t.output(&call!("path_readlink", fd, linked), readlink(fd, &linked), String::from("target"));
+        expect_same_inode(t, fd, MOUNT_POINT, "symlink", "symlink_link");
+        expect_nlink(t, fd, &target, 1);
+    }
+
+    // with SYMLINK_FOLLOW its target is linked
+    let followed = mnt("followed_link");
+    if link(t, fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, &symlink, &followed) {
+        expect_same_inode(t, fd, MOUNT_POINT, "target", "followed_link");
+        expect_nlink(t, fd, &target, 2);
+    }
+}
+
+unsafe fn test_errors(t: &mut Checks, fd: wasi::Fd) {
+    let (file, dir) = (mnt("error_file"), mnt("error_dir"));
+    if !create_file(t, fd, &file, CONTENTS) || !create_dir(t, fd, &dir) {
+        return;
+    }
+
+    expect_error(t, fd, &dir, &mnt("dir_link"), wasi::ERRNO_PERM, "linking a directory succeeded");
+    expect_error(t, fd, &mnt("nonexistent"), &mnt("new"), wasi::ERRNO_NOENT, "linking nonexistent file succeeded");
+    expect_error(t, fd, &file, &dir, wasi::ERRNO_EXIST, "linking over an existing directory succeeded");
+    expect_error(t, fd, &file, &file, wasi::ERRNO_EXIST, "linking a file to itself succeeded");
+    expect_error(
+        t, fd, &file, &mnt("nonexistent/link"), wasi::ERRNO_NOENT, "linking into nonexistent directory succeeded");
+    expect_nlink(t, fd, &file, 1);
+
+    // the scratch directory and the mount point are on different filesystems
+    if create_file(t, fd, "outside", CONTENTS) {
+        expect_error(t, fd, "outside", &mnt("outside"), wasi::ERRNO_XDEV, "linking across mount points succeeded");
+    }
+    expect_error(t, fd, &file, "inside", wasi::ERRNO_XDEV, "linking across mount points succeeded");
+    expect_nlink(t, fd, &file, 1);
+}
+
+pub fn test_path_link(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        with_vfs_mount(&mut t, fixture, |t| {
+            test_link_unlink(t, fixture.fd);
+            test_symlinks(t, fixture.fd);
+            test_errors(t, fixture.fd);
+        });
+    }
+    t.finish()
+}
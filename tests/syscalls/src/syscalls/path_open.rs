@@ -1,4 +1,6 @@
+use assertions::Checks;
 use constants;
+use fixture::Fixture;
 
 struct Test {
     fds: Vec<wasi::Fd>,
@@ -9,10 +11,10 @@ struct Test {
 }
 
 impl Test {
-    pub fn new(dummy_file: &str, dummy_dir: &str, no_file: &str) -> Self {
+    pub fn new(root_fd: wasi::Fd, dummy_file: &str, dummy_dir: &str, no_file: &str) -> Self {
         Self {
             fds: Vec::new(),
-            root_fd: constants::PWD_DESC,
+            root_fd,
             dummy_file: dummy_file.to_string(),
             dummy_dir: dummy_dir.to_string(),
             no_file: no_file.to_string()
@@ -29,95 +31,91 @@ impl Test {
         }
         Ok(())
     }
-    pub unsafe fn run_tests(&mut self) -> Result<(), String> {
+    pub unsafe fn run_tests(&mut self, t: &mut Checks) {
         // attempt to open a directory should succeed
-        self.fds.push(expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
-            wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            "Attempt to open a regular directory failed")?);
+        if let Some(fd) = expect_success(
+            t, self.root_fd, 0, constants::SAMPLE_DIR_FILENAME,
+            wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+            self.fds.push(fd);
+        }
 
         // attempt to open a regular file should succeed
-        self.fds.push(expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
-            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            "Attempt to open a regular file failed")?);
+        if let Some(fd) = expect_success(
+            t, self.root_fd, 0, constants::SAMPLE_TEXT_FILENAME,
+            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+            self.fds.push(fd);
+        }
 
         // attempt to open and expand symlink should succeed
-        self.fds.push(expect_success(
-            constants::PWD_DESC, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_LINK_FILENAME,
-            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            "Attempt to open and expand symlink failed")?);
+        if let Some(fd) = expect_success(
+            t, self.root_fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, constants::SAMPLE_LINK_FILENAME,
+            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+            self.fds.push(fd);
+        }
 
         // attempt to open symlink should succeed
-        self.fds.push(expect_success(
-            constants::PWD_DESC, 0, constants::SAMPLE_LINK_FILENAME,
-            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            "Attempt to open a symlink failed")?);
+        if let Some(fd) = expect_success(
+            t, self.root_fd, 0, constants::SAMPLE_LINK_FILENAME,
+            0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+            self.fds.push(fd);
+        }
 
         // attempt to open nonexistent file without CREAT flag should fail
         expect_error(
-            constants::PWD_DESC, 0, &self.no_file,
+            t, self.root_fd, 0, &self.no_file,
             wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            wasi::ERRNO_NOENT, "Attempt to open nonexistent file without CREAT flag succeeded")?;
+            wasi::ERRNO_NOENT, "Attempt to open nonexistent file without CREAT flag succeeded");
 
         // attempt to open existing file with CREAT and EXCL flags should fail
         expect_error(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
+            t, self.root_fd, 0, constants::SAMPLE_TEXT_FILENAME,
             wasi::OFLAGS_CREAT | wasi::OFLAGS_EXCL, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            wasi::ERRNO_EXIST, "Attempt to open existing file with CREAT and EXCL flags succeeded")?;
+            wasi::ERRNO_EXIST, "Attempt to open existing file with CREAT and EXCL flags succeeded");
 
         // attempt to open file with directory flag should fail
         expect_error(
-            constants::PWD_DESC, 0, constants::SAMPLE_TEXT_FILENAME,
+            t, self.root_fd, 0, constants::SAMPLE_TEXT_FILENAME,
             wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            wasi::ERRNO_NOTDIR, "Attempt to open a file with directory flag succeeded")?;
+            wasi::ERRNO_NOTDIR, "Attempt to open a file with directory flag succeeded");
 
         // attempt to open an existing directory with CREAT and EXCL flags should return ERRNO_EXIST
         expect_error(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+            t, self.root_fd, 0, constants::SAMPLE_DIR_FILENAME,
             wasi::OFLAGS_CREAT | wasi::OFLAGS_EXCL, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            wasi::ERRNO_EXIST, "attempt to open existing directory with CREAT and EXCL succeeded")?;
+            wasi::ERRNO_EXIST, "attempt to open existing directory with CREAT and EXCL succeeded");
 
         // creating files should work
-        self.fds.push(expect_success(
-            constants::PWD_DESC, 0, &self.dummy_file,
-            wasi::OFLAGS_CREAT, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            "Attempt to create a regular file failed")?);
+        if let Some(fd) = expect_success(
+            t, self.root_fd, 0, &self.dummy_file,
+            wasi::OFLAGS_CREAT, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
+            self.fds.push(fd);
+        }
 
         // attempt to create a directory using CREAT and DIRECTORY flags should fail
         expect_error(
-            constants::PWD_DESC, 0, &self.dummy_dir,
+            t, self.root_fd, 0, &self.dummy_dir,
             wasi::OFLAGS_CREAT | wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0,
-            wasi::ERRNO_INVAL, "Attempt to create a file with directory flag succeeded")?;
-
-        Ok(())
+            wasi::ERRNO_INVAL, "Attempt to create a file with directory flag succeeded");
     }
 }
 
 unsafe fn expect_success(
+    t: &mut Checks,
     desc: wasi::Fd,
     dirflags: wasi::Lookupflags,
     path: &str,
     open_flags: wasi::Oflags,
     rights_base: wasi::Rights,
     rights_inheriting: wasi::Rights,
-    fdflags: wasi::Fdflags,
-    msg: &str
-) -> Result<wasi::Fd, String> {
-    match wasi::path_open(
-        desc, dirflags, path,
-        open_flags, rights_base, rights_inheriting, fdflags
-    ) {
-        Ok(desc) => Ok(desc),
-        Err(e) => {
-            Err(format!(
-                "In path_open({}, {}, {}, {}, {}, {}, {}): {} ({})",
-                desc, dirflags, path, open_flags, rights_base, rights_inheriting, fdflags, msg, e))
-        }
-    }
+    fdflags: wasi::Fdflags
+) -> Option<wasi::Fd> {
+    t.success(
+        &call!("path_open", desc, dirflags, path, open_flags, rights_base, rights_inheriting, fdflags),
+        wasi::path_open(desc, dirflags, path, open_flags, rights_base, rights_inheriting, fdflags))
 }
 
 unsafe fn expect_error(
+    t: &mut Checks,
     desc: wasi::Fd,
     dirflags: wasi::Lookupflags,
     path: &str,
@@ -127,36 +125,22 @@ unsafe fn expect_error(
     fdflags: wasi::Fdflags,
     errno: wasi::Errno,
     msg: &str
-) -> Result<(), String> {
-    match wasi::path_open(
-        desc, dirflags, path,
-        open_flags, rights_base, rights_inheriting, fdflags
-    ) {
-        Ok(desc) => {
-            _ = wasi::path_unlink_file(desc, path);
-            if let Err(e) = wasi::fd_close(desc) {
-                Err(e.to_string())
-            } else {
-                Err(format!("In path_open({}, {}, {}, {}, {}, {}, {}): {}",
-                    desc, dirflags, path, open_flags, rights_base, rights_inheriting, fdflags, msg))
-            }
-        },
-        Err(e) => {
-            if e != errno {
-                Err(format!(
-                    "In path_open({}, {}, {}, {}, {}, {}, {}): unexpected error code (expected {}, got {})",
-                    desc, dirflags, path, open_flags, rights_base,
-                    rights_inheriting, fdflags, errno.raw(), e.raw()))
-            } else { Ok(()) }
-        }
+) {
+    let call = call!("path_open", desc, dirflags, path, open_flags, rights_base, rights_inheriting, fdflags);
+    let result = wasi::path_open(desc, dirflags, path, open_flags, rights_base, rights_inheriting, fdflags);
+    if let Ok(fd) = result {
+        // files created by an unexpected success are removed in tear_down
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
     }
+    t.error(&call, result, errno, msg);
 }
 
-pub fn test_path_open() -> Result<(), String> {
+pub fn test_path_open(fixture: &Fixture) -> Result<(), String> {
     unsafe {
-        let mut test = Test::new("dummy_file", "dummy_dir", "not_a_file");
-        let result = test.run_tests();
+        let mut test = Test::new(fixture.fd, "dummy_file", "dummy_dir", "not_a_file");
+        let mut t = Checks::new();
+        test.run_tests(&mut t);
         test.tear_down()?;
-        result
+        t.finish()
     }
 }
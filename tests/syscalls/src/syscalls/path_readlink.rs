@@ -1,5 +1,6 @@
 use constants;
 use std::str;
+use fixture::Fixture;
 
 const BUF_SIZE: usize = 128;
 const INVALID_PATH: &str = "invalid_path";
@@ -42,30 +43,30 @@ unsafe fn expect_error(fd: wasi::Fd, path: &str, errno: wasi::Errno, msg: &str)
     }
 }
 
-pub fn test_path_readlink() -> Result<(), String> {
+pub fn test_path_readlink(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         // readink on symlinks should work
-        expect_success(constants::PWD_DESC, constants::SAMPLE_LINK_FILENAME, constants::SAMPLE_TEXT_FILENAME)?;
-        expect_success(constants::PWD_DESC, constants::SAMPLE_DIR_LINK_FILENAME, constants::SAMPLE_DIR_FILENAME)?;
+        expect_success(fixture.fd, constants::SAMPLE_LINK_FILENAME, constants::SAMPLE_TEXT_FILENAME)?;
+        expect_success(fixture.fd, constants::SAMPLE_DIR_LINK_FILENAME, constants::SAMPLE_DIR_FILENAME)?;
 
         // readlink on directory should fail
         expect_error(
-            constants::PWD_DESC, constants::SAMPLE_DIR_FILENAME, wasi::ERRNO_INVAL,
+            fixture.fd, constants::SAMPLE_DIR_FILENAME, wasi::ERRNO_INVAL,
             "Attempt to read a directory as a symlink succeeded")?;
 
         // readlink on text file should fail
         expect_error(
-            constants::PWD_DESC, constants::SAMPLE_TEXT_FILENAME, wasi::ERRNO_INVAL,
+            fixture.fd, constants::SAMPLE_TEXT_FILENAME, wasi::ERRNO_INVAL,
             "Attempt to read a text file as a symlink succeeded")?;
 
         // readlink on invalid path should fail
         expect_error(
-            constants::PWD_DESC, INVALID_PATH, wasi::ERRNO_INVAL,
+            fixture.fd, INVALID_PATH, wasi::ERRNO_INVAL,
             "Attempt to read a text file as a symlink succeeded")?;
 
         // readlink on invalid descriptor should fail
         let dummy_fd = match wasi::path_open(
-            constants::PWD_DESC, 0, constants::SAMPLE_DIR_FILENAME,
+            fixture.fd, 0, constants::SAMPLE_DIR_FILENAME,
             0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0) {
             Ok(d) => {
                 if let Err(e) = wasi::fd_close(d) { return Err(e.to_string()) }
@@ -0,0 +1,237 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use syscalls::fd_readdir::wasi_ls;
+use utils::{
+    create_dir, create_file, expect_contents, expect_filetype, expect_missing, mnt, readlink, with_vfs_mount,
+    MOUNT_POINT, READDIR_BUF_LEN,
+};
+
+// The filesystem of the scratch directory can only move regular files, a virtual filesystem
+// is mounted to rename directories and symlinks.
+
+const CONTENTS: &[u8] = b"renamed file contents\n";
+const OTHER_CONTENTS: &[u8] = b"overwritten file contents\n";
+
+unsafe fn rename(t: &mut Checks, fd: wasi::Fd, old: &str, new: &str) -> bool {
+    t.success(&call!("path_rename", fd, old, fd, new), wasi::path_rename(fd, old, fd, new)).is_some()
+}
+
+unsafe fn expect_error(t: &mut Checks, fd: wasi::Fd, old: &str, new: &str, errno: wasi::Errno, msg: &str) {
+    t.error(&call!("path_rename", fd, old, fd, new), wasi::path_rename(fd, old, fd, new), errno, msg);
+}
+
+unsafe fn test_files(t: &mut Checks, fd: wasi::Fd) {
+    // regular files can be renamed on every filesystem
+    if create_file(t, fd, "file", CONTENTS) && rename(t, fd, "file", "renamed") {
+        expect_missing(t, fd, "file");
+        expect_contents(t, fd, "renamed", CONTENTS);
+    }
+
+    // within a directory and between directories
+    let (file, renamed) = (mnt("file"), mnt("renamed"));
+    if !create_file(t, fd, &file, CONTENTS) || !rename(t, fd, &file, &renamed) {
+        return;
+    }
+    expect_missing(t, fd, &file);
+    expect_contents(t, fd, &renamed, CONTENTS);
+    let moved = mnt("dir/moved");
+    if create_dir(t, fd, &mnt("dir")) && rename(t, fd, &renamed, &moved) {
+        expect_missing(t, fd, &renamed);
+        expect_contents(t, fd, &moved, CONTENTS);
+    }
+
+    // new path can be relative to a different descriptor
+    let dir_fd = match t.success(
+        &call!("path_open", fd, 0, mnt("dir"), wasi::OFLAGS_DIRECTORY),
+        wasi::path_open(fd, 0, &mnt("dir"), wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(dir_fd) => dir_fd,
+        None => return,
+    };
+    let call = call!("path_rename", fd, moved, dir_fd, "other");
+    if t.success(&call, wasi::path_rename(fd, &moved, dir_fd, "other")).is_some() {
+        expect_missing(t, fd, &moved);
+        expect_contents(t, fd, &mnt("dir/other"), CONTENTS);
+    }
+    t.success(&call!("fd_close", dir_fd), wasi::fd_close(dir_fd));
+}
+
+unsafe fn test_directories(t: &mut Checks, fd: wasi::Fd) {
+    let (src, dst) = (mnt("src_dir"), mnt("dst_dir"));
+    if !create_dir(t, fd, &src) || !create_file(t, fd, &mnt("src_dir/file"), CONTENTS) {
+        return;
+    }
+
+    // contents move together with the directory
+    if rename(t, fd, &src, &dst) {
+        expect_missing(t, fd, &src);
+        expect_contents(t, fd, &mnt("dst_dir/file"), CONTENTS);
+    }
+    let nested = mnt("parent/nested");
+    if create_dir(t, fd, &mnt("parent")) && rename(t, fd, &dst, &nested) {
+        expect_missing(t, fd, &dst);
+        expect_contents(t, fd, &mnt("parent/nested/file"), CONTENTS);
+    }
+
+    // renaming a directory to itself does nothing
+    if rename(t, fd, &nested, &nested) {
+        expect_filetype(t, fd, &nested, wasi::FILETYPE_DIRECTORY);
+    }
+    let missing = mnt("missing");
+    expect_error(t, fd, &missing, &missing, wasi::ERRNO_NOENT, "renaming a missing entry to itself succeeded");
+
+    // directory can't be moved into its own subtree
+    expect_error(
+        t, fd, &mnt("parent"), &mnt("parent/nested/parent"), wasi::ERRNO_INVAL,
+        "moving a directory into its own subdirectory succeeded");
+    expect_error(
+        t, fd, &mnt("parent"), &mnt("parent/child"), wasi::ERRNO_INVAL,
+        "moving a directory below itself succeeded");
+    expect_filetype(t, fd, &mnt("parent"), wasi::FILETYPE_DIRECTORY);
+}
+
+unsafe fn test_overwrite(t: &mut Checks, fd: wasi::Fd) {
+    // existing file is replaced
+    let (src, dst) = (mnt("src"), mnt("dst"));
+    if create_file(t, fd, &src, CONTENTS) && create_file(t, fd, &dst, OTHER_CONTENTS) && rename(t, fd, &src, &dst) {
+        expect_missing(t, fd, &src);
+        expect_contents(t, fd, &dst, CONTENTS);
+    }
+
+    // empty directory is replaced, non-empty one isn't
+    let (full, empty) = (mnt("full"), mnt("empty"));
+    if !create_dir(t, fd, &full) || !create_file(t, fd, &mnt("full/file"), CONTENTS) || !create_dir(t, fd, &empty) {
+        return;
+    }
+    if rename(t, fd, &full, &empty) {
+        expect_missing(t, fd, &full);
+        expect_contents(t, fd, &mnt("empty/file"), CONTENTS);
+    }
+    let other = mnt("other");
+    if create_dir(t, fd, &other) && create_file(t, fd, &mnt("other/file"), OTHER_CONTENTS) {
+        expect_error(
+            t, fd, &other, &empty, wasi::ERRNO_NOTEMPTY, "replacing a non-empty directory succeeded");
+        expect_contents(t, fd, &mnt("other/file"), OTHER_CONTENTS);
+        expect_contents(t, fd, &mnt("empty/file"), CONTENTS);
+    }
+
+    // directory can't replace a file
+    expect_error(t, fd, &other, &dst, wasi::ERRNO_NOTDIR, "replacing a file with a directory succeeded");
+    expect_contents(t, fd, &dst, CONTENTS);
+}
+
+unsafe fn test_hard_links(t: &mut Checks, fd: wasi::Fd) {
+    // renaming a path to another link of the same file does nothing, both links stay
+    let (file, link) = (mnt("linked"), mnt("linked_link"));
+    let call = call!("path_link", fd, 0, file, fd, link);
+    if create_file(t, fd, &file, CONTENTS)
+        && t.success(&call, wasi::path_link(fd, 0, &file, fd, &link)).is_some()
+        && rename(t, fd, &file, &link) {
+        expect_contents(t, fd, &file, CONTENTS);
+        expect_contents(t, fd, &link, CONTENTS);
+    }
+}
+
+unsafe fn test_symlinks(t: &mut Checks, fd: wasi::Fd) {
+    // links are moved themselves, their targets stay in place
+    let (target, link, renamed) = (mnt("target"), mnt("link"), mnt("renamed_link"));
+    if !create_file(t, fd, &target, CONTENTS) {
+        return;
+    }
+    for &link_target in ["target", "nonexistent"].iter() {
+        let call = call!("path_symlink", link_target, fd, link);
+        if t.success(&call, wasi::path_symlink(link_target, fd, &link)).is_none() || !rename(t, fd, &link, &renamed) {
+            continue;
+        }
+        expect_missing(t, fd, &link);
+        t.output(&call!("path_readlink", fd, renamed), readlink(fd, &renamed), String::from(link_target));
+        expect_filetype(t, fd, &target, wasi::FILETYPE_REGULAR_FILE);
+        t.success(&call!("path_unlink_file", fd, renamed), wasi::path_unlink_file(fd, &renamed));
+    }
+}
+
+unsafe fn test_cross_device(t: &mut Checks, fd: wasi::Fd) {
+    // the scratch directory and the mount point are on different filesystems
+    if create_file(t, fd, "outside", CONTENTS) {
+        expect_error(
+            t, fd, "outside", &mnt("outside"), wasi::ERRNO_XDEV, "renaming across mount points succeeded");
+        expect_contents(t, fd, "outside", CONTENTS);
+    }
+    let inside = mnt("inside");
+    if create_file(t, fd, &inside, CONTENTS) {
+        expect_error(t, fd, &inside, "inside", wasi::ERRNO_XDEV, "renaming across mount points succeeded");
+        expect_contents(t, fd, &inside, CONTENTS);
+    }
+
+    // mount points can't be moved
+    expect_error(t, fd, MOUNT_POINT, "moved_mnt", wasi::ERRNO_BUSY, "renaming a mount point succeeded");
+}
+
+unsafe fn test_open_descriptors(t: &mut Checks, fd: wasi::Fd) {
+    let (file, renamed) = (mnt("open_file"), mnt("open_renamed"));
+    if !create_file(t, fd, &file, CONTENTS) {
+        return;
+    }
+    let file_fd = match t.success(
+        &call!("path_open", fd, 0, file),
+        wasi::path_open(fd, 0, &file, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(file_fd) => file_fd,
+        None => return,
+    };
+
+    // descriptors refer to the file, not to its path
+    if rename(t, fd, &file, &renamed) {
+        let mut buf = vec![0u8; CONTENTS.len()];
+        t.output(
+            &call!("fd_read", file_fd, buf.len()),
+            wasi::fd_read(file_fd, &[wasi::Iovec { buf: buf.as_mut_ptr(), buf_len: buf.len() }]),
+            CONTENTS.len());
+        t.equal(&call!("fd_read", file_fd, buf.len()), "contents", CONTENTS, &buf[..]);
+        t.output(
+            &call!("fd_write", file_fd, OTHER_CONTENTS),
+            wasi::fd_write(file_fd, &[wasi::Ciovec { buf: OTHER_CONTENTS.as_ptr(), buf_len: OTHER_CONTENTS.len() }]),
+            OTHER_CONTENTS.len());
+        expect_contents(t, fd, &renamed, &[CONTENTS, OTHER_CONTENTS].concat());
+    }
+    t.success(&call!("fd_close", file_fd), wasi::fd_close(file_fd));
+
+    let (dir, renamed_dir) = (mnt("open_dir"), mnt("open_dir_renamed"));
+    if !create_dir(t, fd, &dir) || !create_file(t, fd, &mnt("open_dir/file"), CONTENTS) {
+        return;
+    }
+    let dir_fd = match t.success(
+        &call!("path_open", fd, 0, dir, wasi::OFLAGS_DIRECTORY),
+        wasi::path_open(fd, 0, &dir, wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(dir_fd) => dir_fd,
+        None => return,
+    };
+    if rename(t, fd, &dir, &renamed_dir) {
+        match wasi_ls(dir_fd, READDIR_BUF_LEN, 0, true) {
+            Ok((dirents, _)) => {
+                t.check(
+                    &call!("fd_readdir", dir_fd), dirents.contains_key("file"),
+                    &format!("renamed directory doesn't list its file (got {:?})", dirents.keys()));
+            }
+            Err(e) => {
+                t.check(&call!("fd_readdir", dir_fd), false, &e);
+            }
+        }
+    }
+    t.success(&call!("fd_close", dir_fd), wasi::fd_close(dir_fd));
+}
+
+pub fn test_path_rename(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        with_vfs_mount(&mut t, fixture, |t| {
+            test_files(t, fixture.fd);
+            test_directories(t, fixture.fd);
+            test_overwrite(t, fixture.fd);
+            test_hard_links(t, fixture.fd);
+            test_symlinks(t, fixture.fd);
+            test_cross_device(t, fixture.fd);
+            test_open_descriptors(t, fixture.fd);
+        });
+    }
+    t.finish()
+}
@@ -1,15 +1,16 @@
 use constants;
 use std::str;
+use fixture::Fixture;
 
 struct Test<'a> {
     root_fd: wasi::Fd,
     dir_path: &'a str,
     dir_link: &'a str,
-    dir_path_abs: &'a str,
+    dir_path_abs: String,
     dir_link_abs: &'a str,
     file_path: &'a str,
     file_link: &'a str,
-    file_path_abs: &'a str,
+    file_path_abs: String,
     file_link_abs: &'a str,
     invalid_path: &'a str,
     dummy_links: Vec<&'a str>
@@ -29,10 +30,10 @@ impl Test<'_> {
         check_contents(self.root_fd, self.file_link, self.file_path)?;
 
         // creating symlinks to existing directory or text file using absolute paths should work
-        expect_success(self.dir_path_abs, self.root_fd, self.dir_link_abs)?;
-        check_contents(self.root_fd, self.dir_link_abs, self.dir_path_abs)?;
-        expect_success(self.file_path_abs, self.root_fd, self.file_link_abs)?;
-        check_contents(self.root_fd, self.file_link_abs, self.file_path_abs)?;
+        expect_success(&self.dir_path_abs, self.root_fd, self.dir_link_abs)?;
+        check_contents(self.root_fd, self.dir_link_abs, &self.dir_path_abs)?;
+        expect_success(&self.file_path_abs, self.root_fd, self.file_link_abs)?;
+        check_contents(self.root_fd, self.file_link_abs, &self.file_path_abs)?;
 
         // creating symlinks to invalid files should work
         expect_success(self.invalid_path, self.root_fd, self.dummy_links[0])?;
@@ -121,17 +122,17 @@ unsafe fn check_contents(
     }
 }
 
-pub fn test_path_symlink() -> Result<(), String> {
+pub fn test_path_symlink(fixture: &Fixture) -> Result<(), String> {
     unsafe {
         let test = Test {
-            root_fd: constants::PWD_DESC,
+            root_fd: fixture.fd,
             dir_path: constants::SAMPLE_DIR_FILENAME,
             dir_link: "path_symlink_dir_link",
-            dir_path_abs: constants::SAMPLE_DIR_FILENAME_ABS,
+            dir_path_abs: fixture.abs_path(constants::SAMPLE_DIR_FILENAME),
             dir_link_abs: "path_symlink_dir_link_abs",
             file_path: constants::SAMPLE_TEXT_FILENAME,
             file_link: "path_symlink_file_link",
-            file_path_abs: constants::SAMPLE_TEXT_FILENAME_ABS,
+            file_path_abs: fixture.abs_path(constants::SAMPLE_TEXT_FILENAME),
             file_link_abs: "path_symlink_file_link_abs",
             invalid_path: "invalid_path",
             dummy_links: vec!["dummy_link1", "dummy_link2"]
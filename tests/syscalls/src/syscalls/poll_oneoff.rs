@@ -1,398 +1,376 @@
+use std::collections::HashMap;
+use std::env;
 use std::mem;
+use std::time::{Duration, Instant};
 
+use assertions::Checks;
+use child;
 use constants;
+use fixture::Fixture;
+use utils::{
+    clock_subscription, fd_subscription, make_fifo, open_end, poll, read_n, write, FIFO_READ_RIGHTS,
+    FIFO_WRITE_RIGHTS,
+};
 
-fn event_eq(s1: &wasi::Event, s2: &wasi::Event) -> bool {
-    if s1.type_ == wasi::EVENTTYPE_CLOCK {
-        (s1.userdata, s1.error, s1.type_)
-        ==
-        (s2.userdata, s2.error, s2.type_)
-    } else {
-        (s1.userdata, s1.error, s1.type_, s1.fd_readwrite.nbytes, s1.fd_readwrite.flags)
-        ==
-        (s2.userdata, s2.error, s2.type_, s2.fd_readwrite.nbytes, s2.fd_readwrite.flags)
-    }
-}
+use syscalls::clock_res_get::INVALID_CLOCK;
 
-fn expected_errno_returned(
-    result: Result<wasi::Size, wasi::Errno>,
-    expected: Result<wasi::Size, wasi::Errno>,
-) -> Result<(), String> {
-    match (result, expected) {
-        (Ok(res), Ok(exp)) => if res != exp {
-            return Err(format!(
-                "In poll_oneoff(): invalid syscall output \
-                (expected {{events number: {}}})\
-                got {{events number: {}}}", exp, res
-            ))
-        },
-        (Err(res), Err(exp)) => if res != exp {
-            return Err(format!(
-                "In poll_oneoff(): invalid syscall output \
-                (expected {{errno: {}}})\
-                got {{errno: {}}}", exp, res
-            ))
-        },
-        (Err(res), Ok(num)) => {
-            return Err(format!(
-                "In poll_oneoff(): invalid syscall output \
-                (expected success {{events number: {}}})\
-                got fail {{errno: {}}}", num, res
-            ))
-        },
-        (Ok(res), Err(err)) => {
-            return Err(format!(
-                "In poll_oneoff(): invalid syscall output \
-                (expected fail {{errno: {}}})\
-                got success {{events number: {}}}", err, res
-            ))
-        },
-    }
-    Ok(())
+// URL of a WebSocket echo server, connection devices are polled only if it is set and
+// reported as skipped otherwise
+const WS_ECHO_ENV: &str = "SYSCALLS_TEST_WS_ECHO";
+
+// clock expected to fire, and a clock that should never be reached by the test
+const TIMEOUT: wasi::Timestamp = 100_000_000;
+const LONG_TIMEOUT: wasi::Timestamp = 5_000_000_000;
+
+// a clock firing this much earlier than requested was not waited for, realtime clock
+// only counts whole milliseconds
+const TOLERANCE: wasi::Timestamp = 1_000_000;
+
+const BAD_FD: wasi::Fd = 1024;
+const INVALID_TAG: u8 = 0xff;
+
+const CHILD_BYTES: usize = 4096;
+
+// connection devices are always writable, nbytes is Number.MAX_SAFE_INTEGER
+const WS_WRITE_NBYTES: wasi::Filesize = (1 << 53) - 1;
+
+// nbytes of events that don't report it
+const NO_NBYTES: wasi::Filesize = 0;
+
+// (userdata, error, type, nbytes) of an event, events are compared in the order they were reported
+type Occurred = (wasi::Userdata, wasi::Errno, wasi::Eventtype, wasi::Filesize);
+
+unsafe fn poll_events(subscriptions: &[wasi::Subscription]) -> Result<Vec<Occurred>, wasi::Errno> {
+    poll(subscriptions).map(|events| {
+        events.iter().map(|e| (e.userdata, e.error, e.type_, e.fd_readwrite.nbytes)).collect()
+    })
 }
 
-unsafe fn expect_success(
-    in_: *const wasi::Subscription,
-    nsubscriptions: wasi::Size,
-    events: &Vec<wasi::Event>
-) -> Result<(), String> {
-    let expected = events.len();
-    let mut out: Vec<wasi::Event> = vec![mem::zeroed(); nsubscriptions];
-
-    let result = wasi::poll_oneoff(in_, out.as_mut_ptr(), nsubscriptions);
-    expected_errno_returned(result, Ok(expected))?;
-
-    let got = result.unwrap();
-
-    for i in 0..got {
-        if !event_eq(&out[i], &events[i]) {
-            return Err(
-                format!(
-                    "In poll_oneoff({:?}): invalid syscall output \
-                    (expected {{events[{i}]: {:?}}})\
-                    got {{events[{i}]: {:?}}}", in_, events[i], out[i]
-                )
-            );
-        }
-    }
+fn clock_event(userdata: wasi::Userdata) -> Occurred {
+    (userdata, wasi::ERRNO_SUCCESS, wasi::EVENTTYPE_CLOCK, NO_NBYTES)
+}
 
-    Ok(())
+fn fd_event(userdata: wasi::Userdata, type_: wasi::Eventtype, nbytes: wasi::Filesize) -> Occurred {
+    (userdata, wasi::ERRNO_SUCCESS, type_, nbytes)
 }
 
-unsafe fn expect_error_event(
-    in_: *const wasi::Subscription,
-    nsubscriptions: wasi::Size,
-    events: &Vec<wasi::Event>
-) -> Result<(), String> {
-    // Syscall return Ok(nevents) but in events buffor schould be errno code
-    expect_success(in_, nsubscriptions, events)
+fn fd_error(userdata: wasi::Userdata, type_: wasi::Eventtype, error: wasi::Errno) -> Occurred {
+    (userdata, error, type_, NO_NBYTES)
 }
 
-unsafe fn expect_error(
-    in_: *const wasi::Subscription,
-    nsubscriptions: wasi::Size,
-    errno: wasi::Errno
-) -> Result<(), String> {
-    let mut out: Vec<wasi::Event> = vec![mem::zeroed(); nsubscriptions];
+fn subscription_clock(
+    userdata: wasi::Userdata,
+    id: wasi::Clockid,
+    timeout: wasi::Timestamp,
+    flags: wasi::Subclockflags
+) -> wasi::Subscription {
+    let mut sub = clock_subscription(userdata, timeout);
+    sub.u.u.clock.id = id;
+    sub.u.u.clock.flags = flags;
+    sub
+}
 
-    let result = wasi::poll_oneoff(in_, out.as_mut_ptr(), nsubscriptions);
-    expected_errno_returned(result, Err(errno))
+// absolute subscription expiring timeout from now
+unsafe fn abstime_subscription(
+    t: &mut Checks,
+    userdata: wasi::Userdata,
+    id: wasi::Clockid,
+    timeout: wasi::Timestamp
+) -> Option<wasi::Subscription> {
+    let result = wasi::clock_time_get(id, 0);
+    t.success(&call!("clock_time_get", id.raw(), 0), result.map(|_| ()))?;
+    Some(subscription_clock(userdata, id, result.unwrap() + timeout, wasi::SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME))
 }
 
-pub fn test_poll_oneoff() -> Result<(), String> {
-    let clock_sub = wasi::Subscription {
-        userdata: 0,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_CLOCK.raw(),
-            u: wasi::SubscriptionUU {
-                clock: wasi::SubscriptionClock {
-                    id: wasi::CLOCKID_MONOTONIC,
-                    timeout: 100000000,
-                    precision: 0,
-                    flags: 0
-                }
-            }
+unsafe fn test_clocks(t: &mut Checks) {
+    t.output(
+        &call!("poll_oneoff", "clock", TIMEOUT),
+        poll_events(&[clock_subscription(1, TIMEOUT)]),
+        vec![clock_event(1)]);
+
+    // absolute timeouts are waited for on their own clock
+    for &id in [wasi::CLOCKID_REALTIME, wasi::CLOCKID_MONOTONIC].iter() {
+        let sub = match abstime_subscription(t, 1, id, TIMEOUT) {
+            Some(sub) => sub,
+            None => continue,
+        };
+        let call = call!("poll_oneoff", "abstime", id.raw(), "clock", LONG_TIMEOUT);
+        let start = Instant::now();
+        if t.output(&call, poll_events(&[sub, clock_subscription(2, LONG_TIMEOUT)]), vec![clock_event(1)]) {
+            let elapsed = start.elapsed();
+            t.check(
+                &call, elapsed >= Duration::from_nanos(TIMEOUT - TOLERANCE),
+                &format!("absolute timeout expired too early (after {:?})", elapsed));
         }
-    };
 
-    let stdin_sub = wasi::Subscription {
-        userdata: 1,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_READ.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: 0
-                }
-            }
-        }
-    };
+        // deadline that already passed expires immediately
+        let past = subscription_clock(1, id, 0, wasi::SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME);
+        t.output(
+            &call!("poll_oneoff", "abstime", id.raw(), 0, "clock", LONG_TIMEOUT),
+            poll_events(&[past, clock_subscription(2, LONG_TIMEOUT)]),
+            vec![clock_event(1)]);
+    }
 
-    let stdout_sub = wasi::Subscription {
-        userdata: 2,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_WRITE.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: 1
-                }
-            }
-        }
-    };
+    // only the clock expiring first is reported, regardless of its id or position, CPU time
+    // clocks aren't used as they don't advance while the process is blocked in poll
+    let mixed = [
+        Some(subscription_clock(1, wasi::CLOCKID_REALTIME, 3 * TIMEOUT, 0)),
+        abstime_subscription(t, 2, wasi::CLOCKID_MONOTONIC, 2 * TIMEOUT),
+        abstime_subscription(t, 3, wasi::CLOCKID_REALTIME, TIMEOUT),
+        Some(subscription_clock(4, wasi::CLOCKID_MONOTONIC, 4 * TIMEOUT, 0)),
+    ];
+    if let [Some(first), Some(second), Some(third), Some(fourth)] = mixed {
+        t.output(
+            &call!("poll_oneoff", "realtime", "abstime monotonic", "abstime realtime", "monotonic"),
+            poll_events(&[first, second, third, fourth]),
+            vec![clock_event(3)]);
+        t.output(
+            &call!("poll_oneoff", "realtime", "abstime monotonic", "monotonic"),
+            poll_events(&[first, second, fourth]),
+            vec![clock_event(2)]);
+    }
 
-    let file_fd = unsafe {
-        let dirflags = 0;
-        let oflags = 0;
-        let fdflags = wasi::FDFLAGS_SYNC | wasi::FDFLAGS_DSYNC;
-
-        match wasi::path_open(
-            constants::PWD_DESC, dirflags,
-            constants::SAMPLE_TEXT_FILENAME,
-            oflags,
-            constants::RIGHTS_ALL,
-            constants::RIGHTS_ALL,
-            fdflags
-        ) {
-            Ok(d) => d,
-            Err(e) => { return Err(e.to_string()) }
-        }
-    };
+    // the id is written over the clock of an otherwise valid subscription
+    let mut sub = clock_subscription(1, TIMEOUT);
+    *(&mut sub.u.u.clock.id as *mut wasi::Clockid as *mut u32) = INVALID_CLOCK;
+    t.error(
+        &call!("poll_oneoff", "clock", INVALID_CLOCK), poll_events(&[sub]),
+        wasi::ERRNO_INVAL, "polling an invalid clock succeeded");
+}
 
-    let file_read_sub = wasi::Subscription {
-        userdata: 3,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_READ.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: file_fd
-                }
-            }
-        }
+unsafe fn test_files(t: &mut Checks, fixture: &Fixture) {
+    // nothing is typed in the terminal during the test
+    t.output(
+        &call!("poll_oneoff", "clock", TIMEOUT, "fd_read", 0),
+        poll_events(&[clock_subscription(1, TIMEOUT), fd_subscription(2, wasi::EVENTTYPE_FD_READ, 0)]),
+        vec![clock_event(1)]);
+
+    let call = call!("path_open", fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME, 0);
+    let file_fd = match t.success(&call, wasi::path_open(
+        fixture.fd, 0, constants::SAMPLE_TEXT_FILENAME, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        Some(fd) => fd,
+        None => return,
     };
 
-    let file_write_sub = wasi::Subscription {
-        userdata: 4,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_WRITE.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: file_fd
-                }
-            }
-        }
-    };
+    // regular files are always ready, with their size in nbytes
+    let size = constants::SAMPLE_TEXT_LEN as wasi::Filesize;
+    let writes = [
+        fd_subscription(1, wasi::EVENTTYPE_FD_WRITE, 1),
+        fd_subscription(2, wasi::EVENTTYPE_FD_WRITE, file_fd),
+    ];
+    let expected = vec![
+        fd_event(1, wasi::EVENTTYPE_FD_WRITE, NO_NBYTES),
+        fd_event(2, wasi::EVENTTYPE_FD_WRITE, size),
+    ];
+    t.output(&call!("poll_oneoff", "fd_write", 1, "fd_write", file_fd), poll_events(&writes), expected.clone());
+    t.output(
+        &call!("poll_oneoff", "clock", TIMEOUT, "fd_write", 1, "fd_write", file_fd),
+        poll_events(&[clock_subscription(3, TIMEOUT), writes[0], writes[1]]),
+        expected);
+
+    let file_read = fd_subscription(1, wasi::EVENTTYPE_FD_READ, file_fd);
+    t.output(
+        &call!("poll_oneoff", "clock", LONG_TIMEOUT, "fd_read", file_fd),
+        poll_events(&[clock_subscription(2, LONG_TIMEOUT), file_read]),
+        vec![fd_event(1, wasi::EVENTTYPE_FD_READ, size)]);
+    t.success(&call!("fd_close", file_fd), wasi::fd_close(file_fd));
+
+    // directories can't be polled
+    let call = call!("path_open", fixture.fd, 0, constants::SAMPLE_DIR_FILENAME, 0);
+    if let Some(dir_fd) = t.success(&call, wasi::path_open(
+        fixture.fd, 0, constants::SAMPLE_DIR_FILENAME, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)) {
+        t.output(
+            &call!("poll_oneoff", "clock", LONG_TIMEOUT, "fd_read", dir_fd, "fd_write", dir_fd),
+            poll_events(&[
+                clock_subscription(1, LONG_TIMEOUT),
+                fd_subscription(2, wasi::EVENTTYPE_FD_READ, dir_fd),
+                fd_subscription(3, wasi::EVENTTYPE_FD_WRITE, dir_fd),
+            ]),
+            vec![
+                fd_error(2, wasi::EVENTTYPE_FD_READ, wasi::ERRNO_NOTSUP),
+                fd_error(3, wasi::EVENTTYPE_FD_WRITE, wasi::ERRNO_NOTSUP),
+            ]);
+        t.success(&call!("fd_close", dir_fd), wasi::fd_close(dir_fd));
+    }
+}
 
-    let dir_fd = unsafe {
-        let dirflags = 0;
-        let oflags = 0;
-        let fdflags = 0;
-
-        match wasi::path_open(
-            constants::PWD_DESC,
-            dirflags,
-            constants::SAMPLE_DIR_FILENAME,
-            oflags,
-            constants::RIGHTS_ALL,
-            constants::RIGHTS_ALL,
-            fdflags
-        ) {
-            Ok(d) => d,
-            Err(e) => { return Err(e.to_string()) }
-        }
+unsafe fn test_fifo(t: &mut Checks, fixture: &Fixture, path: &str) {
+    let read_fd = match open_end(t, path, FIFO_READ_RIGHTS) {
+        Some(fd) => fd,
+        None => return,
     };
-
-    let dir_read_sub = wasi::Subscription {
-        userdata: 5,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_READ.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: dir_fd
-                }
-            }
+    let write_fd = match open_end(t, path, FIFO_WRITE_RIGHTS) {
+        Some(fd) => fd,
+        None => {
+            t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+            return;
         }
     };
 
-    let dir_write_sub = wasi::Subscription {
-        userdata: 6,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_WRITE.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: dir_fd
-                }
-            }
-        }
-    };
+    // empty FIFO only wakes up the writer
+    t.output(
+        &call!("poll_oneoff", "fd_read", read_fd, "fd_write", write_fd),
+        poll_events(&[
+            fd_subscription(1, wasi::EVENTTYPE_FD_READ, read_fd),
+            fd_subscription(2, wasi::EVENTTYPE_FD_WRITE, write_fd),
+        ]),
+        vec![fd_event(2, wasi::EVENTTYPE_FD_WRITE, NO_NBYTES)]);
+    t.output(
+        &call!("poll_oneoff", "fd_read", read_fd, "clock", TIMEOUT),
+        poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, read_fd), clock_subscription(2, TIMEOUT)]),
+        vec![clock_event(2)]);
+
+    // pending data is reported in nbytes
+    let data = child::pattern(3);
+    t.output(&call!("fd_write", write_fd, data.len()), write(write_fd, &data), data.len());
+    t.output(
+        &call!("poll_oneoff", "fd_read", read_fd, "clock", LONG_TIMEOUT),
+        poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, read_fd), clock_subscription(2, LONG_TIMEOUT)]),
+        vec![fd_event(1, wasi::EVENTTYPE_FD_READ, data.len() as wasi::Filesize)]);
+    t.output(&call!("fd_read", read_fd, data.len()), read_n(read_fd, data.len(), data.len()), data);
+
+    // event source without pending events never wakes up the poll
+    let call = call!("event_source_fd", "WINCH|SIGINT");
+    let mask = wasi_ext_lib::WASI_EVENT_WINCH | wasi_ext_lib::WASI_EVENT_SIGINT;
+    if let Some(source) = t.success(&call, wasi_ext_lib::event_source_fd(mask)) {
+        let source = source as wasi::Fd;
+        t.output(
+            &call!("poll_oneoff", "fd_read", source, "clock", TIMEOUT),
+            poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, source), clock_subscription(2, TIMEOUT)]),
+            vec![clock_event(2)]);
+        t.output(
+            &call!("poll_oneoff", "fd_read", source, "fd_write", write_fd, "clock", LONG_TIMEOUT),
+            poll_events(&[
+                fd_subscription(1, wasi::EVENTTYPE_FD_READ, source),
+                fd_subscription(2, wasi::EVENTTYPE_FD_WRITE, write_fd),
+                clock_subscription(3, LONG_TIMEOUT),
+            ]),
+            vec![fd_event(2, wasi::EVENTTYPE_FD_WRITE, NO_NBYTES)]);
+        t.success(&call!("fd_close", source), wasi::fd_close(source));
+    }
 
-    let bad_fd_read_sub = wasi::Subscription {
-        userdata: 7,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_READ.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: 1024
-                }
-            }
+    // data written by another process wakes up the reader before the clock
+    let redirects = [wasi_ext_lib::Redirect::Write((1, fixture.preopen_path(path)))];
+    let n = CHILD_BYTES.to_string();
+    let call = call!("spawn", "write-N-bytes", n, "background");
+    if let Ok((_, pid)) = child::spawn("write-N-bytes", &[&n], &HashMap::new(), true, &redirects) {
+        let call = call!("poll_oneoff", "fd_read", read_fd, "clock", LONG_TIMEOUT);
+        if let Some(events) = t.success(&call, poll_events(&[
+            fd_subscription(1, wasi::EVENTTYPE_FD_READ, read_fd),
+            clock_subscription(2, LONG_TIMEOUT),
+        ])) {
+            t.check(
+                &call,
+                events.len() == 1 && events[0].0 == 1 && events[0].1 == wasi::ERRNO_SUCCESS && events[0].3 > 0,
+                &format!("read end didn't wake up on data written by the child (got {:?})", events));
         }
+        t.check(
+            &call!("fd_read", read_fd, CHILD_BYTES),
+            read_n(read_fd, CHILD_BYTES, CHILD_BYTES) == Ok(child::pattern(CHILD_BYTES)),
+            "data read from the FIFO differs from data written by the child");
+        t.check(
+            &call!("wait_for_exit", "write-N-bytes"), child::wait_for_exit(pid, Duration::from_nanos(LONG_TIMEOUT)),
+            "background child didn't exit");
+    } else {
+        t.check(&call, false, "spawning a background child failed");
+    }
+
+    t.success(&call!("fd_close", write_fd), wasi::fd_close(write_fd));
+    t.success(&call!("fd_close", read_fd), wasi::fd_close(read_fd));
+}
+
+unsafe fn test_websocket(t: &mut Checks, url: &str) {
+    let dev_fd = match open_end(t, "dev/ws0", FIFO_WRITE_RIGHTS) {
+        Some(fd) => fd,
+        None => return,
+    };
+    // writing the URL connects, the number of the created connection device is returned
+    let minor = t.success(&call!("fd_write", dev_fd, url), write(dev_fd, url.as_bytes()));
+    t.success(&call!("fd_close", dev_fd), wasi::fd_close(dev_fd));
+    let path = match minor {
+        Some(minor) => format!("dev/ws0s{}", minor),
+        None => return,
     };
 
-    let bad_fd_write_sub = wasi::Subscription {
-        userdata: 8,
-        u: wasi::SubscriptionU {
-            tag: wasi::EVENTTYPE_FD_WRITE.raw(),
-            u: wasi::SubscriptionUU {
-                fd_read: wasi::SubscriptionFdReadwrite {
-                    file_descriptor: 1024
-                }
-            }
-        }
+    let fd = match open_end(t, &path, FIFO_READ_RIGHTS | FIFO_WRITE_RIGHTS) {
+        Some(fd) => fd,
+        None => return,
     };
 
-    let invalid_sub = wasi::Subscription {
-        userdata: 9,
-        u: wasi::SubscriptionU {
-            tag: 0xff,
-            u: unsafe { mem::zeroed() }
-        }
+    t.output(
+        &call!("poll_oneoff", "fd_read", fd, "fd_write", fd),
+        poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, fd), fd_subscription(2, wasi::EVENTTYPE_FD_WRITE, fd)]),
+        vec![fd_event(2, wasi::EVENTTYPE_FD_WRITE, WS_WRITE_NBYTES)]);
+    t.output(
+        &call!("poll_oneoff", "fd_read", fd, "clock", TIMEOUT),
+        poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, fd), clock_subscription(2, TIMEOUT)]),
+        vec![clock_event(2)]);
+
+    // echoed message arrives while the connection is polled
+    let message = child::pattern(5);
+    t.output(&call!("fd_write", fd, message.len()), write(fd, &message), message.len());
+    t.output(
+        &call!("poll_oneoff", "fd_read", fd, "clock", LONG_TIMEOUT),
+        poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, fd), clock_subscription(2, LONG_TIMEOUT)]),
+        vec![fd_event(1, wasi::EVENTTYPE_FD_READ, message.len() as wasi::Filesize)]);
+    t.output(&call!("fd_read", fd, message.len()), read_n(fd, message.len(), message.len()), message);
+
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+unsafe fn test_errors(t: &mut Checks) {
+    t.error(&call!("poll_oneoff"), poll_events(&[]), wasi::ERRNO_INVAL, "polling no subscriptions succeeded");
+
+    // bad descriptors are reported in events instead of failing the call
+    let bad = [
+        fd_subscription(1, wasi::EVENTTYPE_FD_READ, BAD_FD),
+        fd_subscription(2, wasi::EVENTTYPE_FD_WRITE, BAD_FD),
+    ];
+    let expected = vec![
+        fd_error(1, wasi::EVENTTYPE_FD_READ, wasi::ERRNO_BADF),
+        fd_error(2, wasi::EVENTTYPE_FD_WRITE, wasi::ERRNO_BADF),
+    ];
+    t.output(&call!("poll_oneoff", "fd_read", BAD_FD, "fd_write", BAD_FD), poll_events(&bad), expected.clone());
+    t.output(
+        &call!("poll_oneoff", "clock", LONG_TIMEOUT, "fd_read", BAD_FD, "fd_write", BAD_FD),
+        poll_events(&[clock_subscription(3, LONG_TIMEOUT), bad[0], bad[1]]),
+        expected);
+
+    // descriptor closed before the call is just as bad
+    if let Some(fd) = t.success(
+        &call!("path_open", constants::PWD_DESC, 0, ".", 0),
+        wasi::path_open(constants::PWD_DESC, 0, ".", 0, 0, 0, 0)) {
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        t.output(
+            &call!("poll_oneoff", "fd_read", fd),
+            poll_events(&[fd_subscription(1, wasi::EVENTTYPE_FD_READ, fd)]),
+            vec![fd_error(1, wasi::EVENTTYPE_FD_READ, wasi::ERRNO_BADF)]);
+    }
+
+    let invalid = wasi::Subscription {
+        userdata: 1,
+        u: wasi::SubscriptionU { tag: INVALID_TAG, u: mem::zeroed() },
     };
+    t.error(
+        &call!("poll_oneoff", "tag", INVALID_TAG), poll_events(&[invalid]),
+        wasi::ERRNO_INVAL, "polling a subscription with undefined tag succeeded");
+}
 
+pub fn test_poll_oneoff(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
     unsafe {
-        // test one clock subscribion
-        let in_ = [ clock_sub ];
-        let events = vec![
-            wasi::Event {
-                userdata: 0,
-                error: wasi::ERRNO_SUCCESS,
-                type_: wasi::EVENTTYPE_CLOCK,
-                fd_readwrite: mem::zeroed()
-            },
-        ];
-        expect_success(in_.as_ptr(), in_.len(), &events)?;
-
-        // test waiting for stdin with timeout - timeout exceed
-        let in_ = [
-            clock_sub, stdin_sub,
-        ];
-        expect_success(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-            stdout_sub, file_write_sub,
-        ];
-        let events = vec![
-            wasi::Event {
-                userdata: 2,
-                error: wasi::ERRNO_SUCCESS,
-                type_: wasi::EVENTTYPE_FD_WRITE,
-                fd_readwrite: mem::zeroed()
-            },
-            wasi::Event {
-                userdata: 4,
-                error: wasi::ERRNO_SUCCESS,
-                type_: wasi::EVENTTYPE_FD_WRITE,
-                fd_readwrite: wasi::EventFdReadwrite {
-                    nbytes: constants::SAMPLE_TEXT_LEN as u64,
-                    flags: 0
-                }
-            },
-        ];
-
-        // Writing to any file type is unsupported right now
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-        let in_ = [
-           clock_sub, stdout_sub, file_write_sub,
-        ];
-
-        // With clock sub output should be the same for writing
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-           file_read_sub,
-        ];
-
-        let events = vec![
-            wasi::Event {
-                userdata: 3,
-                error: wasi::ERRNO_SUCCESS,
-                type_: wasi::EVENTTYPE_FD_READ,
-                fd_readwrite: wasi::EventFdReadwrite {
-                    nbytes: constants::SAMPLE_TEXT_LEN as u64,
-                    flags: 0
-                }
-            },
-        ];
-
-        // Read sub for regular files is not permited
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-            clock_sub, file_read_sub,
-        ];
-
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-           dir_read_sub, dir_write_sub,
-        ];
-
-        let events = vec![
-            wasi::Event {
-                userdata: 5,
-                error: wasi::ERRNO_NOTSUP,
-                type_: wasi::EVENTTYPE_FD_READ,
-                fd_readwrite: mem::zeroed()
-            },
-            wasi::Event {
-                userdata: 6,
-                error: wasi::ERRNO_NOTSUP,
-                type_: wasi::EVENTTYPE_FD_WRITE,
-                fd_readwrite: mem::zeroed()
-            },
-        ];
-
-        // Any operation on directiories are not permited/supported
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-            clock_sub, dir_read_sub, dir_write_sub,
-        ];
-
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-           bad_fd_read_sub, bad_fd_write_sub,
-        ];
-
-        let events = vec![
-            wasi::Event {
-                userdata: 7,
-                error: wasi::ERRNO_BADF,
-                type_: wasi::EVENTTYPE_FD_READ,
-                fd_readwrite: mem::zeroed()
-            },
-            wasi::Event {
-                userdata: 8,
-                error: wasi::ERRNO_BADF,
-                type_: wasi::EVENTTYPE_FD_WRITE,
-                fd_readwrite: mem::zeroed()
-            },
-        ];
-
-        // When user passes unopened file descriptor then syscall should returns errno
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-            clock_sub, bad_fd_read_sub, bad_fd_write_sub,
-        ];
-
-        expect_error_event(in_.as_ptr(), in_.len(), &events)?;
-
-        let in_ = [
-           invalid_sub,
-        ];
-
-        // User used undefined tag for event subscription
-        expect_error(in_.as_ptr(), in_.len(), wasi::ERRNO_INVAL)?;
+        test_clocks(&mut t);
+        test_files(&mut t, fixture);
+
+        if let Some(path) = make_fifo(&mut t, fixture) {
+            test_fifo(&mut t, fixture, &path);
+        }
+
+        match env::var(WS_ECHO_ENV) {
+            Ok(url) => test_websocket(&mut t, &url),
+            Err(_) => t.skip(&call!("poll_oneoff", "dev/ws0"), &format!("{} is not set", WS_ECHO_ENV)),
+        }
+        test_errors(&mut t);
     }
-    
-    Ok(())
+    t.finish()
 }
@@ -1,6 +1,8 @@
+use fixture::Fixture;
+
 const BUF_SIZE: usize = 128;
 
-pub fn test_random_get() -> Result<(), String> {
+pub fn test_random_get(_: &Fixture) -> Result<(), String> {
     unsafe {
         let mut buf = vec![0u8; BUF_SIZE];
         match wasi::random_get(buf.as_mut_ptr(), BUF_SIZE) {
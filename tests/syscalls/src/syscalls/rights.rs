@@ -0,0 +1,338 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+use utils::{fd_subscription, poll, write_file};
+
+// Every operation is run on a descriptor missing just the right it needs and has to fail with
+// ENOTCAPABLE. Directory operations work on entries of DIR, paths are relative to the scratch
+// directory.
+const DIR: &str = "dir";
+const FILE: &str = "dir/file";
+const SUBDIR: &str = "dir/subdir";
+const SUBDIR_FILE: &str = "dir/subdir/file";
+
+const BUF_LEN: usize = 256;
+
+type Op = unsafe fn(wasi::Fd) -> Result<(), wasi::Errno>;
+
+// fd_fdstat_set_flags isn't listed, descriptors of standard streams opened by the shell don't
+// have FD_FDSTAT_SET_FLAGS and programs change their flags anyway
+const FILE_OPS: &[(wasi::Rights, &str, Op)] = &[
+    (wasi::RIGHTS_FD_DATASYNC, "fd_datasync", fd_datasync),
+    (wasi::RIGHTS_FD_READ, "fd_read", fd_read),
+    (wasi::RIGHTS_FD_SEEK, "fd_seek", fd_seek),
+    (wasi::RIGHTS_FD_SYNC, "fd_sync", fd_sync),
+    (wasi::RIGHTS_FD_TELL, "fd_tell", fd_tell),
+    (wasi::RIGHTS_FD_WRITE, "fd_write", fd_write),
+    (wasi::RIGHTS_FD_ADVISE, "fd_advise", fd_advise),
+    (wasi::RIGHTS_FD_ALLOCATE, "fd_allocate", fd_allocate),
+    (wasi::RIGHTS_FD_FILESTAT_GET, "fd_filestat_get", fd_filestat_get),
+    (wasi::RIGHTS_FD_FILESTAT_SET_SIZE, "fd_filestat_set_size", fd_filestat_set_size),
+    (wasi::RIGHTS_FD_FILESTAT_SET_TIMES, "fd_filestat_set_times", fd_filestat_set_times),
+    (wasi::RIGHTS_POLL_FD_READWRITE, "poll_oneoff(fd_read)", poll_fd_read),
+    (wasi::RIGHTS_POLL_FD_READWRITE, "poll_oneoff(fd_write)", poll_fd_write),
+];
+
+// destructive operations come last so the others still find their entries when run with all rights
+const DIR_OPS: &[(wasi::Rights, &str, Op)] = &[
+    (wasi::RIGHTS_FD_READDIR, "fd_readdir", fd_readdir),
+    (wasi::RIGHTS_PATH_OPEN, "path_open", path_open),
+    (wasi::RIGHTS_PATH_CREATE_FILE, "path_open(OFLAGS_CREAT)", path_open_creat),
+    (wasi::RIGHTS_PATH_FILESTAT_SET_SIZE, "path_open(OFLAGS_TRUNC)", path_open_trunc),
+    (wasi::RIGHTS_PATH_FILESTAT_GET, "path_filestat_get", path_filestat_get),
+    (wasi::RIGHTS_PATH_FILESTAT_SET_TIMES, "path_filestat_set_times", path_filestat_set_times),
+    (wasi::RIGHTS_PATH_READLINK, "path_readlink", path_readlink),
+    (wasi::RIGHTS_PATH_SYMLINK, "path_symlink", path_symlink),
+    (wasi::RIGHTS_PATH_CREATE_DIRECTORY, "path_create_directory", path_create_directory),
+    (wasi::RIGHTS_PATH_LINK_SOURCE, "path_link", path_link),
+    (wasi::RIGHTS_PATH_LINK_TARGET, "path_link", path_link),
+    (wasi::RIGHTS_PATH_RENAME_SOURCE, "path_rename", path_rename),
+    (wasi::RIGHTS_PATH_RENAME_TARGET, "path_rename", path_rename),
+    (wasi::RIGHTS_PATH_REMOVE_DIRECTORY, "path_remove_directory", path_remove_directory),
+    (wasi::RIGHTS_PATH_UNLINK_FILE, "path_unlink_file", path_unlink_file),
+];
+
+unsafe fn fd_datasync(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_datasync(fd)
+}
+
+unsafe fn fd_read(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    let mut buf = [0u8; BUF_LEN];
+    wasi::fd_read(fd, &[wasi::Iovec { buf: buf.as_mut_ptr(), buf_len: buf.len() }]).map(|_| ())
+}
+
+unsafe fn fd_seek(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_seek(fd, 0, wasi::WHENCE_SET).map(|_| ())
+}
+
+unsafe fn fd_sync(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_sync(fd)
+}
+
+unsafe fn fd_tell(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_tell(fd).map(|_| ())
+}
+
+unsafe fn fd_write(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    let data = constants::SAMPLE_TEXT;
+    wasi::fd_write(fd, &[wasi::Ciovec { buf: data.as_ptr(), buf_len: data.len() }]).map(|_| ())
+}
+
+unsafe fn fd_advise(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_advise(fd, 0, 0, wasi::ADVICE_NORMAL)
+}
+
+unsafe fn fd_allocate(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_allocate(fd, 0, constants::SAMPLE_TEXT_LEN as wasi::Filesize)
+}
+
+unsafe fn fd_filestat_get(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_filestat_get(fd).map(|_| ())
+}
+
+unsafe fn fd_filestat_set_size(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_filestat_set_size(fd, constants::SAMPLE_TEXT_LEN as wasi::Filesize)
+}
+
+unsafe fn fd_filestat_set_times(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::fd_filestat_set_times(fd, 0, 0, wasi::FSTFLAGS_MTIM_NOW)
+}
+
+// poll_oneoff itself succeeds, a missing right is reported in the error of the event
+unsafe fn poll_fd(fd: wasi::Fd, type_: wasi::Eventtype) -> Result<(), wasi::Errno> {
+    match poll(&[fd_subscription(1, type_, fd)])?.first() {
+        Some(event) if event.error != wasi::ERRNO_SUCCESS => Err(event.error),
+        _ => Ok(()),
+    }
+}
+
+unsafe fn poll_fd_read(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    poll_fd(fd, wasi::EVENTTYPE_FD_READ)
+}
+
+unsafe fn poll_fd_write(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    poll_fd(fd, wasi::EVENTTYPE_FD_WRITE)
+}
+
+unsafe fn fd_readdir(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    let mut buf = [0u8; BUF_LEN];
+    wasi::fd_readdir(fd, buf.as_mut_ptr(), buf.len(), 0).map(|_| ())
+}
+
+unsafe fn open_close(fd: wasi::Fd, path: &str, oflags: wasi::Oflags) -> Result<(), wasi::Errno> {
+    let fd = wasi::path_open(fd, 0, path, oflags, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)?;
+    wasi::fd_close(fd)
+}
+
+unsafe fn path_open(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    open_close(fd, "file", 0)
+}
+
+unsafe fn path_open_creat(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    open_close(fd, "created", wasi::OFLAGS_CREAT)
+}
+
+unsafe fn path_open_trunc(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    open_close(fd, "file", wasi::OFLAGS_TRUNC)
+}
+
+unsafe fn path_filestat_get(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_filestat_get(fd, 0, "file").map(|_| ())
+}
+
+unsafe fn path_filestat_set_times(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_filestat_set_times(fd, 0, "file", 0, 0, wasi::FSTFLAGS_MTIM_NOW)
+}
+
+unsafe fn path_readlink(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    let mut buf = [0u8; BUF_LEN];
+    wasi::path_readlink(fd, "symlink", buf.as_mut_ptr(), buf.len()).map(|_| ())
+}
+
+unsafe fn path_symlink(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_symlink("file", fd, "new_symlink")
+}
+
+unsafe fn path_create_directory(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_create_directory(fd, "new_dir")
+}
+
+unsafe fn path_link(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_link(fd, 0, "file", fd, "hard_link")
+}
+
+unsafe fn path_rename(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_rename(fd, "created", fd, "renamed")
+}
+
+unsafe fn path_remove_directory(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_remove_directory(fd, "subdir")
+}
+
+unsafe fn path_unlink_file(fd: wasi::Fd) -> Result<(), wasi::Errno> {
+    wasi::path_unlink_file(fd, "file")
+}
+
+unsafe fn open(
+    t: &mut Checks, fd: wasi::Fd, path: &str, oflags: wasi::Oflags, base: wasi::Rights, inheriting: wasi::Rights,
+) -> Option<wasi::Fd> {
+    t.success(
+        &call!("path_open", fd, 0, path, oflags, base, inheriting, 0),
+        wasi::path_open(fd, 0, path, oflags, base, inheriting, 0))
+}
+
+unsafe fn close(t: &mut Checks, fd: wasi::Fd) {
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+}
+
+unsafe fn expect_rights(t: &mut Checks, fd: wasi::Fd, base: wasi::Rights, inheriting: wasi::Rights) {
+    let call = call!("fd_fdstat_get", fd);
+    if let Some(stat) = t.success(&call, wasi::fd_fdstat_get(fd)) {
+        t.equal(&call, "fs_rights_base", base, stat.fs_rights_base);
+        t.equal(&call, "fs_rights_inheriting", inheriting, stat.fs_rights_inheriting);
+    }
+}
+
+unsafe fn expect_not_capable(t: &mut Checks, fd: wasi::Fd, name: &str, op: Op) {
+    t.error(&call!(name, fd), op(fd), wasi::ERRNO_NOTCAPABLE, "operation succeeded without the right");
+}
+
+unsafe fn set_up(t: &mut Checks, fixture: &Fixture) -> bool {
+    let dir_fd = fixture.fd;
+    t.success(&call!("path_create_directory", dir_fd, DIR), wasi::path_create_directory(dir_fd, DIR)).is_some()
+        && t.success(&call!("path_create_directory", dir_fd, SUBDIR), wasi::path_create_directory(dir_fd, SUBDIR))
+            .is_some()
+        && t.success(&call!("write_file", FILE), write_file(dir_fd, FILE, constants::SAMPLE_TEXT)).is_some()
+        && t.success(&call!("write_file", SUBDIR_FILE), write_file(dir_fd, SUBDIR_FILE, constants::SAMPLE_TEXT))
+            .is_some()
+        && t.success(
+            &call!("path_symlink", "file", dir_fd, "dir/symlink"),
+            wasi::path_symlink("file", dir_fd, "dir/symlink")).is_some()
+        && t.success(
+            &call!("write_file", "dir/created"), write_file(dir_fd, "dir/created", constants::SAMPLE_TEXT)).is_some()
+}
+
+unsafe fn test_matrix(
+    t: &mut Checks, fixture: &Fixture, path: &str, oflags: wasi::Oflags, ops: &[(wasi::Rights, &str, Op)],
+) {
+    for &(right, name, op) in ops {
+        let base = constants::RIGHTS_ALL & !right;
+        if let Some(fd) = open(t, fixture.fd, path, oflags, base, constants::RIGHTS_ALL) {
+            expect_rights(t, fd, base, constants::RIGHTS_ALL);
+            expect_not_capable(t, fd, name, op);
+            close(t, fd);
+        }
+    }
+
+    // with all rights none of the operations is refused for lack of them
+    if let Some(fd) = open(t, fixture.fd, path, oflags, constants::RIGHTS_ALL, constants::RIGHTS_ALL) {
+        for &(_, name, op) in ops {
+            let result = op(fd);
+            t.check(
+                &call!(name, fd), result != Err(wasi::ERRNO_NOTCAPABLE),
+                "operation refused although all rights are granted");
+        }
+        close(t, fd);
+    }
+}
+
+unsafe fn test_inheriting(t: &mut Checks, fixture: &Fixture) {
+    let inheriting = constants::RIGHTS_ALL & !(wasi::RIGHTS_FD_WRITE | wasi::RIGHTS_PATH_UNLINK_FILE);
+    let dir_fd = match open(t, fixture.fd, DIR, wasi::OFLAGS_DIRECTORY, constants::RIGHTS_ALL, inheriting) {
+        Some(fd) => fd,
+        None => return,
+    };
+
+    // rights asked for beyond the inheriting ones of the directory aren't granted
+    if let Some(fd) = open(t, dir_fd, "file", 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL) {
+        expect_rights(t, fd, inheriting, inheriting);
+        expect_not_capable(t, fd, "fd_write", fd_write);
+        close(t, fd);
+    }
+
+    // and the limit is passed further down through subdirectories
+    let all = constants::RIGHTS_ALL;
+    if let Some(sub_fd) = open(t, dir_fd, "subdir", wasi::OFLAGS_DIRECTORY, all, all) {
+        expect_rights(t, sub_fd, inheriting, inheriting);
+        expect_not_capable(t, sub_fd, "path_unlink_file", path_unlink_file);
+        if let Some(fd) = open(t, sub_fd, "file", 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL) {
+            expect_rights(t, fd, inheriting, inheriting);
+            expect_not_capable(t, fd, "fd_write", fd_write);
+            close(t, fd);
+        }
+        close(t, sub_fd);
+    }
+    close(t, dir_fd);
+}
+
+unsafe fn set_rights(t: &mut Checks, fd: wasi::Fd, base: wasi::Rights, inheriting: wasi::Rights) -> bool {
+    t.success(
+        &call!("fd_fdstat_set_rights", fd, base, inheriting),
+        wasi::fd_fdstat_set_rights(fd, base, inheriting)).is_some()
+}
+
+unsafe fn expect_no_regain(t: &mut Checks, fd: wasi::Fd, base: wasi::Rights, inheriting: wasi::Rights) {
+    t.error(
+        &call!("fd_fdstat_set_rights", fd, base, inheriting), wasi::fd_fdstat_set_rights(fd, base, inheriting),
+        wasi::ERRNO_NOTCAPABLE, "rights were extended");
+}
+
+unsafe fn test_set_rights(t: &mut Checks, fixture: &Fixture) {
+    let all = constants::RIGHTS_ALL;
+    let no_write = all & !wasi::RIGHTS_FD_WRITE;
+    if let Some(fd) = open(t, fixture.fd, FILE, 0, all, all) {
+        // keeping the same rights is allowed
+        if set_rights(t, fd, all, all) {
+            expect_rights(t, fd, all, all);
+        }
+        if set_rights(t, fd, no_write, all) {
+            expect_rights(t, fd, no_write, all);
+            expect_not_capable(t, fd, "fd_write", fd_write);
+        }
+
+        // dropped rights are gone for good
+        expect_no_regain(t, fd, all, all);
+        if set_rights(t, fd, no_write, no_write) {
+            expect_no_regain(t, fd, no_write, all);
+        }
+        expect_rights(t, fd, no_write, no_write);
+        close(t, fd);
+        t.error(
+            &call!("fd_fdstat_set_rights", fd, 0, 0), wasi::fd_fdstat_set_rights(fd, 0, 0),
+            wasi::ERRNO_BADF, "setting rights of a closed descriptor succeeded");
+    }
+
+    // dropped inheriting rights limit what is opened afterwards
+    let no_read = all & !wasi::RIGHTS_FD_READ;
+    if let Some(dir_fd) = open(t, fixture.fd, DIR, wasi::OFLAGS_DIRECTORY, all, all) {
+        if set_rights(t, dir_fd, all & !wasi::RIGHTS_PATH_OPEN, no_read) {
+            expect_not_capable(t, dir_fd, "path_open", path_open);
+            expect_no_regain(t, dir_fd, all, no_read);
+        }
+        close(t, dir_fd);
+    }
+    if let Some(dir_fd) = open(t, fixture.fd, DIR, wasi::OFLAGS_DIRECTORY, all, all) {
+        if set_rights(t, dir_fd, all, no_read) {
+            if let Some(fd) = open(t, dir_fd, "file", 0, all, all) {
+                expect_rights(t, fd, no_read, no_read);
+                expect_not_capable(t, fd, "fd_read", fd_read);
+                close(t, fd);
+            }
+        }
+        close(t, dir_fd);
+    }
+}
+
+pub fn test_rights(fixture: &Fixture) -> Result<(), String> {
+    let mut t = Checks::new();
+    unsafe {
+        if !set_up(&mut t, fixture) {
+            return t.finish();
+        }
+        test_inheriting(&mut t, fixture);
+        test_set_rights(&mut t, fixture);
+        test_matrix(&mut t, fixture, FILE, 0, FILE_OPS);
+        test_matrix(&mut t, fixture, DIR, wasi::OFLAGS_DIRECTORY, DIR_OPS);
+    }
+    t.finish()
+}
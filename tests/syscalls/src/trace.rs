@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+
+use assertions::Check;
+use constants;
+use fixture;
+use report;
+
+// Outcome of a single call made by a test. Traces are newline-delimited JSON objects
+// with "test", "call" and "observed" string fields, one per check. Observed is the errno or
+// Ok, followed by the returned value if values are recorded.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub test: String,
+    pub call: String,
+    pub observed: String,
+}
+
+// Scratch directory names are random, replace them so traces of different runs can be compared.
+fn normalize(test: &str, s: &str) -> String {
+    let prefix = format!("{}{}.", fixture::SCRATCH_PREFIX, test);
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find(&prefix) {
+        let suffix = &rest[i + prefix.len()..];
+        let suffix_len = suffix.chars().take_while(|c| c.is_ascii_hexdigit()).count();
+        out.push_str(&rest[..i]);
+        out.push_str("$SCRATCH");
+        rest = &suffix[suffix_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// fields of values like wasi::Filestat that differ between runs, their numbers are masked
+const VOLATILE_FIELDS: &[&str] = &["dev", "ino", "atim", "mtim", "ctim"];
+
+// calls returning a descriptor that wasn't open before
+const FD_RESULTS: &[&str] = &["path_open", "event_source_fd"];
+
+// positions of descriptor arguments, poll_oneoff names the descriptor of each subscription
+// by the preceding "fd_read" or "fd_write" argument instead
+fn fd_args(name: &str) -> &'static [usize] {
+    match name {
+        "fd_renumber" => &[0, 1],
+        "path_rename" => &[0, 2],
+        "path_link" => &[0, 3],
+        "path_symlink" => &[1],
+        "isatty" | "ioctl" => &[0],
+        _ if name.starts_with("fd_") || name.starts_with("path_") => &[0],
+        _ => &[],
+    }
+}
+
+// Runtimes number descriptors differently, so descriptors above constants::PWD_DESC are
+// replaced with $FD<n>, numbered in the order they are opened or first used by a test.
+struct Normalizer<'a> {
+    test: &'a str,
+    fds: HashMap<wasi::Fd, usize>,
+    // index of the next placeholder
+    next: usize,
+}
+
+impl<'a> Normalizer<'a> {
+    fn new(test: &'a str) -> Self {
+        Normalizer { test, fds: HashMap::new(), next: 0 }
+    }
+
+    fn fd(&mut self, fd: wasi::Fd) -> String {
+        if fd <= constants::PWD_DESC {
+            return fd.to_string();
+        }
+        if !self.fds.contains_key(&fd) {
+            self.fds.insert(fd, self.next);
+            self.next += 1;
+        }
+        format!("$FD{}", self.fds[&fd])
+    }
+
+    // a reused number refers to a new descriptor
+    fn opened_fd(&mut self, fd: wasi::Fd) -> String {
+        self.fds.remove(&fd);
+        self.fd(fd)
+    }
+
+    fn call(&mut self, call: &str) -> String {
+        let call = normalize(self.test, call);
+        let (name, args) = match (call.find('('), call.ends_with(')')) {
+            (Some(i), true) => (&call[..i], split_args(&call[i + 1..call.len() - 1])),
+            _ => return call.clone(),
+        };
+        let positions = fd_args(name);
+        let mut out = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            let subscribed = name == "poll_oneoff" && i > 0
+                && (args[i - 1] == "\"fd_read\"" || args[i - 1] == "\"fd_write\"");
+            match arg.parse::<wasi::Fd>() {
+                Ok(fd) if subscribed || positions.contains(&i) => out.push(self.fd(fd)),
+                _ => out.push(String::from(*arg)),
+            }
+        }
+        format!("{}({})", name, out.join(", "))
+    }
+
+    fn value(&mut self, call: &str, value: &str) -> String {
+        let name = call.split('(').next().unwrap_or(call);
+        match value.parse::<wasi::Fd>() {
+            Ok(fd) if FD_RESULTS.contains(&name) => self.opened_fd(fd),
+            _ => mask_fields(&normalize(self.test, value)),
+        }
+    }
+}
+
+// splits arguments formatted by call!, strings and nested values may contain ", "
+fn split_args(args: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let (mut depth, mut quoted, mut escaped, mut start) = (0, false, false, 0);
+    for (i, c) in args.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '(' | '[' | '{' if !quoted => depth += 1,
+            ')' | ']' | '}' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                out.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !args.is_empty() {
+        out.push(args[start..].trim());
+    }
+    out
+}
+
+fn mask_fields(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    'scan: while let Some(c) = rest.chars().next() {
+        if !out.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+            for field in VOLATILE_FIELDS {
+                let key = format!("{}: ", field);
+                if !rest.starts_with(&key) {
+                    continue;
+                }
+                let digits = rest[key.len()..].chars().take_while(|c| c.is_ascii_digit()).count();
+                if digits > 0 {
+                    out.push_str(&key);
+                    out.push('_');
+                    rest = &rest[key.len() + digits..];
+                    continue 'scan;
+                }
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+// Tests that don't use assertions::Checks record no calls and have no entries, their
+// outcome alone can't be compared between runtimes. Values are only included if asked for,
+// even normalized they may differ between runtimes that both behave correctly.
+pub fn entries(test: &str, checks: &[Check], values: bool) -> Vec<Entry> {
+    let mut normalizer = Normalizer::new(test);
+    checks.iter().map(|c| {
+        let call = normalizer.call(&c.call);
+        // descriptors opened by the call are numbered even if values aren't recorded
+        let value = c.value.as_ref().map(|v| normalizer.value(&c.call, v));
+        let observed = match value {
+            Some(v) if values => format!("{}: {}", c.observed, v),
+            _ => c.observed.clone(),
+        };
+        Entry { test: String::from(test), call, observed }
+    }).collect()
+}
+
+pub struct Recorder {
+    out: File,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> Result<Self, String> {
+        match File::create(path) {
+            Ok(out) => Ok(Recorder { out }),
+            Err(e) => Err(format!("Couldn't create trace file {}: {}", path, e)),
+        }
+    }
+
+    pub fn write(&mut self, entries: &[Entry]) -> Result<(), String> {
+        for e in entries {
+            if let Err(err) = writeln!(
+                self.out, "{{\"test\":\"{}\",\"call\":\"{}\",\"observed\":\"{}\"}}",
+                report::escape_json(&e.test), report::escape_json(&e.call),
+                report::escape_json(&e.observed)) {
+                return Err(format!("Couldn't write trace: {}", err));
+            }
+        }
+        Ok(())
+    }
+}
+
+// trace recorded by a reference runtime, entries are grouped by test
+pub struct Reference {
+    tests: HashMap<String, Vec<Entry>>,
+}
+
+impl Reference {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return Err(format!("Couldn't read reference trace {}: {}", path, e)),
+        };
+        let mut tests: HashMap<String, Vec<Entry>> = HashMap::new();
+        for (n, line) in contents.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let invalid = |msg: &str| format!("Invalid reference trace {} (line {}): {}", path, n + 1, msg);
+            let mut fields = parse_object(line).map_err(|e| invalid(&e))?;
+            let mut field = |name: &str| fields.remove(name).ok_or_else(|| invalid(&format!("missing \"{}\"", name)));
+            let entry = Entry { test: field("test")?, call: field("call")?, observed: field("observed")? };
+            tests.entry(entry.test.clone()).or_insert_with(Vec::new).push(entry);
+        }
+        Ok(Reference { tests })
+    }
+
+    // Compares entries of a test with the reference, every entry becomes a check that fails
+    // if it diverges. Returns None if the reference doesn't contain the test.
+    pub fn diff(&self, test: &str, entries: &[Entry]) -> Option<Vec<Check>> {
+        let reference = self.tests.get(test)?;
+        let mut checks: Vec<Check> = entries.iter().enumerate().map(|(i, e)| {
+            let message = match reference.get(i) {
+                None => Some(String::from("call not made by the reference runtime")),
+                Some(r) if r.call != e.call => Some(format!(
+                    "reference runtime made a different call ({})", r.call)),
+                Some(r) if r.observed != e.observed => Some(format!(
+                    "differs from reference (expected {}, got {})", r.observed, e.observed)),
+                Some(_) => None,
+            };
+            Check {
+                call: e.call.clone(),
+                observed: e.observed.clone(),
+                value: None,
+                passed: message.is_none(),
+                message: message.map(|m| format!("In {}: {}", e.call, m)),
+            }
+        }).collect();
+        for r in reference.iter().skip(entries.len()) {
+            checks.push(Check {
+                call: r.call.clone(),
+                observed: String::new(),
+                value: None,
+                passed: false,
+                message: Some(format!("In {}: call made by the reference runtime is missing", r.call)),
+            });
+        }
+        Some(checks)
+    }
+}
+
+// parses a flat JSON object with string values, as written by Recorder
+fn parse_object(line: &str) -> Result<HashMap<String, String>, String> {
+    let mut chars = line.trim().chars();
+    let mut fields = HashMap::new();
+    if chars.next() != Some('{') {
+        return Err(String::from("expected '{'"));
+    }
+    loop {
+        match chars.next() {
+            Some('}') if fields.is_empty() => break,
+            Some('"') => {}
+            _ => return Err(String::from("expected a string key")),
+        }
+        let key = parse_string(&mut chars)?;
+        if chars.next() != Some(':') || chars.next() != Some('"') {
+            return Err(format!("expected a string value for \"{}\"", key));
+        }
+        let value = parse_string(&mut chars)?;
+        fields.insert(key, value);
+        match chars.next() {
+            Some(',') => {}
+            Some('}') => break,
+            _ => return Err(String::from("expected ',' or '}'")),
+        }
+    }
+    Ok(fields)
+}
+
+// parses the rest of a string after the opening quote
+fn parse_string<I: Iterator<Item = char>>(chars: &mut I) -> Result<String, String> {
+    let mut out = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(out),
+            Some('\\') => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    match u32::from_str_radix(&hex, 16).ok().and_then(std::char::from_u32) {
+                        Some(c) => out.push(c),
+                        None => return Err(format!("invalid escape \\u{}", hex)),
+                    }
+                }
+                Some(c) => out.push(c),
+                None => return Err(String::from("unterminated string")),
+            },
+            Some(c) => out.push(c),
+            None => return Err(String::from("unterminated string")),
+        }
+    }
+}
@@ -1,3 +1,130 @@
+use assertions::Checks;
+use constants;
+use fixture::Fixture;
+
+// mknod with a negative device number creates a FIFO
+pub const FIFO_DEV: i32 = -1;
+pub const FIFO_READ_RIGHTS: wasi::Rights = wasi::RIGHTS_FD_READ | wasi::RIGHTS_POLL_FD_READWRITE;
+pub const FIFO_WRITE_RIGHTS: wasi::Rights = wasi::RIGHTS_FD_WRITE | wasi::RIGHTS_POLL_FD_READWRITE;
+
+// A virtual filesystem is mounted here for checks the filesystem of the scratch directory
+// can't run. Paths are relative to the scratch directory.
+pub const MOUNT_POINT: &str = "mnt";
+
+// path of name in root, an empty root is the scratch directory itself
+pub fn path(root: &str, name: &str) -> String {
+    if root.is_empty() {
+        String::from(name)
+    } else {
+        format!("{}/{}", root, name)
+    }
+}
+
+// Calls f with a virtual filesystem mounted at MOUNT_POINT and unmounts it afterwards,
+// f isn't called if the mount fails.
+pub unsafe fn with_vfs_mount<F: FnOnce(&mut Checks)>(t: &mut Checks, fixture: &Fixture, f: F) {
+    let target = fixture.abs_path(MOUNT_POINT);
+    if t.success(
+        &call!("path_create_directory", fixture.fd, MOUNT_POINT),
+        wasi::path_create_directory(fixture.fd, MOUNT_POINT)).is_none()
+        || t.success(&call!("mount", "", target, "vfs", 0, ""), wasi_ext_lib::mount("", &target, "vfs", 0, "")).is_none() {
+        return;
+    }
+    f(t);
+    t.success(&call!("umount", target), wasi_ext_lib::umount(&target));
+}
+
+// Calls f with the root of the scratch directory and then with MOUNT_POINT while a virtual
+// filesystem is mounted there, paths in f are built with path().
+pub unsafe fn for_each_root<F: FnMut(&mut Checks, &str)>(t: &mut Checks, fixture: &Fixture, mut f: F) {
+    f(t, "");
+    with_vfs_mount(t, fixture, |t| f(t, MOUNT_POINT));
+}
+
+// buffer size for listing scratch directories with wasi_ls
+pub const READDIR_BUF_LEN: usize = 4096;
+
+// path of name in the virtual filesystem mounted by with_vfs_mount
+pub fn mnt(name: &str) -> String {
+    path(MOUNT_POINT, name)
+}
+
+pub unsafe fn create_file(t: &mut Checks, dir: wasi::Fd, path: &str, contents: &[u8]) -> bool {
+    t.success(&call!("write_file", path), write_file(dir, path, contents)).is_some()
+}
+
+pub unsafe fn create_dir(t: &mut Checks, dir: wasi::Fd, path: &str) -> bool {
+    t.success(&call!("path_create_directory", dir, path), wasi::path_create_directory(dir, path)).is_some()
+}
+
+pub unsafe fn stat(t: &mut Checks, dir: wasi::Fd, path: &str) -> Option<wasi::Filestat> {
+    t.success(&call!("path_filestat_get", dir, 0, path), wasi::path_filestat_get(dir, 0, path))
+}
+
+pub unsafe fn expect_contents(t: &mut Checks, dir: wasi::Fd, path: &str, contents: &[u8]) {
+    t.output(&call!("read_file", path), read_file(dir, path), contents.to_vec());
+}
+
+pub unsafe fn expect_filetype(t: &mut Checks, dir: wasi::Fd, path: &str, filetype: wasi::Filetype) {
+    if let Some(s) = stat(t, dir, path) {
+        t.equal(&call!("path_filestat_get", dir, 0, path), "filetype", filetype, s.filetype);
+    }
+}
+
+pub unsafe fn expect_fd_filetype(t: &mut Checks, fd: wasi::Fd, filetype: wasi::Filetype) {
+    if let Some(s) = t.success(&call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd)) {
+        t.equal(&call!("fd_fdstat_get", fd), "filetype", filetype, s.fs_filetype);
+    }
+}
+
+// entries that were renamed or removed
+pub unsafe fn expect_missing(t: &mut Checks, dir: wasi::Fd, path: &str) {
+    t.error(
+        &call!("path_filestat_get", dir, 0, path), wasi::path_filestat_get(dir, 0, path),
+        wasi::ERRNO_NOENT, "entry still exists");
+}
+
+// returns a descriptor of name in dir that was opened and closed again
+pub unsafe fn closed_fd(t: &mut Checks, dir: wasi::Fd, name: &str) -> Option<wasi::Fd> {
+    let fd = t.success(
+        &call!("path_open", dir, 0, name, 0),
+        wasi::path_open(dir, 0, name, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0))?;
+    t.success(&call!("fd_close", fd), wasi::fd_close(fd))?;
+    Some(fd)
+}
+
+// fdstat of a standard stream if it is a terminal, streams might be redirected to files
+pub unsafe fn terminal_stat(t: &mut Checks, fd: wasi::Fd) -> Option<wasi::Fdstat> {
+    match t.success(&call!("fd_fdstat_get", fd), wasi::fd_fdstat_get(fd)) {
+        Some(stat) if stat.fs_filetype == wasi::FILETYPE_CHARACTER_DEVICE => Some(stat),
+        _ => None,
+    }
+}
+
+pub unsafe fn read(fd: wasi::Fd, len: usize) -> Result<Vec<u8>, wasi::Errno> {
+    let mut buf = vec![0u8; len];
+    let n = wasi::fd_read(fd, &[wasi::Iovec { buf: buf.as_mut_ptr(), buf_len: buf.len() }])?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+// reads until n bytes are read or EOF is reached, at most buf_len bytes at a time
+pub unsafe fn read_n(fd: wasi::Fd, n: usize, buf_len: usize) -> Result<Vec<u8>, wasi::Errno> {
+    let mut data = Vec::new();
+    while data.len() < n {
+        let chunk = read(fd, buf_len.min(n - data.len()))?;
+        if chunk.is_empty() {
+            break;
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+pub unsafe fn write(fd: wasi::Fd, data: &[u8]) -> Result<usize, wasi::Errno> {
+    wasi::fd_write(fd, &[wasi::Ciovec { buf: data.as_ptr(), buf_len: data.len() }])
+}
+
 unsafe fn check_times(
     filestat: wasi::Filestat,
     atim_ex: Option<wasi::Timestamp>,
@@ -43,3 +170,128 @@ pub unsafe fn path_check_times(
         Err(e) => { return Err(e.to_string()); }
     }, atim_ex, mtim_ex)
 }
+
+// reads a whole file
+pub unsafe fn read_file(dir: wasi::Fd, path: &str) -> Result<Vec<u8>, wasi::Errno> {
+    let fd = wasi::path_open(dir, 0, path, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)?;
+    let mut contents = Vec::new();
+    let mut buf = [0u8; 256];
+    let result = loop {
+        match wasi::fd_read(fd, &[wasi::Iovec { buf: buf.as_mut_ptr(), buf_len: buf.len() }]) {
+            Ok(0) => break Ok(contents),
+            Ok(n) => contents.extend_from_slice(&buf[..n]),
+            Err(e) => break Err(e),
+        }
+    };
+    wasi::fd_close(fd)?;
+    result
+}
+
+// creates or truncates a file and writes contents to it
+pub unsafe fn write_file(dir: wasi::Fd, path: &str, contents: &[u8]) -> Result<(), wasi::Errno> {
+    let fd = wasi::path_open(
+        dir, 0, path, wasi::OFLAGS_CREAT | wasi::OFLAGS_TRUNC,
+        constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0)?;
+    let result = wasi::fd_write(fd, &[wasi::Ciovec { buf: contents.as_ptr(), buf_len: contents.len() }]);
+    wasi::fd_close(fd)?;
+    match result {
+        Ok(n) if n == contents.len() => Ok(()),
+        Ok(_) => Err(wasi::ERRNO_IO),
+        Err(e) => Err(e),
+    }
+}
+
+// offset open_at_cursor moves the cursor to, calls that don't use it shouldn't change it
+pub const CURSOR: wasi::Filesize = 3;
+
+// writes SAMPLE_TEXT to a file and opens it with all rights and the cursor at CURSOR
+pub unsafe fn open_at_cursor(t: &mut Checks, dir: wasi::Fd, path: &str) -> Option<wasi::Fd> {
+    t.success(&call!("write_file", path), write_file(dir, path, constants::SAMPLE_TEXT))?;
+    let call = call!("path_open", dir, 0, path, 0);
+    let fd = t.success(&call, wasi::path_open(dir, 0, path, 0, constants::RIGHTS_ALL, constants::RIGHTS_ALL, 0))?;
+    if t.success(
+        &call!("fd_seek", fd, CURSOR, wasi::WHENCE_SET.raw()),
+        wasi::fd_seek(fd, CURSOR as wasi::Filedelta, wasi::WHENCE_SET)).is_none() {
+        t.success(&call!("fd_close", fd), wasi::fd_close(fd));
+        return None;
+    }
+    Some(fd)
+}
+
+// Enums of the wasi crate can't hold values outside of their range, so invalid ones are
+// passed to the raw wasi_snapshot_preview1 import and ret is the errno it returned.
+pub fn raw_errno(t: &mut Checks, call: &str, ret: i32, errno: wasi::Errno) -> bool {
+    t.equal(call, "errno", errno.raw() as i32, ret)
+}
+
+// Calls a jswasi kernel syscall directly, the way wasi_ext_lib does: path_readlink gets a path
+// starting with "!" that names the command and points to its JSON arguments. Used to pass
+// arguments the library API doesn't expose, pointers in args are addresses in linear memory.
+pub unsafe fn ext_syscall(command: &str, args: &str, output: &mut [u8]) -> Result<usize, wasi::Errno> {
+    let call = format!(
+        "!{{\"command\":\"{}\",\"buf_len\":{},\"buf_ptr\":{}}}",
+        command, args.len(), args.as_ptr() as usize);
+    wasi::path_readlink(constants::PWD_DESC, &call, output.as_mut_ptr(), output.len())
+}
+
+pub fn fd_subscription(userdata: wasi::Userdata, type_: wasi::Eventtype, fd: wasi::Fd) -> wasi::Subscription {
+    let readwrite = wasi::SubscriptionFdReadwrite { file_descriptor: fd };
+    wasi::Subscription {
+        userdata,
+        u: wasi::SubscriptionU {
+            tag: type_.raw(),
+            u: if type_ == wasi::EVENTTYPE_FD_READ {
+                wasi::SubscriptionUU { fd_read: readwrite }
+            } else {
+                wasi::SubscriptionUU { fd_write: readwrite }
+            },
+        },
+    }
+}
+
+// timeout is relative, in nanoseconds
+pub fn clock_subscription(userdata: wasi::Userdata, timeout: wasi::Timestamp) -> wasi::Subscription {
+    wasi::Subscription {
+        userdata,
+        u: wasi::SubscriptionU {
+            tag: wasi::EVENTTYPE_CLOCK.raw(),
+            u: wasi::SubscriptionUU {
+                clock: wasi::SubscriptionClock {
+                    id: wasi::CLOCKID_MONOTONIC,
+                    timeout,
+                    precision: 0,
+                    flags: 0,
+                },
+            },
+        },
+    }
+}
+
+// returns events that occurred
+pub unsafe fn poll(subscriptions: &[wasi::Subscription]) -> Result<Vec<wasi::Event>, wasi::Errno> {
+    let mut events: Vec<wasi::Event> = vec![std::mem::zeroed(); subscriptions.len()];
+    let n = wasi::poll_oneoff(subscriptions.as_ptr(), events.as_mut_ptr(), subscriptions.len())?;
+    events.truncate(n);
+    Ok(events)
+}
+
+pub unsafe fn readlink(dir: wasi::Fd, path: &str) -> Result<String, wasi::Errno> {
+    let mut buf = [0u8; 1024];
+    let n = wasi::path_readlink(dir, path, buf.as_mut_ptr(), buf.len())?;
+    Ok(String::from_utf8_lossy(&buf[..n]).into_owned())
+}
+
+// creates a FIFO that is removed together with the fixture, returns its path relative to PWD_DESC
+pub fn make_fifo(t: &mut Checks, fixture: &Fixture) -> Option<String> {
+    let path = fixture.dev_path("fifo");
+    let abs_path = fixture.preopen_path(&path);
+    t.success(&call!("mknod", abs_path, FIFO_DEV), wasi_ext_lib::mknod(&abs_path, FIFO_DEV))?;
+    Some(path)
+}
+
+// opens the end of a FIFO made by make_fifo given by rights
+pub unsafe fn open_end(t: &mut Checks, path: &str, rights: wasi::Rights) -> Option<wasi::Fd> {
+    t.success(
+        &call!("path_open", constants::PWD_DESC, 0, path, 0, rights, 0, 0),
+        wasi::path_open(constants::PWD_DESC, 0, path, 0, rights, 0, 0))
+}